@@ -0,0 +1,618 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+
+use crate::game::common::Team;
+use crate::game::protocol::Protocol;
+use crate::game::result::GameResult;
+use crate::game::server_side_message::ServerSideMessage;
+use crate::game::state::State;
+
+use super::player::Player;
+
+// The server measures its limit from sending the move request.
+pub const DEFAULT_TIME_LIMIT_IN_MILLIS: u64 = 2000;
+// Time reserved for the move to travel back, the player is asked to finish this early.
+pub const DEFAULT_SAFETY_MARGIN_IN_MILLIS: u64 = 150;
+// If the player has not finished this long before the limit, the fallback move is sent.
+pub const DEFAULT_EMERGENCY_MARGIN_IN_MILLIS: u64 = 50;
+
+// Elapsed times of the stages of answering a move request, measured from receiving it.
+struct StageTimer {
+    received_at: Instant,
+    stages: Vec<(&'static str, u128)>,
+}
+
+impl StageTimer {
+    fn new(received_at: Instant) -> Self {
+        Self {
+            received_at,
+            stages: vec![],
+        }
+    }
+
+    fn mark(&mut self, stage: &'static str) {
+        self.stages
+            .push((stage, self.received_at.elapsed().as_millis()));
+    }
+}
+
+impl std::fmt::Display for StageTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stages: Vec<String> = self
+            .stages
+            .iter()
+            .map(|(stage, elapsed)| format!("{stage} after {elapsed}ms"))
+            .collect();
+        write!(f, "{}", stages.join(", "))
+    }
+}
+
+pub struct GameClient {
+    protocol: Protocol,
+    time_limit: Duration,
+    safety_margin: Duration,
+    emergency_margin: Duration,
+    // Deadlines start when the move request arrived, or when it is handled if arrival times are meaningless
+    deadline_from_arrival: bool,
+    current_state: Option<State>,
+}
+
+impl GameClient {
+    pub fn new(protocol: Protocol) -> Self {
+        Self {
+            protocol,
+            time_limit: Duration::from_millis(DEFAULT_TIME_LIMIT_IN_MILLIS),
+            safety_margin: Duration::from_millis(DEFAULT_SAFETY_MARGIN_IN_MILLIS),
+            emergency_margin: Duration::from_millis(DEFAULT_EMERGENCY_MARGIN_IN_MILLIS),
+            deadline_from_arrival: true,
+            current_state: None,
+        }
+    }
+
+    pub fn with_time_limit_in_millis(mut self, time_limit_in_millis: u64) -> Self {
+        self.time_limit = Duration::from_millis(time_limit_in_millis);
+        self
+    }
+
+    pub fn with_safety_margin_in_millis(mut self, safety_margin_in_millis: u64) -> Self {
+        self.safety_margin = Duration::from_millis(safety_margin_in_millis);
+        self
+    }
+
+    pub fn with_emergency_margin_in_millis(mut self, emergency_margin_in_millis: u64) -> Self {
+        self.emergency_margin = Duration::from_millis(emergency_margin_in_millis);
+        self
+    }
+
+    // For messages that were not sent live, like a replayed transcript that arrives in a single read.
+    pub fn with_deadlines_from_handling(mut self) -> Self {
+        self.deadline_from_arrival = false;
+        self
+    }
+
+    pub fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+
+    // Plays games until the server leaves the room, returning the amount of finished games and the player.
+    pub fn run<P: Player + Send + 'static>(&mut self, player: P) -> anyhow::Result<(u32, P)> {
+        let mut player = PlayerThread::new(player);
+        let mut finished_games = 0;
+        while let Some(message) = self.protocol.read_server_side_message()? {
+            match message {
+                ServerSideMessage::Welcome(own_team) => {
+                    println!("Own team: {:?}", own_team);
+                    self.protocol.own_team = Some(own_team);
+                    self.current_state = None;
+                    player.notify(Notification::Welcome(own_team))?;
+                }
+                ServerSideMessage::Memento(state) => {
+                    println!("Current state:\n{}", state);
+                    player.notify(Notification::State(state.clone()))?;
+                    self.current_state = Some(state);
+                }
+                ServerSideMessage::MoveRequest => match player.take_idle()? {
+                    Some(idle_player) => {
+                        let choosing = self.handle_move_request(idle_player)?;
+                        player.start_choosing(choosing);
+                    }
+                    None => self.handle_move_request_while_choosing()?,
+                },
+                ServerSideMessage::Result(result) => {
+                    println!("Own team: {:?}", self.protocol.own_team);
+                    println!("Result: {:?}", result);
+                    player.notify(Notification::Result(result))?;
+                    self.current_state = None;
+                    finished_games += 1;
+                }
+            }
+        }
+        Ok((finished_games, player.into_player()?))
+    }
+
+    // The player is still choosing the last move, so the first possible move is sent right away.
+    fn handle_move_request_while_choosing(&mut self) -> anyhow::Result<()> {
+        println!("Got move request while the player is still choosing, using first possible move");
+        let state = self
+            .current_state
+            .as_ref()
+            .context("Got move request before receiving any state")?
+            .with_moveless_player_skipped()?;
+        let sent_move = state
+            .possible_moves()
+            .next()
+            .context("No possible move to fall back to")?;
+        println!("Sending move {:?}", sent_move);
+        self.protocol.send_move(sent_move)
+    }
+
+    // Returns as soon as a move has been sent. The player keeps choosing on the returned thread
+    // if it overran the emergency deadline, so a hanging player can't stop the client from reading messages.
+    fn handle_move_request<P: Player + Send + 'static>(
+        &mut self,
+        mut player: P,
+    ) -> anyhow::Result<JoinHandle<P>> {
+        println!("Got move request");
+        let received_at = self
+            .protocol
+            .connection
+            .last_message_received_at()
+            .filter(|_| self.deadline_from_arrival)
+            .unwrap_or_else(Instant::now);
+        let deadline = received_at + self.time_limit.saturating_sub(self.safety_margin);
+        let emergency_deadline =
+            received_at + self.time_limit.saturating_sub(self.emergency_margin);
+        let mut timer = StageTimer::new(received_at);
+        timer.mark("handling started");
+
+        let state = self
+            .current_state
+            .as_ref()
+            .context("Got move request before receiving any state")?
+            .with_moveless_player_skipped()?;
+        let fallback_move = player.fallback_move(&state)?;
+        timer.mark("state prepared");
+
+        let (sender, receiver) = mpsc::channel();
+        let choosing = thread::spawn(move || {
+            // The receiver is gone if the fallback move has been sent already
+            let _ = sender.send(player.choose_move(&state, deadline));
+            player
+        });
+
+        let remaining_time = emergency_deadline.saturating_duration_since(Instant::now());
+        let sent_move = match receiver.recv_timeout(remaining_time) {
+            Ok(Ok(chosen_move)) => chosen_move,
+            Ok(Err(error)) => {
+                println!("Choosing a move failed, using fallback move: {:#}", error);
+                fallback_move
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                println!("Choosing a move overran the deadline, using fallback move");
+                fallback_move
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                println!("Player stopped without choosing a move, using fallback move");
+                fallback_move
+            }
+        };
+        timer.mark("move chosen");
+
+        println!("Sending move {:?}", sent_move);
+        self.protocol.send_move(sent_move)?;
+        timer.mark("move sent");
+        println!("Timing: {}", timer);
+        Ok(choosing)
+    }
+}
+
+// Messages for a player that is still choosing, they are delivered in order once it has returned.
+enum Notification {
+    Welcome(Team),
+    State(State),
+    Result(GameResult),
+}
+
+impl Notification {
+    fn deliver(self, player: &mut impl Player) {
+        match self {
+            Self::Welcome(own_team) => player.on_welcome(own_team),
+            Self::State(state) => player.on_state(&state),
+            Self::Result(result) => player.on_result(&result),
+        }
+    }
+}
+
+// The player is moved onto its own thread while choosing a move.
+// A thread that overran the deadline is never waited for until the server has left.
+struct PlayerThread<P> {
+    player: Option<P>,
+    choosing: Option<JoinHandle<P>>,
+    pending: VecDeque<Notification>,
+}
+
+impl<P: Player> PlayerThread<P> {
+    fn new(player: P) -> Self {
+        Self {
+            player: Some(player),
+            choosing: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn start_choosing(&mut self, choosing: JoinHandle<P>) {
+        self.choosing = Some(choosing);
+    }
+
+    fn join(&mut self) -> anyhow::Result<()> {
+        if let Some(choosing) = self.choosing.take() {
+            let mut player = choosing
+                .join()
+                .map_err(|_| anyhow::anyhow!("Player thread panicked while choosing a move"))?;
+            for notification in self.pending.drain(..) {
+                notification.deliver(&mut player);
+            }
+            self.player = Some(player);
+        }
+        Ok(())
+    }
+
+    // Returns None while the player is still choosing.
+    fn idle_player(&mut self) -> anyhow::Result<Option<&mut P>> {
+        if self
+            .choosing
+            .as_ref()
+            .is_some_and(|choosing| choosing.is_finished())
+        {
+            self.join()?;
+        }
+        Ok(self.player.as_mut())
+    }
+
+    fn notify(&mut self, notification: Notification) -> anyhow::Result<()> {
+        match self.idle_player()? {
+            Some(player) => notification.deliver(player),
+            None => self.pending.push_back(notification),
+        }
+        Ok(())
+    }
+
+    fn take_idle(&mut self) -> anyhow::Result<Option<P>> {
+        self.idle_player()?;
+        Ok(self.player.take())
+    }
+
+    fn into_player(mut self) -> anyhow::Result<P> {
+        self.join()?;
+        self.player.context("Player is gone")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::game::board::{Board, FieldState};
+    use crate::game::common::Team;
+    use crate::game::moves::Move;
+    use crate::game::result::GameResult;
+    use crate::game::server::Connection;
+
+    const ROOM_ID: &str = "TEST_ROOM_ID";
+
+    #[derive(Default)]
+    struct FirstMovePlayer {
+        welcomed_teams: Vec<Team>,
+        received_states: u32,
+        received_results: u32,
+    }
+
+    impl Player for FirstMovePlayer {
+        fn on_welcome(&mut self, own_team: Team) {
+            self.welcomed_teams.push(own_team);
+        }
+
+        fn on_state(&mut self, _: &State) {
+            self.received_states += 1;
+        }
+
+        fn choose_move(&mut self, state: &State, _: Instant) -> anyhow::Result<Move> {
+            state.possible_moves().next().context("No possible moves")
+        }
+
+        fn on_result(&mut self, _: &GameResult) {
+            self.received_results += 1;
+        }
+    }
+
+    fn welcome_message(team: &str) -> String {
+        format!(
+            r#"<room roomId="{ROOM_ID}"><data class="welcomeMessage" color="{team}"></data></room>"#
+        )
+    }
+
+    fn one_fish_memento_message() -> String {
+        let row = "<list>".to_string() + &"<field>1</field>".repeat(8) + "</list>";
+        format!(
+            r#"<room roomId="{ROOM_ID}"><data class="memento"><state turn="0"><startTeam>ONE</startTeam><board>{}</board><fishes><int>0</int><int>0</int></fishes></state></data></room>"#,
+            row.repeat(8)
+        )
+    }
+
+    fn move_request_message() -> String {
+        format!(r#"<room roomId="{ROOM_ID}"><data class="moveRequest"></data></room>"#)
+    }
+
+    fn result_message() -> String {
+        format!(
+            r#"<room roomId="{ROOM_ID}">
+            <data class="result">
+                <definition>
+                    <fragment name="Siegpunkte">
+                        <aggregation>SUM</aggregation>
+                        <relevantForRanking>true</relevantForRanking>
+                    </fragment>
+                    <fragment name="∅ Punkte">
+                        <aggregation>AVERAGE</aggregation>
+                        <relevantForRanking>true</relevantForRanking>
+                    </fragment>
+                </definition>
+                <scores>
+                    <entry>
+                        <player name="A Team" team="ONE"/>
+                        <score cause="REGULAR" reason="">
+                            <part>2</part>
+                            <part>27</part>
+                        </score>
+                    </entry>
+                    <entry>
+                        <player name="B Team" team="TWO"/>
+                        <score cause="REGULAR" reason="">
+                            <part>0</part>
+                            <part>15</part>
+                        </score>
+                    </entry>
+                </scores>
+                <winner team="ONE"/>
+            </data>
+        </room>"#
+        )
+    }
+
+    fn read_sent_room(stream: &mut impl Read) -> String {
+        let mut received = vec![];
+        let mut byte = [0u8; 1];
+        while !received.ends_with(b"</room>") {
+            stream.read_exact(&mut byte).unwrap();
+            received.push(byte[0]);
+        }
+        String::from_utf8(received).unwrap()
+    }
+
+    #[test]
+    fn game_client_plays_consecutive_games_until_server_leaves_room() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut sent_rooms = vec![];
+            for team in ["ONE", "TWO"] {
+                let messages =
+                    welcome_message(team) + &one_fish_memento_message() + &move_request_message();
+                stream.write_all(messages.as_bytes()).unwrap();
+                sent_rooms.push(read_sent_room(&mut stream));
+                stream.write_all(result_message().as_bytes()).unwrap();
+            }
+            let left = format!(r#"<left roomId="{ROOM_ID}"/></protocol>"#);
+            stream.write_all(left.as_bytes()).unwrap();
+            sent_rooms
+        });
+
+        let mut protocol = Protocol::from_connection(Connection::connect(&address).unwrap());
+        protocol.room_id = ROOM_ID.to_string();
+        let mut client = GameClient::new(protocol);
+        let (finished_games, player) = client.run(FirstMovePlayer::default()).unwrap();
+
+        assert_eq!(2, finished_games);
+        assert_eq!(vec![Team::One, Team::Two], player.welcomed_teams);
+        assert_eq!(2, player.received_states);
+        assert_eq!(2, player.received_results);
+        let expected_room = format!(
+            r#"<room roomId="{ROOM_ID}"><data class="move"><to x="0" y="0"></to></data></room>"#
+        );
+        assert_eq!(
+            vec![expected_room.clone(), expected_room],
+            server.join().unwrap()
+        );
+    }
+
+    fn serve_single_move_request(listener: TcpListener) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let messages =
+                welcome_message("ONE") + &one_fish_memento_message() + &move_request_message();
+            stream.write_all(messages.as_bytes()).unwrap();
+            let sent_room = read_sent_room(&mut stream);
+            let left = format!(r#"<left roomId="{ROOM_ID}"/></protocol>"#);
+            stream.write_all(left.as_bytes()).unwrap();
+            sent_room
+        })
+    }
+
+    fn connect_client(address: &str) -> GameClient {
+        let mut protocol = Protocol::from_connection(Connection::connect(address).unwrap());
+        protocol.room_id = ROOM_ID.to_string();
+        GameClient::new(protocol)
+    }
+
+    // Takes long to process states and remembers the deadline it was given.
+    #[derive(Default)]
+    struct SlowStatePlayer {
+        state_processed_at: Option<Instant>,
+        deadline: Option<Instant>,
+    }
+
+    impl Player for SlowStatePlayer {
+        fn on_state(&mut self, _: &State) {
+            thread::sleep(Duration::from_millis(100));
+            self.state_processed_at = Some(Instant::now());
+        }
+
+        fn choose_move(&mut self, state: &State, deadline: Instant) -> anyhow::Result<Move> {
+            self.deadline = Some(deadline);
+            state.possible_moves().next().context("No possible moves")
+        }
+    }
+
+    #[test]
+    fn deadline_is_anchored_at_receiving_the_move_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = serve_single_move_request(listener);
+
+        let mut client = connect_client(&address)
+            .with_time_limit_in_millis(1000)
+            .with_safety_margin_in_millis(100);
+        let (_, player) = client.run(SlowStatePlayer::default()).unwrap();
+        server.join().unwrap();
+
+        // The move request arrived before the slow state processing started
+        let latest_expected_deadline =
+            player.state_processed_at.unwrap() + Duration::from_millis(800);
+        assert!(player.deadline.unwrap() <= latest_expected_deadline);
+    }
+
+    struct OverrunningPlayer;
+
+    impl Player for OverrunningPlayer {
+        fn choose_move(&mut self, state: &State, deadline: Instant) -> anyhow::Result<Move> {
+            thread::sleep(
+                deadline.saturating_duration_since(Instant::now()) + Duration::from_millis(300),
+            );
+            state.possible_moves().last().context("No possible moves")
+        }
+    }
+
+    #[test]
+    fn fallback_move_is_sent_when_choosing_overruns() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = serve_single_move_request(listener);
+
+        let sent_at = Instant::now();
+        let mut client = connect_client(&address)
+            .with_time_limit_in_millis(300)
+            .with_safety_margin_in_millis(100)
+            .with_emergency_margin_in_millis(50);
+        client.run(OverrunningPlayer).unwrap();
+
+        let expected_room = format!(
+            r#"<room roomId="{ROOM_ID}"><data class="move"><to x="0" y="0"></to></data></room>"#
+        );
+        assert_eq!(expected_room, server.join().unwrap());
+        assert!(sent_at.elapsed() >= Duration::from_millis(250));
+    }
+
+    struct HangingPlayer;
+
+    impl Player for HangingPlayer {
+        fn choose_move(&mut self, state: &State, _: Instant) -> anyhow::Result<Move> {
+            thread::sleep(Duration::from_secs(5));
+            state.possible_moves().last().context("No possible moves")
+        }
+    }
+
+    #[test]
+    fn move_request_is_answered_without_waiting_for_hanging_player() {
+        let connection = Connection::from_streams(Cursor::new(vec![]), std::io::sink());
+        let mut protocol = Protocol::from_connection(connection);
+        protocol.room_id = ROOM_ID.to_string();
+        let mut client = GameClient::new(protocol)
+            .with_time_limit_in_millis(300)
+            .with_safety_margin_in_millis(100)
+            .with_emergency_margin_in_millis(50);
+        client.current_state = Some(State::from_initial_board_with_start_team_one(Board::fill(
+            FieldState::Fish(1),
+        )));
+
+        let started_at = Instant::now();
+        let choosing = client.handle_move_request(HangingPlayer).unwrap();
+        let elapsed = started_at.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(250));
+        assert!(elapsed < Duration::from_millis(750));
+        assert!(!choosing.is_finished());
+    }
+
+    // Hangs far past the deadline of the first move request and records the order of its callbacks.
+    #[derive(Default)]
+    struct HangingOncePlayer {
+        events: Vec<&'static str>,
+    }
+
+    impl Player for HangingOncePlayer {
+        fn on_state(&mut self, _: &State) {
+            self.events.push("state");
+        }
+
+        fn choose_move(&mut self, state: &State, _: Instant) -> anyhow::Result<Move> {
+            self.events.push("choose");
+            if self.events.len() == 2 {
+                thread::sleep(Duration::from_millis(1500));
+            }
+            state.possible_moves().last().context("No possible moves")
+        }
+
+        fn on_result(&mut self, _: &GameResult) {
+            self.events.push("result");
+        }
+    }
+
+    #[test]
+    fn client_keeps_answering_move_requests_while_player_hangs() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut answer_times = vec![];
+            let messages = welcome_message("ONE") + &one_fish_memento_message();
+            stream.write_all(messages.as_bytes()).unwrap();
+            for _ in 0..2 {
+                let requested_at = Instant::now();
+                stream.write_all(move_request_message().as_bytes()).unwrap();
+                read_sent_room(&mut stream);
+                answer_times.push(requested_at.elapsed());
+                stream
+                    .write_all(one_fish_memento_message().as_bytes())
+                    .unwrap();
+            }
+            let messages = result_message() + &format!(r#"<left roomId="{ROOM_ID}"/></protocol>"#);
+            stream.write_all(messages.as_bytes()).unwrap();
+            answer_times
+        });
+
+        let mut client = connect_client(&address)
+            .with_time_limit_in_millis(300)
+            .with_safety_margin_in_millis(100)
+            .with_emergency_margin_in_millis(50);
+        let (finished_games, player) = client.run(HangingOncePlayer::default()).unwrap();
+        let answer_times = server.join().unwrap();
+
+        assert_eq!(1, finished_games);
+        assert!(answer_times
+            .iter()
+            .all(|answer_time| *answer_time < Duration::from_millis(300)));
+        // The second move request came while the player was hanging, the missed messages follow in order
+        assert_eq!(
+            vec!["state", "choose", "state", "state", "result"],
+            player.events
+        );
+    }
+}
@@ -0,0 +1,6 @@
+pub mod game_client;
+pub mod player;
+pub mod replay;
+
+pub use game_client::GameClient;
+pub use player::Player;
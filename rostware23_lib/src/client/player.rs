@@ -0,0 +1,32 @@
+use std::time::Instant;
+
+use anyhow::Context;
+
+use crate::game::common::Team;
+use crate::game::moves::Move;
+use crate::game::result::GameResult;
+use crate::game::state::State;
+
+// Callbacks invoked by the GameClient while it drives the protocol.
+// Only choose_move is required, the other notifications default to doing nothing.
+pub trait Player {
+    fn on_welcome(&mut self, _own_team: Team) {}
+
+    fn on_state(&mut self, _state: &State) {}
+
+    // The state is passed with a moveless player already skipped, so it always
+    // has possible moves for the current team. The move has to be chosen before the deadline.
+    // The client doesn't wait for a player that overruns it. Until it has returned, the other callbacks
+    // are queued and further move requests are answered with the first possible move.
+    fn choose_move(&mut self, state: &State, deadline: Instant) -> anyhow::Result<Move>;
+
+    // Sent instead when choose_move overruns or fails, so it has to be cheap to compute.
+    fn fallback_move(&mut self, state: &State) -> anyhow::Result<Move> {
+        state
+            .possible_moves()
+            .next()
+            .context("No possible move to fall back to")
+    }
+
+    fn on_result(&mut self, _result: &GameResult) {}
+}
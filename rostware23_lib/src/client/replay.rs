@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::time::Instant;
+
+use crate::game::common::Team;
+use crate::game::moves::Move;
+use crate::game::protocol::Protocol;
+use crate::game::result::GameResult;
+use crate::game::server::Connection;
+use crate::game::state::State;
+use crate::game::transcript::{TranscriptDirection, TranscriptEntry};
+use crate::xml;
+use crate::xml::data::DataClass;
+
+use super::game_client::GameClient;
+use super::player::Player;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveComparison {
+    pub state: State,
+    pub recorded_move: Option<Move>,
+    // The error message if the player failed to choose a move
+    pub chosen_move: Result<Move, String>,
+}
+
+impl MoveComparison {
+    pub fn matches(&self) -> bool {
+        match (&self.recorded_move, &self.chosen_move) {
+            (Some(recorded_move), Ok(chosen_move)) => recorded_move == chosen_move,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub comparisons: Vec<MoveComparison>,
+}
+
+impl ReplayReport {
+    pub fn matching_move_count(&self) -> usize {
+        self.comparisons
+            .iter()
+            .filter(|comparison| comparison.matches())
+            .count()
+    }
+
+    // Returns the index of the first move request at which the player diverged together with its comparison.
+    pub fn first_divergence(&self) -> Option<(usize, &MoveComparison)> {
+        self.comparisons
+            .iter()
+            .enumerate()
+            .find(|(_, comparison)| !comparison.matches())
+    }
+}
+
+fn recorded_moves(entries: &[TranscriptEntry]) -> VecDeque<Move> {
+    entries
+        .iter()
+        .filter(|entry| entry.direction == TranscriptDirection::Sent)
+        .filter_map(|entry| xml::deserialize::<xml::room::Room>(&entry.message).ok())
+        .filter(|room| room.data.class == DataClass::Move)
+        .filter_map(|room| room.data.sent_move)
+        .map(Move::from)
+        .collect()
+}
+
+// Compares the moves chosen by the wrapped player against the moves of the transcript.
+struct ComparingPlayer<P: Player> {
+    player: P,
+    recorded_moves: VecDeque<Move>,
+    report: ReplayReport,
+}
+
+impl<P: Player> Player for ComparingPlayer<P> {
+    fn on_welcome(&mut self, own_team: Team) {
+        self.player.on_welcome(own_team);
+    }
+
+    fn on_state(&mut self, state: &State) {
+        self.player.on_state(state);
+    }
+
+    fn choose_move(&mut self, state: &State, deadline: Instant) -> anyhow::Result<Move> {
+        // Taken before choosing, so a failed move request doesn't shift the later comparisons
+        let recorded_move = self.recorded_moves.pop_front();
+        let chosen_move = self.player.choose_move(state, deadline);
+        self.report.comparisons.push(MoveComparison {
+            state: state.clone(),
+            recorded_move,
+            chosen_move: chosen_move
+                .as_ref()
+                .map(Move::clone)
+                .map_err(|error| format!("{error:#}")),
+        });
+        chosen_move
+    }
+
+    fn on_result(&mut self, result: &GameResult) {
+        self.player.on_result(result);
+    }
+}
+
+// Feeds the received messages of a transcript through the protocol as if they came from the server
+// and lets the player answer every move request of the recorded session.
+// All messages are read at once, so every move request gets the full time limit from being handled.
+pub fn replay_transcript<P: Player + Send + 'static>(
+    entries: &[TranscriptEntry],
+    player: P,
+) -> anyhow::Result<ReplayReport> {
+    let received: String = entries
+        .iter()
+        .filter(|entry| entry.direction == TranscriptDirection::Received)
+        .map(|entry| entry.message.as_str())
+        .collect();
+    let connection = Connection::from_streams(Cursor::new(received.into_bytes()), std::io::sink());
+    let mut protocol = Protocol::from_connection(connection);
+    protocol.read_message_after_join()?;
+
+    let comparing_player = ComparingPlayer {
+        player,
+        recorded_moves: recorded_moves(entries),
+        report: ReplayReport::default(),
+    };
+    let (_, comparing_player) = GameClient::new(protocol)
+        .with_deadlines_from_handling()
+        .run(comparing_player)?;
+    Ok(comparing_player.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use anyhow::Context;
+
+    use super::*;
+    use crate::game::common::Coordinate;
+
+    const ROOM_ID: &str = "TEST_ROOM_ID";
+
+    struct FirstMovePlayer;
+
+    impl Player for FirstMovePlayer {
+        fn choose_move(&mut self, state: &State, _: Instant) -> anyhow::Result<Move> {
+            state.possible_moves().next().context("No possible moves")
+        }
+    }
+
+    fn entry(direction: TranscriptDirection, message: String) -> TranscriptEntry {
+        TranscriptEntry {
+            direction,
+            elapsed_millis: 0,
+            message,
+        }
+    }
+
+    fn sent_place_move(x: u64, y: u64) -> TranscriptEntry {
+        entry(
+            TranscriptDirection::Sent,
+            format!(
+                r#"<room roomId="{ROOM_ID}"><data class="move"><to x="{x}" y="{y}"></to></data></room>"#
+            ),
+        )
+    }
+
+    fn transcript_with_moves(sent_moves: Vec<TranscriptEntry>) -> Vec<TranscriptEntry> {
+        let row = "<list>".to_string() + &"<field>1</field>".repeat(8) + "</list>";
+        let memento = format!(
+            r#"<room roomId="{ROOM_ID}"><data class="memento"><state turn="0"><startTeam>ONE</startTeam><board>{}</board><fishes><int>0</int><int>0</int></fishes></state></data></room>"#,
+            row.repeat(8)
+        );
+        let move_request =
+            format!(r#"<room roomId="{ROOM_ID}"><data class="moveRequest"></data></room>"#);
+
+        let mut entries = vec![
+            entry(TranscriptDirection::Sent, "<protocol>".to_string()),
+            entry(TranscriptDirection::Sent, "<join />".to_string()),
+            entry(
+                TranscriptDirection::Received,
+                format!("<protocol>\n  <joined roomId=\"{ROOM_ID}\"/>"),
+            ),
+            entry(
+                TranscriptDirection::Received,
+                format!(
+                    r#"<room roomId="{ROOM_ID}"><data class="welcomeMessage" color="ONE"></data></room>"#
+                ),
+            ),
+        ];
+        for sent_move in sent_moves {
+            entries.push(entry(TranscriptDirection::Received, memento.clone()));
+            entries.push(entry(TranscriptDirection::Received, move_request.clone()));
+            entries.push(sent_move);
+        }
+        entries.push(entry(
+            TranscriptDirection::Received,
+            format!(r#"<left roomId="{ROOM_ID}"/>"#),
+        ));
+        entries
+    }
+
+    #[test]
+    fn recorded_moves_are_read_from_sent_move_rooms() {
+        let entries = transcript_with_moves(vec![sent_place_move(6, 2)]);
+        let expected = VecDeque::from([Move::Place(Coordinate::new(6, 2))]);
+        assert_eq!(expected, recorded_moves(&entries));
+    }
+
+    #[test]
+    fn replaying_transcript_with_same_moves_has_no_divergence() {
+        let entries = transcript_with_moves(vec![sent_place_move(0, 0), sent_place_move(0, 0)]);
+        let report = replay_transcript(&entries, FirstMovePlayer).unwrap();
+        assert_eq!(2, report.comparisons.len());
+        assert_eq!(2, report.matching_move_count());
+        assert!(report.first_divergence().is_none());
+    }
+
+    #[test]
+    fn replaying_transcript_with_other_moves_reports_first_divergence() {
+        let entries = transcript_with_moves(vec![sent_place_move(0, 0), sent_place_move(2, 0)]);
+        let report = replay_transcript(&entries, FirstMovePlayer).unwrap();
+        let (index, divergence) = report.first_divergence().unwrap();
+        assert_eq!(1, index);
+        assert_eq!(
+            Some(Move::Place(Coordinate::new(2, 0))),
+            divergence.recorded_move
+        );
+        assert_eq!(
+            Ok(Move::Place(Coordinate::new(0, 0))),
+            divergence.chosen_move
+        );
+    }
+
+    // Takes a while for every move and only plays the recorded first move if it got most of the time limit.
+    struct SlowPlayer;
+
+    impl Player for SlowPlayer {
+        fn choose_move(&mut self, state: &State, deadline: Instant) -> anyhow::Result<Move> {
+            let remaining_time = deadline.saturating_duration_since(Instant::now());
+            thread::sleep(Duration::from_millis(500));
+            if remaining_time >= Duration::from_millis(1500) {
+                state.possible_moves().next().context("No possible moves")
+            } else {
+                state.possible_moves().last().context("No possible moves")
+            }
+        }
+    }
+
+    #[test]
+    fn every_replayed_move_request_gets_the_full_time_limit() {
+        let entries = transcript_with_moves(vec![
+            sent_place_move(0, 0),
+            sent_place_move(0, 0),
+            sent_place_move(0, 0),
+        ]);
+        let report = replay_transcript(&entries, SlowPlayer).unwrap();
+        assert_eq!(3, report.comparisons.len());
+        assert_eq!(3, report.matching_move_count());
+    }
+
+    // Fails to choose the first move.
+    #[derive(Default)]
+    struct FailingOncePlayer {
+        failed: bool,
+    }
+
+    impl Player for FailingOncePlayer {
+        fn choose_move(&mut self, state: &State, _: Instant) -> anyhow::Result<Move> {
+            if !self.failed {
+                self.failed = true;
+                anyhow::bail!("Search failed");
+            }
+            state.possible_moves().next().context("No possible moves")
+        }
+    }
+
+    #[test]
+    fn failed_move_request_is_compared_against_its_own_recorded_move() {
+        let entries = transcript_with_moves(vec![sent_place_move(2, 0), sent_place_move(0, 0)]);
+        let report = replay_transcript(&entries, FailingOncePlayer::default()).unwrap();
+        assert_eq!(2, report.comparisons.len());
+        let (index, divergence) = report.first_divergence().unwrap();
+        assert_eq!(0, index);
+        assert_eq!(
+            Some(Move::Place(Coordinate::new(2, 0))),
+            divergence.recorded_move
+        );
+        assert_eq!(Err("Search failed".to_string()), divergence.chosen_move);
+        assert!(report.comparisons[1].matches());
+    }
+}
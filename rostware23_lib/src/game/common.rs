@@ -82,6 +82,16 @@ impl Coordinate {
         Self((x as f64 / 2.0).ceil() as u64 - y % 2, y)
     }
 
+    // Index of the field in 0..64, counting row by row
+    pub fn to_field_index(&self) -> u64 {
+        self.y() * 8 + self.x() / 2
+    }
+
+    pub fn from_field_index(index: u64) -> Self {
+        let y = index / 8;
+        Self((index % 8) * 2 + y % 2, y)
+    }
+
     pub fn is_valid(&self) -> bool {
         let x = self.x();
         let y = self.y();
@@ -93,6 +103,15 @@ impl Coordinate {
 mod tests {
     use super::*;
 
+    #[test]
+    fn field_index_to_coordinate_results_in_previous_value() {
+        for index in 0..64 {
+            let coordinate = Coordinate::from_field_index(index);
+            assert!(coordinate.is_valid());
+            assert_eq!(index, coordinate.to_field_index());
+        }
+    }
+
     #[test]
     fn odd_r_to_doubled_to_odd_r_results_in_previous_value() {
         let coord = Coordinate::new(6, 2);
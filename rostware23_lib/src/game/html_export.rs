@@ -0,0 +1,178 @@
+// Exports a game record as a single HTML page that animates the game, with one SVG image per
+// position and controls for stepping through them.
+
+use std::fmt::Write;
+
+use super::record::GameRecord;
+use super::state::State;
+use super::svg::{escape_xml, team_colour, SvgRenderer};
+
+const FRAME_INTERVAL_IN_MILLIS: u32 = 800;
+
+const SCRIPT: &str = r#"
+const frames = document.querySelectorAll(".frame");
+const slider = document.getElementById("position");
+let position = 0;
+let timer = null;
+function show(next) {
+    position = Math.max(0, Math.min(frames.length - 1, next));
+    frames.forEach((frame, index) => frame.hidden = index !== position);
+    slider.value = position;
+}
+function togglePlay() {
+    if (timer !== null) {
+        clearInterval(timer);
+        timer = null;
+        return;
+    }
+    if (position === frames.length - 1) {
+        show(0);
+    }
+    timer = setInterval(() => {
+        show(position + 1);
+        if (position === frames.length - 1) {
+            togglePlay();
+        }
+    }, INTERVAL);
+}
+slider.addEventListener("input", () => show(Number(slider.value)));
+document.addEventListener("keydown", (event) => {
+    if (event.key === "ArrowLeft") show(position - 1);
+    if (event.key === "ArrowRight") show(position + 1);
+    if (event.key === " ") { event.preventDefault(); togglePlay(); }
+});
+show(0);
+"#;
+
+fn frame_caption(record: &GameRecord, states: &[State], position: usize) -> anyhow::Result<String> {
+    let state = &states[position];
+    let fish = format!("{}:{}", state.team_one_fish, state.team_two_fish);
+    if position == record.moves.len() && state.is_over() {
+        let result = match state.get_result()?.winner {
+            Some(winner) => format!("{} wins", record.team_name(winner)),
+            None => "draw".to_string(),
+        };
+        return Ok(format!("Game over, {}, fish {}", result, fish));
+    }
+    if position == 0 {
+        return Ok(format!("Start, fish {}", fish));
+    }
+    let played_state = &states[position - 1];
+    Ok(format!(
+        "Move {}: {} played {}, fish {}",
+        position,
+        record.team_name(played_state.current_team()),
+        record.moves[position - 1].to_notation(),
+        fish
+    ))
+}
+
+// Every position is rendered with the given renderer and an arrow for the move leading to it.
+pub fn export_html(record: &GameRecord, renderer: &SvgRenderer) -> anyhow::Result<String> {
+    let states = record.states()?;
+    let title = format!(
+        "{} vs {}",
+        escape_xml(&record.team_one),
+        escape_xml(&record.team_two)
+    );
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(
+        html,
+        "<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>",
+        title
+    )?;
+    writeln!(
+        html,
+        "<style>body {{ font-family: sans-serif; text-align: center; }} .controls {{ margin: 1em; }}</style>\n</head>\n<body>"
+    )?;
+    writeln!(html, "<h1>{}</h1>", title)?;
+    for (position, state) in states.iter().enumerate() {
+        let mut frame_renderer = renderer
+            .clone()
+            .with_caption(&frame_caption(record, &states, position)?)
+            .with_id_prefix(&format!("frame-{}-", position));
+        if position > 0 {
+            let played_state = &states[position - 1];
+            frame_renderer = frame_renderer.with_move(
+                record.moves[position - 1].clone(),
+                team_colour(played_state.current_team()),
+            );
+        }
+        writeln!(
+            html,
+            "<div class=\"frame\" hidden>\n{}\n</div>",
+            frame_renderer.render(&state.board)?
+        )?;
+    }
+    writeln!(html, "<div class=\"controls\">")?;
+    writeln!(html, "<button onclick=\"show(0)\">&#x23EE;</button>")?;
+    writeln!(
+        html,
+        "<button onclick=\"show(position - 1)\">&#x25C0;</button>"
+    )?;
+    writeln!(html, "<button onclick=\"togglePlay()\">&#x23EF;</button>")?;
+    writeln!(
+        html,
+        "<button onclick=\"show(position + 1)\">&#x25B6;</button>"
+    )?;
+    writeln!(
+        html,
+        "<button onclick=\"show(frames.length - 1)\">&#x23ED;</button>"
+    )?;
+    writeln!(
+        html,
+        "<br><input id=\"position\" type=\"range\" min=\"0\" max=\"{}\" value=\"0\">",
+        record.moves.len()
+    )?;
+    writeln!(html, "</div>")?;
+    writeln!(
+        html,
+        "<script>{}</script>",
+        SCRIPT.replace("INTERVAL", &FRAME_INTERVAL_IN_MILLIS.to_string())
+    )?;
+    write!(html, "</body>\n</html>\n")?;
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state_generator::create_any;
+
+    #[test]
+    fn every_position_of_the_record_is_a_frame() {
+        let mut record = GameRecord::new(create_any()).with_team_names("<alpha>", "beta");
+        let mut state = record.initial_state.clone();
+        while !state.is_over() {
+            state = state.with_moveless_player_skipped().unwrap();
+            let possible_move = state.possible_moves().next().unwrap();
+            record.moves.push(possible_move.clone());
+            state.perform_move(possible_move).unwrap();
+        }
+        let html = export_html(&record, &SvgRenderer::new()).unwrap();
+        assert_eq!(
+            record.moves.len() + 1,
+            html.matches("class=\"frame\"").count()
+        );
+        // Place moves are drawn as rings, normal moves as arrows
+        let drawn_moves =
+            html.matches("fill=\"none\"").count() + html.matches("marker-end").count();
+        assert_eq!(record.moves.len(), drawn_moves);
+        assert!(html.contains("&lt;alpha&gt; vs beta"));
+        assert!(html.contains(&format!(
+            "Move 1: &lt;alpha&gt; played {}",
+            record.moves[0].to_notation()
+        )));
+        assert!(html.contains("Game over"));
+        let mut ids: Vec<&str> = html
+            .split("id=\"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .collect();
+        let id_count = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(id_count, ids.len());
+    }
+}
@@ -3,16 +3,21 @@ pub mod board_bitset;
 pub mod board_coordinates;
 pub mod common;
 pub mod direction;
+pub mod html_export;
 pub mod move_generator;
 pub mod moves;
+pub mod notation;
 pub mod penguin;
 pub mod penguin_bitset;
 pub mod penguin_collection;
 pub mod possible_moves;
 pub mod protocol;
+pub mod record;
 pub mod protocol_error;
 pub mod result;
 pub mod server;
 pub mod server_side_message;
 pub mod state;
 pub mod state_generator;
+pub mod svg;
+pub mod transcript;
@@ -0,0 +1,257 @@
+// A stable text notation for coordinates, moves and states.
+//
+// Coordinates are written as the column letter a-h and the row number 1-8 in odd-r layout,
+// so "a1" is the top left field. Place moves are written as their target, normal moves as
+// "from-to", e.g. "c4-e6".
+//
+// States are written as the eight rows of the board separated by '/', followed by the start
+// team, the turn and the fish of team one and two. Every field is one of '0' for an empty
+// field, '1'-'4' for the fish on it, 'G' for a penguin of team one or 'P' for one of team two:
+// "44321000/.../00000000 one 10 12 9"
+
+use anyhow::Context;
+
+use super::board::{Board, FieldState};
+use super::common::*;
+use super::moves::Move;
+use super::state::State;
+
+const PENGUINS_PER_TEAM: usize = 4;
+
+impl Coordinate {
+    pub fn to_notation(&self) -> String {
+        let odd_r = self.clone().doubled_to_odd_r();
+        format!("{}{}", (b'a' + odd_r.x() as u8) as char, odd_r.y() + 1)
+    }
+
+    pub fn from_notation(notation: &str) -> anyhow::Result<Self> {
+        let mut chars = notation.chars();
+        let column = chars
+            .next()
+            .filter(|column| ('a'..='h').contains(column))
+            .with_context(|| format!("Invalid column in coordinate '{}'", notation))?;
+        let row = chars
+            .as_str()
+            .parse::<u64>()
+            .ok()
+            .filter(|row| (1..=BOARD_HEIGHT).contains(row))
+            .with_context(|| format!("Invalid row in coordinate '{}'", notation))?;
+        Ok(Coordinate::new(column as u64 - 'a' as u64, row - 1).odd_r_to_doubled())
+    }
+}
+
+impl Move {
+    pub fn to_notation(&self) -> String {
+        match self {
+            Move::Place(to) => to.to_notation(),
+            Move::Normal { from, to } => format!("{}-{}", from.to_notation(), to.to_notation()),
+        }
+    }
+
+    pub fn from_notation(notation: &str) -> anyhow::Result<Self> {
+        match notation.trim().split_once('-') {
+            Some((from, to)) => Ok(Move::Normal {
+                from: Coordinate::from_notation(from)?,
+                to: Coordinate::from_notation(to)?,
+            }),
+            None => Ok(Move::Place(Coordinate::from_notation(notation.trim())?)),
+        }
+    }
+}
+
+fn field_to_notation(field_state: &FieldState) -> char {
+    match field_state {
+        FieldState::Empty => '0',
+        FieldState::Fish(fish_count) => char::from_digit(*fish_count, 10).unwrap_or('?'),
+        FieldState::Team(Team::One) => 'G',
+        FieldState::Team(Team::Two) => 'P',
+    }
+}
+
+fn team_to_notation(team: Team) -> &'static str {
+    match team {
+        Team::One => "one",
+        Team::Two => "two",
+    }
+}
+
+fn parse_board(notation: &str) -> anyhow::Result<Board> {
+    let rows: Vec<&str> = notation.split('/').collect();
+    if rows.len() != BOARD_HEIGHT as usize {
+        anyhow::bail!("Expected {} rows, got {}", BOARD_HEIGHT, rows.len());
+    }
+    let mut board = Board::empty();
+    let mut penguin_counts = [0; 2];
+    for (y, row) in rows.iter().enumerate() {
+        if row.chars().count() != BOARD_WIDTH as usize {
+            anyhow::bail!(
+                "Expected {} fields in row {}, got '{}'",
+                BOARD_WIDTH,
+                y + 1,
+                row
+            );
+        }
+        for (x, field) in row.chars().enumerate() {
+            let coordinate = Coordinate::new(x as u64, y as u64).odd_r_to_doubled();
+            match field {
+                '0'..='4' => board.set(coordinate, FieldState::Fish(field as u32 - '0' as u32))?,
+                'G' | 'P' => {
+                    let team = if field == 'G' { Team::One } else { Team::Two };
+                    penguin_counts[team as usize] += 1;
+                    board.perform_move(Move::Place(coordinate), team)?;
+                }
+                _ => anyhow::bail!("Invalid field '{}' in row {}", field, y + 1),
+            }
+        }
+    }
+    if penguin_counts
+        .iter()
+        .any(|count| *count > PENGUINS_PER_TEAM)
+    {
+        anyhow::bail!("A team has more than {} penguins", PENGUINS_PER_TEAM);
+    }
+    Ok(board)
+}
+
+impl State {
+    pub fn to_notation(&self) -> String {
+        let rows: Vec<String> = (0..BOARD_HEIGHT)
+            .map(|y| {
+                (0..BOARD_WIDTH)
+                    .map(|x| {
+                        let coordinate = Coordinate::new(x, y).odd_r_to_doubled();
+                        field_to_notation(&self.board.get(coordinate).unwrap_or(FieldState::Empty))
+                    })
+                    .collect()
+            })
+            .collect();
+        format!(
+            "{} {} {} {} {}",
+            rows.join("/"),
+            team_to_notation(self.start_team),
+            self.turn,
+            self.team_one_fish,
+            self.team_two_fish
+        )
+    }
+
+    pub fn from_notation(notation: &str) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = notation.split_whitespace().collect();
+        let [board, start_team, turn, team_one_fish, team_two_fish] = parts[..] else {
+            anyhow::bail!(
+                "Expected board, start team, turn and the fish of both teams in '{}'",
+                notation
+            );
+        };
+        let start_team = match start_team {
+            "one" => Team::One,
+            "two" => Team::Two,
+            _ => anyhow::bail!("Invalid start team '{}'", start_team),
+        };
+        Ok(Self {
+            turn: turn
+                .parse()
+                .with_context(|| format!("Invalid turn '{}'", turn))?,
+            start_team,
+            team_one_fish: team_one_fish
+                .parse()
+                .with_context(|| format!("Invalid fish '{}'", team_one_fish))?,
+            team_two_fish: team_two_fish
+                .parse()
+                .with_context(|| format!("Invalid fish '{}'", team_two_fish))?,
+            board: parse_board(board).context("Invalid board in state notation")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state_generator::create_any;
+
+    #[test]
+    fn coordinates_are_named_by_odd_r_column_and_row() {
+        assert_eq!("a1", Coordinate::new(0, 0).to_notation());
+        assert_eq!("a2", Coordinate::new(1, 1).to_notation());
+        assert_eq!("h8", Coordinate::new(15, 7).to_notation());
+        assert_eq!(
+            Coordinate::new(7, 3),
+            Coordinate::from_notation("d4").unwrap()
+        );
+        assert!(Coordinate::from_notation("i1").is_err());
+        assert!(Coordinate::from_notation("a9").is_err());
+        assert!(Coordinate::from_notation("a").is_err());
+    }
+
+    #[test]
+    fn moves_can_be_written_and_read_again() {
+        let moves = [
+            Move::Place(Coordinate::new(4, 2)),
+            Move::Normal {
+                from: Coordinate::new(1, 1),
+                to: Coordinate::new(9, 1),
+            },
+        ];
+        assert_eq!("c3", moves[0].to_notation());
+        assert_eq!("a2-e2", moves[1].to_notation());
+        for written_move in moves {
+            assert_eq!(
+                written_move,
+                Move::from_notation(&written_move.to_notation()).unwrap()
+            );
+        }
+        assert!(Move::from_notation("a2-").is_err());
+    }
+
+    #[test]
+    fn states_can_be_written_and_read_again() {
+        let mut state = create_any();
+        for _ in 0..12 {
+            let possible_move = state.possible_moves().next().unwrap();
+            state.perform_move(possible_move).unwrap();
+            let notation = state.to_notation();
+            let parsed = State::from_notation(&notation).unwrap();
+            assert_eq!(notation, parsed.to_notation());
+            // Penguins are stored in the order they were placed, so only the moves are compared
+            let sorted_moves = |state: &State| {
+                let mut moves: Vec<String> = state
+                    .possible_moves()
+                    .map(|possible_move| possible_move.to_notation())
+                    .collect();
+                moves.sort();
+                moves
+            };
+            assert_eq!(sorted_moves(&state), sorted_moves(&parsed));
+        }
+    }
+
+    #[test]
+    fn state_notation_lists_fields_row_by_row() {
+        let mut board = Board::empty();
+        board
+            .set(Coordinate::new(2, 0), FieldState::Fish(3))
+            .unwrap();
+        board
+            .perform_move(Move::Place(Coordinate::new(15, 7)), Team::Two)
+            .unwrap();
+        let mut state = State::from_initial_board_with_start_team_one(board);
+        state.team_two_fish = 7;
+        let empty_row = "00000000";
+        let expected = format!(
+            "03000000/{0}/{0}/{0}/{0}/{0}/{0}/0000000P one 0 0 7",
+            empty_row
+        );
+        assert_eq!(expected, state.to_notation());
+    }
+
+    #[test]
+    fn invalid_state_notations_are_rejected() {
+        let valid = State::from_initial_board_with_start_team_one(Board::empty()).to_notation();
+        assert!(State::from_notation(&valid).is_ok());
+        assert!(State::from_notation(&valid.replace("one", "three")).is_err());
+        assert!(State::from_notation(&valid.replacen("00000000", "0000000", 1)).is_err());
+        assert!(State::from_notation(&valid.replacen("00000000", "0000000X", 1)).is_err());
+        assert!(State::from_notation(&valid.replacen("00000000", "GGGGG000", 1)).is_err());
+        assert!(State::from_notation("00000000 one 0 0 0").is_err());
+    }
+}
@@ -1,9 +1,12 @@
+use anyhow::Context;
+
 use crate::xml;
 
 use super::common;
 use super::moves::Move;
 use super::protocol_error::*;
 use super::server::Connection;
+use super::server_side_message::ServerSideMessage;
 
 pub enum JoinKind {
     Any,
@@ -45,11 +48,19 @@ impl Protocol {
         xml::deserialize(message)
     }
 
+    fn read_message_or_fail_on_close(&mut self) -> anyhow::Result<String> {
+        self.connection
+            .read_message()?
+            .context("Connection was closed while waiting for a message")
+    }
+
     pub fn read_message_after_join(&mut self) -> anyhow::Result<()> {
-        let mut initial_message = self.connection.read_fully_into_string()?;
+        let mut initial_message = self.read_message_or_fail_on_close()?;
 
-        // Remove <protocol>\n prefix
-        initial_message = initial_message.replace("<protocol>\n", "");
+        // Skip <protocol> prefix
+        if initial_message == "<protocol>" {
+            initial_message = self.read_message_or_fail_on_close()?;
+        }
 
         let joined = xml::deserialize::<xml::connection::Joined>(&initial_message);
         if joined.is_err() {
@@ -64,18 +75,39 @@ impl Protocol {
         Ok(())
     }
 
-    pub fn read_room_message(&mut self) -> anyhow::Result<xml::room::Room> {
-        let room_message = self.connection.read_string_until_condition(&|text: &str| {
-            return text.ends_with("</room>");
-        })?;
-        let room = xml::deserialize::<xml::room::Room>(&room_message);
+    fn deserialize_room(room_message: &str) -> anyhow::Result<xml::room::Room> {
+        let room = xml::deserialize::<xml::room::Room>(room_message);
         if room.is_err() {
-            let error = Self::deserialize_error(&room_message)?;
+            let error = Self::deserialize_error(room_message)?;
             anyhow::bail!(ProtocolError::from(error))
         }
         Ok(room.unwrap())
     }
 
+    pub fn read_room_message(&mut self) -> anyhow::Result<xml::room::Room> {
+        let room_message = self.read_message_or_fail_on_close()?;
+        Self::deserialize_room(&room_message)
+    }
+
+    // Returns None once the server has left the room or closed the connection.
+    pub fn read_server_side_message(&mut self) -> anyhow::Result<Option<ServerSideMessage>> {
+        while let Some(message) = self.connection.read_message()? {
+            if message.starts_with("<left") || message == "</protocol>" {
+                return Ok(None);
+            }
+            if message == "<protocol>" {
+                continue;
+            }
+
+            let room = Self::deserialize_room(&message)?;
+            if room.room_id != self.room_id {
+                anyhow::bail!("Expected room id {}, got {}", self.room_id, room.room_id);
+            }
+            return ServerSideMessage::try_from(room).map(Some);
+        }
+        Ok(None)
+    }
+
     pub fn read_welcome_message(&mut self) -> anyhow::Result<()> {
         let room = self.read_room_message()?;
         if room.room_id != self.room_id {
@@ -0,0 +1,207 @@
+// A game as its start state and the moves played from it, stored as text:
+//
+// team_one NAME
+// team_two NAME
+// state STATE_NOTATION
+// move c3
+// move a2-e2
+//
+// Empty lines and lines starting with '#' are ignored, the result is only written as a comment.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+
+use super::moves::Move;
+use super::result::GameResult;
+use super::state::State;
+use crate::xml::common::Team;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub team_one: String,
+    pub team_two: String,
+    pub initial_state: State,
+    pub moves: Vec<Move>,
+}
+
+impl GameRecord {
+    pub fn new(initial_state: State) -> Self {
+        Self {
+            team_one: "one".to_string(),
+            team_two: "two".to_string(),
+            initial_state,
+            moves: vec![],
+        }
+    }
+
+    pub fn with_team_names(mut self, team_one: &str, team_two: &str) -> Self {
+        self.team_one = team_one.to_string();
+        self.team_two = team_two.to_string();
+        self
+    }
+
+    pub fn team_name(&self, team: Team) -> &str {
+        match team {
+            Team::One => &self.team_one,
+            Team::Two => &self.team_two,
+        }
+    }
+
+    // The state each move was played in with moveless players skipped, followed by the state
+    // after the last move.
+    pub fn states(&self) -> anyhow::Result<Vec<State>> {
+        let mut states = Vec::with_capacity(self.moves.len() + 1);
+        let mut state = self.initial_state.clone();
+        for (index, performed_move) in self.moves.iter().enumerate() {
+            state = state.with_moveless_player_skipped()?;
+            states.push(state.clone());
+            state
+                .perform_move(performed_move.clone())
+                .with_context(|| format!("Invalid move {} of the record", index + 1))?;
+        }
+        states.push(state);
+        Ok(states)
+    }
+
+    pub fn final_state(&self) -> anyhow::Result<State> {
+        self.states()?
+            .pop()
+            .context("A record has at least one state")
+    }
+
+    // Only available once the game is over
+    pub fn result(&self) -> anyhow::Result<GameResult> {
+        self.final_state()?.get_result()
+    }
+
+    pub fn from_text(text: &str) -> anyhow::Result<Self> {
+        let mut team_one = None;
+        let mut team_two = None;
+        let mut initial_state = None;
+        let mut moves = vec![];
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "team_one" => team_one = Some(value.trim().to_string()),
+                "team_two" => team_two = Some(value.trim().to_string()),
+                "state" => initial_state = Some(State::from_notation(value)?),
+                "move" => moves.push(
+                    Move::from_notation(value)
+                        .with_context(|| format!("Invalid move in line {}", index + 1))?,
+                ),
+                _ => anyhow::bail!("Unknown entry '{}' in line {}", key, index + 1),
+            }
+        }
+        let record = Self {
+            team_one: team_one.unwrap_or_else(|| "one".to_string()),
+            team_two: team_two.unwrap_or_else(|| "two".to_string()),
+            initial_state: initial_state.context("The record has no state")?,
+            moves,
+        };
+        record.states()?;
+        Ok(record)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Could not read game record {}", path.display()))?;
+        Self::from_text(&text).with_context(|| format!("Invalid game record {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Could not write game record {}", path.display()))
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "team_one {}", self.team_one)?;
+        writeln!(f, "team_two {}", self.team_two)?;
+        writeln!(f, "state {}", self.initial_state.to_notation())?;
+        for performed_move in &self.moves {
+            writeln!(f, "move {}", performed_move.to_notation())?;
+        }
+        if let Ok(result) = self.result() {
+            let winner = match result.winner {
+                Some(team) => format!("{} wins", self.team_name(team)),
+                None => "draw".to_string(),
+            };
+            let final_state = self.final_state().map_err(|_| fmt::Error)?;
+            writeln!(
+                f,
+                "# result: {}, {}:{}",
+                winner, final_state.team_one_fish, final_state.team_two_fish
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state_generator::create_any;
+
+    fn create_finished_record() -> GameRecord {
+        let mut record = GameRecord::new(create_any()).with_team_names("alpha", "beta");
+        let mut state = record.initial_state.clone();
+        while !state.is_over() {
+            state = state.with_moveless_player_skipped().unwrap();
+            let possible_move = state.possible_moves().next().unwrap();
+            record.moves.push(possible_move.clone());
+            state.perform_move(possible_move).unwrap();
+        }
+        record
+    }
+
+    #[test]
+    fn records_list_the_state_of_every_move() {
+        let record = create_finished_record();
+        let states = record.states().unwrap();
+        assert_eq!(record.moves.len() + 1, states.len());
+        assert!(states.last().unwrap().is_over());
+        for (state, performed_move) in states.iter().zip(&record.moves) {
+            assert!(state
+                .possible_moves()
+                .any(|possible_move| possible_move == *performed_move));
+        }
+        assert!(record.result().is_ok());
+    }
+
+    #[test]
+    fn records_can_be_written_and_read_again() {
+        let record = create_finished_record();
+        let text = record.to_string();
+        assert!(text.contains("# result: "));
+        let parsed = GameRecord::from_text(&text).unwrap();
+        assert_eq!("alpha", parsed.team_name(Team::One));
+        assert_eq!(record.moves, parsed.moves);
+        assert_eq!(
+            record.final_state().unwrap().to_notation(),
+            parsed.final_state().unwrap().to_notation()
+        );
+
+        let path = std::env::temp_dir().join("rostware23_game_record_test.txt");
+        record.save(&path).unwrap();
+        let loaded = GameRecord::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(record.moves, loaded.unwrap().moves);
+    }
+
+    #[test]
+    fn records_with_invalid_moves_are_rejected() {
+        let record = create_finished_record();
+        let text = format!("{}move a1-h8\n", record);
+        assert!(GameRecord::from_text(&text).is_err());
+        assert!(GameRecord::from_text("move c3\n").is_err());
+        assert!(GameRecord::from_text(&text.replace("team_one", "player")).is_err());
+    }
+}
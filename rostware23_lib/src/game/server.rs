@@ -1,25 +1,97 @@
 use std::io::prelude::*;
 use std::io::{BufReader, BufWriter};
 use std::net::TcpStream;
+use std::time::Instant;
+
+use super::transcript::{TranscriptDirection, TranscriptRecorder};
 
 pub struct Connection {
-    reader: BufReader<TcpStream>,
-    writer: BufWriter<TcpStream>,
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: BufWriter<Box<dyn Write + Send>>,
+    pending: Vec<u8>,
+    pending_received_at: Option<Instant>,
+    last_message_received_at: Option<Instant>,
+    recorder: Option<TranscriptRecorder>,
 }
 
 pub const READ_BUFFER_SIZE: usize = 256;
 type ConditionFunction<'a> = &'a dyn Fn(&str) -> bool;
 
+// Finds the end of the first top-level XML message in the given text.
+// The opening <protocol> tag never gets closed before the session ends,
+// so it is treated as a message on its own.
+fn find_message_end(text: &str) -> Option<usize> {
+    let start = text.find('<')?;
+    let opening_tag_end = start + text[start..].find('>')? + 1;
+    let opening_tag = &text[start..opening_tag_end];
+    if opening_tag.starts_with("</") || opening_tag.ends_with("/>") {
+        return Some(opening_tag_end);
+    }
+
+    let tag_name = opening_tag[1..opening_tag.len() - 1]
+        .split_whitespace()
+        .next()?;
+    if tag_name == "protocol" {
+        return Some(opening_tag_end);
+    }
+
+    let closing_tag = format!("</{tag_name}>");
+    let closing_tag_start = opening_tag_end + text[opening_tag_end..].find(&closing_tag)?;
+    Some(closing_tag_start + closing_tag.len())
+}
+
+// A read might stop in the middle of a multi-byte character,
+// so only the completely received prefix can be searched for messages.
+fn complete_utf8_prefix(bytes: &[u8]) -> anyhow::Result<&str> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        Err(error) if error.error_len().is_none() => {
+            Ok(std::str::from_utf8(&bytes[..error.valid_up_to()])?)
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
 impl Connection {
     pub fn connect(address: &str) -> anyhow::Result<Self> {
         let stream = TcpStream::connect(address)?;
-        let reader = BufReader::new(stream.try_clone()?);
-        let writer = BufWriter::new(stream);
-        Ok(Self { reader, writer })
+        Ok(Self::from_streams(stream.try_clone()?, stream))
+    }
+
+    pub fn from_streams(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> Self {
+        Self {
+            reader: BufReader::new(Box::new(reader)),
+            writer: BufWriter::new(Box::new(writer)),
+            pending: Vec::new(),
+            pending_received_at: None,
+            last_message_received_at: None,
+            recorder: None,
+        }
+    }
+
+    pub fn with_recorder(mut self, recorder: TranscriptRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    // The moment the first bytes of the last message returned by read_message arrived.
+    pub fn last_message_received_at(&self) -> Option<Instant> {
+        self.last_message_received_at
+    }
+
+    fn record(&mut self, direction: TranscriptDirection, message: &str) -> anyhow::Result<()> {
+        match &mut self.recorder {
+            Some(recorder) => recorder.record(direction, message),
+            None => Ok(()),
+        }
     }
 
     pub fn write_buffer(&mut self, buffer: &[u8]) -> anyhow::Result<()> {
-        self.writer.write(buffer)?;
+        self.record(TranscriptDirection::Sent, &String::from_utf8_lossy(buffer))?;
+        self.writer.write_all(buffer)?;
         Ok(())
     }
 
@@ -52,19 +124,166 @@ impl Connection {
         let mut read_buffer = [0u8; READ_BUFFER_SIZE];
         loop {
             let read_amount = self.read_buffer(&mut read_buffer)?;
-            let string = String::from_utf8((&read_buffer[..read_amount]).to_vec())?;
+            if read_amount == 0 {
+                anyhow::bail!("Connection was closed before the condition was met");
+            }
+            let string = String::from_utf8(read_buffer[..read_amount].to_vec())?;
             string_buffer.push_str(&string);
             if condition_function(string_buffer.as_str()) {
+                self.record(TranscriptDirection::Received, &string_buffer)?;
                 return Ok(string_buffer);
             }
         }
     }
 
+    // Reads exactly one top-level XML message, keeping anything received after it
+    // for the next call. Returns None once the connection has been closed.
+    pub fn read_message(&mut self) -> anyhow::Result<Option<String>> {
+        let mut read_buffer = [0u8; READ_BUFFER_SIZE];
+        loop {
+            let received = complete_utf8_prefix(&self.pending)?;
+            if let Some(message_end) = find_message_end(received) {
+                let message = received[..message_end].trim().to_string();
+                self.pending.drain(..message_end);
+                // Bytes left over arrived together with this message, so keeping
+                // the timestamp for them errs on the side of less remaining time.
+                self.last_message_received_at = self.pending_received_at;
+                if self.pending.iter().all(u8::is_ascii_whitespace) {
+                    self.pending_received_at = None;
+                }
+                self.record(TranscriptDirection::Received, &message)?;
+                return Ok(Some(message));
+            }
+
+            let read_amount = self.read_buffer(&mut read_buffer)?;
+            if read_amount == 0 {
+                if !self.pending.iter().all(u8::is_ascii_whitespace) {
+                    anyhow::bail!("Connection was closed in the middle of a message");
+                }
+                return Ok(None);
+            }
+            if self.pending_received_at.is_none() {
+                self.pending_received_at = Some(Instant::now());
+            }
+            self.pending.extend_from_slice(&read_buffer[..read_amount]);
+        }
+    }
+
     pub fn read_fully_into_string(&mut self) -> anyhow::Result<String> {
         let received: Vec<u8> = self.reader.fill_buf()?.to_vec();
         self.reader.consume(received.len());
 
         let string = String::from_utf8(received)?;
+        self.record(TranscriptDirection::Received, &string)?;
         Ok(string)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::game::transcript::read_transcript_file;
+
+    #[test]
+    fn message_end_of_incomplete_room_is_not_found() {
+        let text = r#"<room roomId="TEST_ROOM_ID"><data class="moveRequest">"#;
+        assert_eq!(None, find_message_end(text));
+    }
+
+    #[test]
+    fn message_end_of_room_followed_by_other_message_is_end_of_room() {
+        let room = r#"<room roomId="TEST_ROOM_ID"><data class="moveRequest"></data></room>"#;
+        let text = format!("{room}<left roomId=\"TEST_ROOM_ID\"/>");
+        assert_eq!(Some(room.len()), find_message_end(&text));
+    }
+
+    #[test]
+    fn message_end_of_self_closing_tag_is_end_of_tag() {
+        let text = "\n  <left roomId=\"TEST_ROOM_ID\"/></protocol>";
+        assert_eq!(
+            Some(text.len() - "</protocol>".len()),
+            find_message_end(text)
+        );
+    }
+
+    #[test]
+    fn message_end_of_protocol_start_is_end_of_opening_tag() {
+        let text = "<protocol>\n  <joined roomId=\"TEST_ROOM_ID\"/>";
+        assert_eq!(Some("<protocol>".len()), find_message_end(text));
+    }
+
+    #[test]
+    fn complete_utf8_prefix_excludes_partial_character() {
+        let text = "<fragment name=\"∅ Punkte\">";
+        let bytes = text.as_bytes();
+        let cut = text.find('∅').unwrap() + 1;
+        assert_eq!(
+            &text[..cut - 1],
+            complete_utf8_prefix(&bytes[..cut]).unwrap()
+        );
+    }
+
+    #[test]
+    fn message_end_of_closing_tag_is_end_of_tag() {
+        let text = "</protocol>";
+        assert_eq!(Some(text.len()), find_message_end(text));
+    }
+
+    #[test]
+    fn reading_messages_splits_received_text_at_message_boundaries() {
+        let received = "<protocol>\n  <joined roomId=\"TEST\"/><left roomId=\"TEST\"/>";
+        let mut connection =
+            Connection::from_streams(Cursor::new(received.as_bytes().to_vec()), std::io::sink());
+        assert_eq!(
+            Some("<protocol>".to_string()),
+            connection.read_message().unwrap()
+        );
+        assert_eq!(
+            Some("<joined roomId=\"TEST\"/>".to_string()),
+            connection.read_message().unwrap()
+        );
+        assert_eq!(
+            Some("<left roomId=\"TEST\"/>".to_string()),
+            connection.read_message().unwrap()
+        );
+        assert_eq!(None, connection.read_message().unwrap());
+    }
+
+    #[test]
+    fn buffered_message_keeps_time_of_its_arrival() {
+        let received = "<joined roomId=\"TEST\"/><left roomId=\"TEST\"/>";
+        let mut connection =
+            Connection::from_streams(Cursor::new(received.as_bytes().to_vec()), std::io::sink());
+        let before_reading = Instant::now();
+        connection.read_message().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let before_reading_buffered_message = Instant::now();
+        connection.read_message().unwrap();
+
+        let received_at = connection.last_message_received_at().unwrap();
+        assert!(received_at >= before_reading);
+        assert!(received_at < before_reading_buffered_message);
+    }
+
+    #[test]
+    fn connection_with_recorder_records_messages_in_both_directions() {
+        let directory = std::env::temp_dir().join("rostware23_connection_recorder_test");
+        let (recorder, path) = TranscriptRecorder::create_in_directory(&directory).unwrap();
+        let received = "<joined roomId=\"TEST\"/>";
+        let mut connection =
+            Connection::from_streams(Cursor::new(received.as_bytes().to_vec()), std::io::sink())
+                .with_recorder(recorder);
+        connection.write_string_slice("<join />").unwrap();
+        connection.read_message().unwrap();
+
+        let entries = read_transcript_file(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(2, entries.len());
+        assert_eq!(TranscriptDirection::Sent, entries[0].direction);
+        assert_eq!("<join />", entries[0].message);
+        assert_eq!(TranscriptDirection::Received, entries[1].direction);
+        assert_eq!(received, entries[1].message);
+    }
+}
@@ -1,3 +1,6 @@
+use anyhow::Context;
+
+use crate::game::common::Team;
 use crate::game::result::GameResult;
 use crate::game::state::State;
 use crate::xml::data::DataClass;
@@ -5,6 +8,7 @@ use crate::xml::room::Room;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ServerSideMessage {
+    Welcome(Team),
     MoveRequest,
     Memento(super::state::State),
     Result(super::result::GameResult),
@@ -15,7 +19,12 @@ impl TryFrom<Room> for ServerSideMessage {
 
     fn try_from(value: Room) -> anyhow::Result<Self> {
         match value.data.class {
-            DataClass::WelcomeMessage => anyhow::bail!("Welcome message was already handled!"),
+            DataClass::WelcomeMessage => Ok(Self::Welcome(
+                value
+                    .data
+                    .color
+                    .context("Expected color attribute on welcome message")?,
+            )),
             DataClass::MoveRequest => Ok(Self::MoveRequest),
             DataClass::Memento => Ok(Self::Memento(State::from(value.data.state.unwrap()))),
             DataClass::Move => anyhow::bail!("Moves are not server-side messages"),
@@ -23,3 +32,42 @@ impl TryFrom<Room> for ServerSideMessage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xml::data::Data;
+
+    fn room_with_data_class(class: DataClass, color: Option<Team>) -> Room {
+        Room {
+            room_id: "TEST_ROOM_ID".to_string(),
+            data: Data {
+                class,
+                color,
+                state: None,
+                sent_move: None,
+                result: None,
+            },
+        }
+    }
+
+    #[test]
+    fn welcome_message_from_room_contains_own_team() {
+        let room = room_with_data_class(DataClass::WelcomeMessage, Some(Team::Two));
+        let actual = ServerSideMessage::try_from(room).unwrap();
+        assert_eq!(ServerSideMessage::Welcome(Team::Two), actual);
+    }
+
+    #[test]
+    fn welcome_message_without_color_from_room_fails() {
+        let room = room_with_data_class(DataClass::WelcomeMessage, None);
+        assert!(ServerSideMessage::try_from(room).is_err());
+    }
+
+    #[test]
+    fn move_request_from_room() {
+        let room = room_with_data_class(DataClass::MoveRequest, None);
+        let actual = ServerSideMessage::try_from(room).unwrap();
+        assert_eq!(ServerSideMessage::MoveRequest, actual);
+    }
+}
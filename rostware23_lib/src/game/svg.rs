@@ -0,0 +1,436 @@
+// Renders boards as SVG images. The hexagons are laid out in odd-r layout like the board's
+// display, with the column letters and row numbers of the coordinate notation around them.
+
+use std::fmt::Write;
+
+use super::board::{Board, FieldState};
+use super::board_bitset::Bitset8x8;
+use super::common::{Coordinate, BOARD_HEIGHT, BOARD_WIDTH};
+use super::moves::Move;
+use super::state::State;
+use crate::xml::common::Team;
+
+const TEAM_ONE_COLOUR: &str = "#2e9e44";
+const TEAM_TWO_COLOUR: &str = "#b0379f";
+const FISH_FIELD_COLOUR: &str = "#e4f1f8";
+const WATER_COLOUR: &str = "#8cc4e4";
+const LINE_COLOUR: &str = "#4a6b80";
+const DEFAULT_RADIUS: f64 = 30.0;
+
+pub fn team_colour(team: Team) -> &'static str {
+    match team {
+        Team::One => TEAM_ONE_COLOUR,
+        Team::Two => TEAM_TWO_COLOUR,
+    }
+}
+
+// Escapes text for use in SVG and HTML documents
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[derive(Debug, Clone)]
+struct Arrow {
+    performed_move: Move,
+    colour: String,
+    label: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SvgRenderer {
+    // Distance from the centre of a field to its corners
+    radius: f64,
+    highlights: Vec<(Bitset8x8, String)>,
+    arrows: Vec<Arrow>,
+    caption: Option<String>,
+    // Prepended to the ids of the image, so several images can be embedded in the same page
+    id_prefix: String,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SvgRenderer {
+    pub fn new() -> Self {
+        Self {
+            radius: DEFAULT_RADIUS,
+            highlights: vec![],
+            arrows: vec![],
+            caption: None,
+            id_prefix: String::new(),
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    // Fields are given in odd-r coordinates, like the bitsets of the raters
+    pub fn with_highlight(mut self, fields: Bitset8x8, colour: &str) -> Self {
+        self.highlights.push((fields, colour.to_string()));
+        self
+    }
+
+    pub fn with_move(mut self, performed_move: Move, colour: &str) -> Self {
+        self.arrows.push(Arrow {
+            performed_move,
+            colour: colour.to_string(),
+            label: None,
+        });
+        self
+    }
+
+    // Numbered arrows in the colour of the team playing each move
+    pub fn with_principal_variation(
+        mut self,
+        state: &State,
+        principal_variation: &[Move],
+    ) -> anyhow::Result<Self> {
+        let mut state = state.clone();
+        for (index, performed_move) in principal_variation.iter().enumerate() {
+            state = state.with_moveless_player_skipped()?;
+            self.arrows.push(Arrow {
+                performed_move: performed_move.clone(),
+                colour: team_colour(state.current_team()).to_string(),
+                label: Some((index + 1).to_string()),
+            });
+            state.perform_move(performed_move.clone())?;
+        }
+        Ok(self)
+    }
+
+    pub fn with_caption(mut self, caption: &str) -> Self {
+        self.caption = Some(caption.to_string());
+        self
+    }
+
+    pub fn with_id_prefix(mut self, id_prefix: &str) -> Self {
+        self.id_prefix = id_prefix.to_string();
+        self
+    }
+
+    // One arrow head per colour
+    fn marker_id(&self, colour: &str) -> String {
+        let colour_name: String = colour.chars().filter(char::is_ascii_alphanumeric).collect();
+        format!("{}arrow-head-{}", self.id_prefix, colour_name)
+    }
+
+    fn field_width(&self) -> f64 {
+        3f64.sqrt() * self.radius
+    }
+
+    // Space for the coordinate labels
+    fn margin(&self) -> f64 {
+        self.radius
+    }
+
+    fn centre(&self, x: u64, y: u64) -> (f64, f64) {
+        let indentation = if y % 2 == 1 { 0.5 } else { 0.0 };
+        (
+            self.margin() + self.field_width() * (x as f64 + 0.5 + indentation),
+            self.margin() + self.radius * (1.0 + 1.5 * y as f64),
+        )
+    }
+
+    fn centre_of_coordinate(&self, coordinate: &Coordinate) -> (f64, f64) {
+        let odd_r = coordinate.clone().doubled_to_odd_r();
+        self.centre(odd_r.x(), odd_r.y())
+    }
+
+    pub fn width(&self) -> f64 {
+        self.margin() * 2.0 + self.field_width() * (BOARD_WIDTH as f64 + 0.5)
+    }
+
+    pub fn height(&self) -> f64 {
+        let board_height = self.radius * (2.0 + 1.5 * (BOARD_HEIGHT - 1) as f64);
+        let caption_height = if self.caption.is_some() {
+            self.radius
+        } else {
+            0.0
+        };
+        self.margin() * 2.0 + board_height + caption_height
+    }
+
+    fn hexagon(&self, x: u64, y: u64, fill: &str, extra_attributes: &str) -> String {
+        let (centre_x, centre_y) = self.centre(x, y);
+        let corners: Vec<String> = (0..6)
+            .map(|corner| {
+                let angle = (60.0 * corner as f64 - 90.0).to_radians();
+                format!(
+                    "{:.1},{:.1}",
+                    centre_x + self.radius * angle.cos(),
+                    centre_y + self.radius * angle.sin()
+                )
+            })
+            .collect();
+        format!(
+            "<polygon points=\"{}\" fill=\"{}\" {}/>",
+            corners.join(" "),
+            fill,
+            extra_attributes
+        )
+    }
+
+    fn write_field(&self, svg: &mut String, board: &Board, x: u64, y: u64) -> anyhow::Result<()> {
+        let field = board.get(Coordinate::new(x, y).odd_r_to_doubled())?;
+        let (centre_x, centre_y) = self.centre(x, y);
+        let font_size = self.radius * 0.6;
+        let border = format!("stroke=\"{}\" stroke-width=\"1\"", LINE_COLOUR);
+        match field {
+            FieldState::Empty => writeln!(svg, "{}", self.hexagon(x, y, WATER_COLOUR, &border))?,
+            FieldState::Fish(fish) => {
+                writeln!(svg, "{}", self.hexagon(x, y, FISH_FIELD_COLOUR, &border))?;
+                writeln!(
+                    svg,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>",
+                    centre_x, centre_y, font_size, LINE_COLOUR, fish
+                )?;
+            }
+            FieldState::Team(team) => {
+                writeln!(svg, "{}", self.hexagon(x, y, FISH_FIELD_COLOUR, &border))?;
+                writeln!(
+                    svg,
+                    "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\"/>",
+                    centre_x,
+                    centre_y,
+                    self.radius * 0.6,
+                    team_colour(team)
+                )?;
+                let symbol = if team == Team::One { 'G' } else { 'P' };
+                writeln!(
+                    svg,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"white\" font-weight=\"bold\">{}</text>",
+                    centre_x, centre_y, font_size, symbol
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_arrow(&self, svg: &mut String, arrow: &Arrow) -> anyhow::Result<()> {
+        let stroke_width = self.radius * 0.12;
+        let (label_x, label_y) = match &arrow.performed_move {
+            Move::Place(to) => {
+                let (to_x, to_y) = self.centre_of_coordinate(to);
+                writeln!(
+                    svg,
+                    "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{:.1}\"/>",
+                    to_x, to_y, self.radius * 0.75, arrow.colour, stroke_width
+                )?;
+                (to_x, to_y - self.radius * 0.75)
+            }
+            Move::Normal { from, to } => {
+                let (from_x, from_y) = self.centre_of_coordinate(from);
+                let (to_x, to_y) = self.centre_of_coordinate(to);
+                // The head ends before the centre, so the target stays visible
+                let length = (to_x - from_x).hypot(to_y - from_y);
+                let shortening = (self.radius * 0.4 / length).min(0.5);
+                let end_x = to_x - (to_x - from_x) * shortening;
+                let end_y = to_y - (to_y - from_y) * shortening;
+                writeln!(
+                    svg,
+                    "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{:.1}\" stroke-linecap=\"round\" opacity=\"0.85\" marker-end=\"url(#{})\"/>",
+                    from_x, from_y, end_x, end_y, arrow.colour, stroke_width, self.marker_id(&arrow.colour)
+                )?;
+                ((from_x + to_x) / 2.0, (from_y + to_y) / 2.0)
+            }
+        };
+        if let Some(label) = &arrow.label {
+            writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"white\" stroke=\"{}\"/>",
+                label_x,
+                label_y,
+                self.radius * 0.3,
+                arrow.colour
+            )?;
+            writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>",
+                label_x, label_y, self.radius * 0.4, arrow.colour, escape_xml(label)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_markers(&self, svg: &mut String) -> anyhow::Result<()> {
+        let mut colours: Vec<&str> = vec![];
+        for arrow in &self.arrows {
+            if matches!(arrow.performed_move, Move::Normal { .. })
+                && !colours.contains(&arrow.colour.as_str())
+            {
+                colours.push(&arrow.colour);
+            }
+        }
+        if colours.is_empty() {
+            return Ok(());
+        }
+        writeln!(svg, "<defs>")?;
+        for colour in colours {
+            writeln!(
+                svg,
+                "<marker id=\"{}\" viewBox=\"0 0 10 10\" refX=\"5\" refY=\"5\" markerWidth=\"4\" markerHeight=\"4\" orient=\"auto\"><path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"{}\"/></marker>",
+                self.marker_id(colour), colour
+            )?;
+        }
+        writeln!(svg, "</defs>")?;
+        Ok(())
+    }
+
+    pub fn render(&self, board: &Board) -> anyhow::Result<String> {
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.1} {:.1}\" font-family=\"sans-serif\">",
+            self.width(), self.height(), self.width(), self.height()
+        )?;
+        self.write_markers(&mut svg)?;
+        writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+        let label_size = self.radius * 0.5;
+        for x in 0..BOARD_WIDTH {
+            let (centre_x, _) = self.centre(x, 0);
+            writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                centre_x, self.margin() / 2.0, label_size, (b'a' + x as u8) as char
+            )?;
+        }
+        for y in 0..BOARD_HEIGHT {
+            let (_, centre_y) = self.centre(0, y);
+            writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                self.margin() / 2.0, centre_y, label_size, y + 1
+            )?;
+        }
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                self.write_field(&mut svg, board, x, y)?;
+            }
+        }
+        for (fields, colour) in &self.highlights {
+            for y in 0..BOARD_HEIGHT {
+                for x in (0..BOARD_WIDTH).filter(|x| fields.get(*x, y).unwrap_or(false)) {
+                    let attributes = format!(
+                        "fill-opacity=\"0.45\" stroke=\"{}\" stroke-width=\"{:.1}\"",
+                        colour,
+                        self.radius * 0.08
+                    );
+                    writeln!(svg, "{}", self.hexagon(x, y, colour, &attributes))?;
+                }
+            }
+        }
+        for arrow in &self.arrows {
+            self.write_arrow(&mut svg, arrow)?;
+        }
+        if let Some(caption) = &self.caption {
+            writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                self.width() / 2.0,
+                self.height() - self.margin(),
+                label_size,
+                escape_xml(caption)
+            )?;
+        }
+        write!(svg, "</svg>")?;
+        Ok(svg)
+    }
+
+    // Like render, captioned with the fish of both teams and the team to move if no caption is set
+    pub fn render_state(&self, state: &State) -> anyhow::Result<String> {
+        if self.caption.is_some() {
+            return self.render(&state.board);
+        }
+        let status = if state.is_over() {
+            "game over".to_string()
+        } else {
+            format!(
+                "team {:?} to move",
+                state.with_moveless_player_skipped()?.current_team()
+            )
+        };
+        let caption = format!(
+            "Turn {}, {}, fish {}:{}",
+            state.turn + 1,
+            status,
+            state.team_one_fish,
+            state.team_two_fish
+        );
+        self.clone().with_caption(&caption).render(&state.board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state_generator::create_any;
+
+    #[test]
+    fn every_field_is_drawn_as_hexagon() {
+        let state = create_any();
+        let svg = SvgRenderer::new().render(&state.board).unwrap();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert_eq!(
+            (BOARD_WIDTH * BOARD_HEIGHT) as usize,
+            svg.matches("<polygon").count()
+        );
+        assert!(svg.contains(">a</text>") && svg.contains(">8</text>"));
+        assert!(!svg.contains("marker"));
+    }
+
+    #[test]
+    fn highlights_arrows_and_captions_are_drawn() {
+        let state = create_any();
+        let fields = Bitset8x8::empty()
+            .with_set(0, 0, true)
+            .unwrap()
+            .with_set(7, 7, true)
+            .unwrap();
+        let principal_variation: Vec<Move> = state.possible_moves().take(1).collect();
+        let svg = SvgRenderer::new()
+            .with_highlight(fields, "#ffcc00")
+            .with_move(
+                Move::Normal {
+                    from: Coordinate::from_notation("a1").unwrap(),
+                    to: Coordinate::from_notation("c1").unwrap(),
+                },
+                "red",
+            )
+            .with_move(
+                Move::Normal {
+                    from: Coordinate::from_notation("c1").unwrap(),
+                    to: Coordinate::from_notation("e1").unwrap(),
+                },
+                "red",
+            )
+            .with_principal_variation(&state, &principal_variation)
+            .unwrap()
+            .with_caption("Fish & chips")
+            .render_state(&state)
+            .unwrap();
+        assert_eq!(
+            (BOARD_WIDTH * BOARD_HEIGHT) as usize + 2,
+            svg.matches("<polygon").count()
+        );
+        assert_eq!(2, svg.matches("marker-end").count());
+        assert_eq!(1, svg.matches("<marker ").count());
+        assert!(svg.contains("<defs>"));
+        assert!(svg.contains(&format!("stroke=\"{}\"", TEAM_ONE_COLOUR)));
+        assert!(svg.contains(">1</text>"));
+        assert!(svg.contains("Fish &amp; chips"));
+    }
+
+    #[test]
+    fn states_are_captioned_with_their_status() {
+        let svg = SvgRenderer::new().render_state(&create_any()).unwrap();
+        assert!(svg.contains("Turn 1, team One to move, fish 0:0"));
+    }
+}
@@ -0,0 +1,197 @@
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use anyhow::Context;
+
+// A transcript consists of entries, each made up of a header line
+// `<SENT|RECEIVED> <elapsed millis> <message length in bytes>`
+// followed by the raw message and a line break.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptDirection {
+    Sent,
+    Received,
+}
+
+impl TranscriptDirection {
+    fn keyword(&self) -> &'static str {
+        match self {
+            TranscriptDirection::Sent => "SENT",
+            TranscriptDirection::Received => "RECEIVED",
+        }
+    }
+
+    fn from_keyword(keyword: &str) -> anyhow::Result<Self> {
+        match keyword {
+            "SENT" => Ok(TranscriptDirection::Sent),
+            "RECEIVED" => Ok(TranscriptDirection::Received),
+            other => anyhow::bail!("Unknown transcript direction '{other}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub direction: TranscriptDirection,
+    pub elapsed_millis: u128,
+    pub message: String,
+}
+
+pub struct TranscriptRecorder {
+    writer: Box<dyn Write + Send>,
+    start: Instant,
+}
+
+impl TranscriptRecorder {
+    pub fn from_writer(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+            start: Instant::now(),
+        }
+    }
+
+    // Creates a new transcript file named after the current time inside the given directory.
+    pub fn create_in_directory(directory: &Path) -> anyhow::Result<(Self, PathBuf)> {
+        std::fs::create_dir_all(directory)?;
+        let millis_since_epoch = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_millis();
+        let path = directory.join(format!("transcript-{millis_since_epoch}.txt"));
+        let file = File::create(&path)
+            .with_context(|| format!("Couldn't create transcript file {}", path.display()))?;
+        Ok((Self::from_writer(BufWriter::new(file)), path))
+    }
+
+    // Every entry is flushed immediately, so the transcript survives a crashing client.
+    pub fn record(&mut self, direction: TranscriptDirection, message: &str) -> anyhow::Result<()> {
+        writeln!(
+            self.writer,
+            "{} {} {}",
+            direction.keyword(),
+            self.start.elapsed().as_millis(),
+            message.len()
+        )?;
+        self.writer.write_all(message.as_bytes())?;
+        writeln!(self.writer)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+fn read_entry(reader: &mut impl BufRead) -> anyhow::Result<Option<TranscriptEntry>> {
+    let mut header = String::new();
+    if reader.read_line(&mut header)? == 0 {
+        return Ok(None);
+    }
+
+    let mut header_parts = header.split_whitespace();
+    let mut next_part = || {
+        header_parts
+            .next()
+            .context("Incomplete transcript entry header")
+    };
+    let direction = TranscriptDirection::from_keyword(next_part()?)?;
+    let elapsed_millis = next_part()?.parse::<u128>()?;
+    let message_length = next_part()?.parse::<usize>()?;
+
+    let mut message = vec![0u8; message_length];
+    reader.read_exact(&mut message)?;
+    let mut line_break = [0u8; 1];
+    reader.read_exact(&mut line_break)?;
+
+    Ok(Some(TranscriptEntry {
+        direction,
+        elapsed_millis,
+        message: String::from_utf8(message)?,
+    }))
+}
+
+pub fn read_transcript(mut reader: impl BufRead) -> anyhow::Result<Vec<TranscriptEntry>> {
+    let mut entries = vec![];
+    while let Some(entry) = read_entry(&mut reader)? {
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+pub fn read_transcript_file(path: &Path) -> anyhow::Result<Vec<TranscriptEntry>> {
+    let file = File::open(path)
+        .with_context(|| format!("Couldn't open transcript file {}", path.display()))?;
+    read_transcript(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recorded_entries_are_read_back_in_order() {
+        let buffer = SharedBuffer::default();
+        let mut recorder = TranscriptRecorder::from_writer(buffer.clone());
+        recorder
+            .record(TranscriptDirection::Sent, "<protocol>")
+            .unwrap();
+        recorder
+            .record(
+                TranscriptDirection::Received,
+                "<room roomId=\"TEST\">\n  <data class=\"moveRequest\"></data>\n</room>",
+            )
+            .unwrap();
+
+        let written = buffer.0.lock().unwrap().clone();
+        let entries = read_transcript(written.as_slice()).unwrap();
+        assert_eq!(2, entries.len());
+        assert_eq!(TranscriptDirection::Sent, entries[0].direction);
+        assert_eq!("<protocol>", entries[0].message);
+        assert_eq!(TranscriptDirection::Received, entries[1].direction);
+        assert_eq!(
+            "<room roomId=\"TEST\">\n  <data class=\"moveRequest\"></data>\n</room>",
+            entries[1].message
+        );
+    }
+
+    #[test]
+    fn entry_header_contains_direction_elapsed_time_and_length() {
+        let buffer = SharedBuffer::default();
+        let mut recorder = TranscriptRecorder::from_writer(buffer.clone());
+        recorder
+            .record(TranscriptDirection::Received, "<left roomId=\"TEST\"/>")
+            .unwrap();
+
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let header: Vec<&str> = written.lines().next().unwrap().split(' ').collect();
+        assert_eq!("RECEIVED", header[0]);
+        assert!(header[1].parse::<u128>().is_ok());
+        assert_eq!("21", header[2]);
+    }
+
+    #[test]
+    fn reading_transcript_with_unknown_direction_fails() {
+        let transcript = "FORWARDED 0 4\n<a/>\n";
+        assert!(read_transcript(transcript.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn reading_transcript_with_truncated_message_fails() {
+        let transcript = "SENT 0 40\n<a/>\n";
+        assert!(read_transcript(transcript.as_bytes()).is_err());
+    }
+}
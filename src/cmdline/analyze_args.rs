@@ -0,0 +1,116 @@
+use getopts::Occur;
+use args::Args;
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct AnalyzeArgs {
+    pub state: Option<String>,
+    pub record: Option<String>,
+    pub move_number: Option<usize>,
+    pub depth: Option<i32>,
+    pub time_in_millis: Option<u128>,
+    pub evaluation_config: Option<String>,
+    pub model: Option<String>,
+    pub svg: Option<String>
+}
+
+impl AnalyzeArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Searches a position and explains the result",
+        );
+        args.option(
+            "s",
+            "state",
+            "The position in state notation",
+            "STATE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "r",
+            "record",
+            "A game record to take the position from, instead of --state",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "m",
+            "move",
+            "The number of the move of the record to analyze, the final position by default",
+            "NUMBER",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "d",
+            "depth",
+            "The depth to search to",
+            "DEPTH",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "t",
+            "time",
+            "The total time in milliseconds, half for the search and the rest for ranking the root moves",
+            "MILLIS",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "evaluation",
+            "A file with the weights of the evaluation components per game phase",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "model",
+            "A file with a learned evaluation model, used instead of the weighted evaluation",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "svg",
+            "Draws the position with the principal variation to this SVG file",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        let analyze_args = Self {
+            state: args.optional_value_of::<String>("state")?,
+            record: args.optional_value_of::<String>("record")?,
+            move_number: args.optional_value_of::<String>("move")?.map(|number| number.parse()).transpose()?,
+            depth: args.optional_value_of::<String>("depth")?.map(|depth| depth.parse()).transpose()?,
+            time_in_millis: args.optional_value_of::<String>("time")?.map(|millis| millis.parse()).transpose()?,
+            evaluation_config: args.optional_value_of::<String>("evaluation")?,
+            model: args.optional_value_of::<String>("model")?,
+            svg: args.optional_value_of::<String>("svg")?,
+        };
+        if analyze_args.state.is_some() == analyze_args.record.is_some() {
+            anyhow::bail!("Exactly one of --state and --record is required");
+        }
+        if analyze_args.depth.is_none() && analyze_args.time_in_millis.is_none() {
+            anyhow::bail!("At least one of --depth and --time is required");
+        }
+        Ok(analyze_args)
+    }
+}
@@ -0,0 +1,57 @@
+use std::env;
+
+use super::ClientArgs;
+use super::analyze_args::AnalyzeArgs;
+use super::engine_args::EngineArgs;
+use super::export_features_args::ExportFeaturesArgs;
+use super::play_args::PlayArgs;
+use super::replay_args::ReplayArgs;
+use super::replay_transcript_args::ReplayTranscriptArgs;
+use super::self_play_args::SelfPlayArgs;
+use super::tournament_args::TournamentArgs;
+use super::train_args::TrainArgs;
+use super::tune_args::TuneArgs;
+
+pub enum Command {
+    Client(ClientArgs),
+    ReplayTranscript(ReplayTranscriptArgs),
+    Tune(TuneArgs),
+    ExportFeatures(ExportFeaturesArgs),
+    SelfPlay(SelfPlayArgs),
+    Train(TrainArgs),
+    Tournament(TournamentArgs),
+    Engine(EngineArgs),
+    Play(PlayArgs),
+    Analyze(AnalyzeArgs),
+    Replay(ReplayArgs)
+}
+
+// Removes the subcommand, so its options are parsed like those of a program on its own.
+fn subcommand_args(mut env_args: Vec<String>, program: String) -> Vec<String> {
+    env_args.drain(..2);
+    env_args.insert(0, program);
+    env_args
+}
+
+impl Command {
+    // The first argument selects a subcommand, playing on the server is the default.
+    pub fn parse() -> anyhow::Result<Self> {
+        let env_args: Vec<String> = env::args().collect();
+        let subcommand = env_args.get(1).cloned().unwrap_or_default();
+        let program = format!("{} {}", env_args[0], subcommand);
+
+        match subcommand.as_str() {
+            "replay-transcript" => Ok(Self::ReplayTranscript(ReplayTranscriptArgs::parse(subcommand_args(env_args, program))?)),
+            "tune" => Ok(Self::Tune(TuneArgs::parse(subcommand_args(env_args, program))?)),
+            "self-play" => Ok(Self::SelfPlay(SelfPlayArgs::parse(subcommand_args(env_args, program))?)),
+            "train" => Ok(Self::Train(TrainArgs::parse(subcommand_args(env_args, program))?)),
+            "tournament" => Ok(Self::Tournament(TournamentArgs::parse(subcommand_args(env_args, program))?)),
+            "engine" => Ok(Self::Engine(EngineArgs::parse(subcommand_args(env_args, program))?)),
+            "replay" => Ok(Self::Replay(ReplayArgs::parse(subcommand_args(env_args, program))?)),
+            "analyze" => Ok(Self::Analyze(AnalyzeArgs::parse(subcommand_args(env_args, program))?)),
+            "play" => Ok(Self::Play(PlayArgs::parse(subcommand_args(env_args, program))?)),
+            "export-features" => Ok(Self::ExportFeatures(ExportFeaturesArgs::parse(subcommand_args(env_args, program))?)),
+            _ => Ok(Self::Client(ClientArgs::parse(env_args)?))
+        }
+    }
+}
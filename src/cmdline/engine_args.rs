@@ -0,0 +1,50 @@
+use getopts::Occur;
+use args::Args;
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct EngineArgs {
+    pub engine: String,
+    pub name: String
+}
+
+impl EngineArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Serves an engine over the engine protocol on standard input and output",
+        );
+        args.option(
+            "e",
+            "engine",
+            "The engine searching the positions",
+            "ENGINE",
+            Occur::Req,
+            Some("pvs".to_string()),
+        );
+        args.option(
+            "n",
+            "name",
+            "The name the engine introduces itself with",
+            "NAME",
+            Occur::Optional,
+            None,
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        let engine = args.value_of::<String>("engine")?;
+        Ok(Self {
+            name: args.optional_value_of::<String>("name")?.unwrap_or_else(|| format!("rostware23 {}", engine)),
+            engine,
+        })
+    }
+}
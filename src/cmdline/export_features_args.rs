@@ -0,0 +1,81 @@
+use getopts::Occur;
+use args::Args;
+
+use crate::logic::features::{ExportFormat, FeatureLayout};
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct ExportFeaturesArgs {
+    pub output: String,
+    pub format: ExportFormat,
+    pub layout: FeatureLayout,
+    pub games: u64,
+    pub seed: u64
+}
+
+impl ExportFeaturesArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Exports the feature vectors of every position of seeded random games",
+        );
+        args.option(
+            "o",
+            "output",
+            "The file the features are written to",
+            "FILE",
+            Occur::Req,
+            Some("features.csv".to_string()),
+        );
+        args.option(
+            "f",
+            "format",
+            "Either csv with a header of feature names or binary",
+            "FORMAT",
+            Occur::Req,
+            Some("csv".to_string()),
+        );
+        args.option(
+            "",
+            "layout",
+            "The version of the feature layout",
+            "VERSION",
+            Occur::Req,
+            Some(FeatureLayout::LATEST.version().to_string()),
+        );
+        args.option(
+            "g",
+            "games",
+            "The amount of games to export",
+            "GAMES",
+            Occur::Req,
+            Some("10".to_string()),
+        );
+        args.option(
+            "",
+            "seed",
+            "The seed of the boards and moves",
+            "SEED",
+            Occur::Req,
+            Some("0".to_string()),
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        Ok(Self {
+            output: args.value_of::<String>("output")?,
+            format: ExportFormat::from_name(&args.value_of::<String>("format")?)?,
+            layout: FeatureLayout::from_version(args.value_of::<String>("layout")?.parse()?)?,
+            games: args.value_of::<String>("games")?.parse()?,
+            seed: args.value_of::<String>("seed")?.parse()?,
+        })
+    }
+}
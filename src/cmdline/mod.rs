@@ -1,20 +1,54 @@
 extern crate args;
 extern crate getopts;
 
-use std::env;
+pub mod analyze_args;
+pub mod command;
+pub mod engine_args;
+pub mod export_features_args;
+pub mod play_args;
+pub mod replay_args;
+pub mod replay_transcript_args;
+pub mod self_play_args;
+pub mod tournament_args;
+pub mod train_args;
+pub mod tune_args;
+
+use std::path::Path;
 
 use getopts::Occur;
 use args::Args;
 
+use rostware23_lib::client::game_client::DEFAULT_SAFETY_MARGIN_IN_MILLIS;
 use rostware23_lib::game::protocol::*;
 use rostware23_lib::game::server::*;
+use rostware23_lib::game::transcript::TranscriptRecorder;
+
+use crate::logic::hash_transposition_table::DEFAULT_SIZE_IN_MEGABYTES;
+
+pub fn parse_or_print_usage(args: &mut Args, env_args: Vec<String>) -> anyhow::Result<()> {
+    let parse_result = args.parse(env_args);
+
+    if let Err(error) = parse_result {
+        let usage = args.full_usage();
+        println!("{}", usage);
+        anyhow::bail!(error);
+    }
+    Ok(())
+}
 
 #[derive(Debug, Clone)]
 pub struct ClientArgs {
     pub host: String,
     pub port: i32,
     pub reservation: Option<String>,
-    pub room: Option<String>
+    pub room: Option<String>,
+    pub record_directory: Option<String>,
+    pub safety_margin_in_millis: u64,
+    pub hash_size_in_megabytes: usize,
+    pub threads: usize,
+    pub evaluation_config: Option<String>,
+    pub model: Option<String>,
+    pub trace_evaluation: bool,
 }
 
 impl ClientArgs {
@@ -55,6 +89,59 @@ impl ClientArgs {
             Occur::Optional,
             None
         );
+        args.option(
+            "",
+            "safety-margin",
+            "Milliseconds before the server's time limit at which the move should be sent.",
+            "MILLIS",
+            Occur::Req,
+            Some(DEFAULT_SAFETY_MARGIN_IN_MILLIS.to_string()),
+        );
+        args.option(
+            "",
+            "hash",
+            "The memory budget of the transposition table in megabytes.",
+            "MEGABYTES",
+            Occur::Req,
+            Some(DEFAULT_SIZE_IN_MEGABYTES.to_string()),
+        );
+        args.option(
+            "",
+            "threads",
+            "The amount of threads used for searching moves.",
+            "THREADS",
+            Occur::Req,
+            Some("1".to_string()),
+        );
+        args.option(
+            "",
+            "evaluation",
+            "A file with the weights of the evaluation components per game phase.",
+            "FILE",
+            Occur::Optional,
+            None
+        );
+        args.option(
+            "",
+            "model",
+            "A file with a learned evaluation model, used instead of the weighted evaluation.",
+            "FILE",
+            Occur::Optional,
+            None
+        );
+        args.flag(
+            "",
+            "trace-evaluation",
+            "Prints the rating components of the root and the principal variation leaf after every search."
+        );
+        args.option(
+            "",
+            "record",
+            "Records all protocol messages to a transcript file in the given directory.",
+            "DIRECTORY",
+            Occur::Optional,
+            None
+        );
 
         args
     }
@@ -64,31 +151,38 @@ impl ClientArgs {
         let port_string = args.value_of::<String>("port")?;
         let reservation = args.optional_value_of::<String>("reservation")?;
         let room = args.optional_value_of::<String>("room")?;
+        let record_directory = args.optional_value_of::<String>("record")?;
+        let evaluation_config = args.optional_value_of::<String>("evaluation")?;
+        let model = args.optional_value_of::<String>("model")?;
+        let trace_evaluation = args.value_of::<bool>("trace-evaluation")?;
+        let safety_margin_string = args.value_of::<String>("safety-margin")?;
+        let hash_size_string = args.value_of::<String>("hash")?;
+        let threads_string = args.value_of::<String>("threads")?;
 
         let port = port_string.parse::<i32>()?;
+        let safety_margin_in_millis = safety_margin_string.parse::<u64>()?;
+        let hash_size_in_megabytes = hash_size_string.parse::<usize>()?;
+        let threads = threads_string.parse::<usize>()?;
         Ok(Self {
             host,
             port,
             reservation,
             room,
+            record_directory,
+            safety_margin_in_millis,
+            hash_size_in_megabytes,
+            threads,
+            evaluation_config,
+            model,
+            trace_evaluation,
         })
     }
 
-    pub fn parse() -> anyhow::Result<Self> {
-        let env_args: Vec<String> = env::args().collect();
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
         let program = env_args.first().unwrap();
 
         let mut args = Self::setup_args(program);
-
-        let parse_result = args.parse(env_args);
-
-        if parse_result.is_err() {
-            let usage = args.full_usage();
-            println!("{}", usage);
-
-            let error = parse_result.unwrap_err();
-            anyhow::bail!(error);
-        }
+        parse_or_print_usage(&mut args, env_args)?;
 
         Self::create_client_args(args)
     }
@@ -100,7 +194,12 @@ impl TryInto<Protocol> for ClientArgs {
     fn try_into(self) -> Result<Protocol, Self::Error> {
         println!("Got args: {:?}", self);
         let address = format!("{}:{}", self.host, self.port);
-        let connection = Connection::connect(&address)?;
+        let mut connection = Connection::connect(&address)?;
+        if let Some(record_directory) = &self.record_directory {
+            let (recorder, path) = TranscriptRecorder::create_in_directory(Path::new(record_directory))?;
+            println!("Recording protocol messages to {}", path.display());
+            connection = connection.with_recorder(recorder);
+        }
         let mut protocol = Protocol::from_connection(connection);
 
         match (self.room, self.reservation) {
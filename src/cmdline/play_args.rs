@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use getopts::Occur;
+use args::Args;
+
+use rostware23_lib::xml::common::Team;
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct PlayArgs {
+    pub human_team: Team,
+    pub engine: String,
+    pub think_time_in_millis: u128,
+    pub board_seed: Option<u64>,
+    pub output: Option<PathBuf>
+}
+
+impl PlayArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Plays a game against an engine in the terminal",
+        );
+        args.option(
+            "c",
+            "colour",
+            "The team you play, one (G, moves first) or two (P)",
+            "one|two",
+            Occur::Req,
+            Some("one".to_string()),
+        );
+        args.option(
+            "e",
+            "engine",
+            "The engine playing against you",
+            "ENGINE",
+            Occur::Req,
+            Some("pvs".to_string()),
+        );
+        args.option(
+            "t",
+            "time",
+            "The think time of the engine per move in milliseconds",
+            "MILLIS",
+            Occur::Req,
+            Some("1000".to_string()),
+        );
+        args.option(
+            "s",
+            "seed",
+            "The seed of the board, a random board is used otherwise",
+            "SEED",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "o",
+            "output",
+            "Saves the game record to this file once the game is over",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        let human_team = match args.value_of::<String>("colour")?.as_str() {
+            "one" => Team::One,
+            "two" => Team::Two,
+            colour => anyhow::bail!("Unknown colour '{}', expected one or two", colour)
+        };
+        Ok(Self {
+            human_team,
+            engine: args.value_of::<String>("engine")?,
+            think_time_in_millis: args.value_of::<String>("time")?.parse()?,
+            board_seed: args.optional_value_of::<String>("seed")?.map(|seed| seed.parse()).transpose()?,
+            output: args.optional_value_of::<String>("output")?.map(PathBuf::from),
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use getopts::Occur;
+use args::Args;
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct ReplayArgs {
+    pub record: String,
+    pub start_position: usize,
+    pub review_depth: Option<i32>,
+    pub evaluation_config: Option<String>,
+    pub model: Option<String>,
+    pub html: Option<String>
+}
+
+impl ReplayArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Steps through a recorded game in the terminal",
+        );
+        args.option(
+            "r",
+            "record",
+            "The game record to replay",
+            "FILE",
+            Occur::Req,
+            None,
+        );
+        args.option(
+            "m",
+            "move",
+            "The number of moves to start after",
+            "NUMBER",
+            Occur::Req,
+            Some("0".to_string()),
+        );
+        args.option(
+            "d",
+            "review-depth",
+            "Shows the move the engine finds at this depth and the eval swing of every played move",
+            "DEPTH",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "evaluation",
+            "A file with the weights of the evaluation components per game phase",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "model",
+            "A file with a learned evaluation model, used instead of the weighted evaluation",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "html",
+            "Exports the game as an animated HTML page to this file instead of replaying it",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        Ok(Self {
+            record: args.value_of::<String>("record")?,
+            start_position: args.value_of::<String>("move")?.parse()?,
+            review_depth: args.optional_value_of::<String>("review-depth")?.map(|depth| depth.parse()).transpose()?,
+            evaluation_config: args.optional_value_of::<String>("evaluation")?,
+            model: args.optional_value_of::<String>("model")?,
+            html: args.optional_value_of::<String>("html")?,
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use getopts::Occur;
+use args::Args;
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct ReplayTranscriptArgs {
+    pub transcript: String,
+    pub engine: String
+}
+
+impl ReplayTranscriptArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Replays a recorded protocol transcript and compares the moves of an engine to the recorded ones",
+        );
+        args.option(
+            "t",
+            "transcript",
+            "The transcript file written by the client's --record option",
+            "FILE",
+            Occur::Req,
+            None,
+        );
+        args.option(
+            "e",
+            "engine",
+            "The engine choosing the moves (pvs, pvs-fixed or random)",
+            "ENGINE",
+            Occur::Req,
+            Some("pvs".to_string()),
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        Ok(Self {
+            transcript: args.value_of::<String>("transcript")?,
+            engine: args.value_of::<String>("engine")?,
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use getopts::Occur;
+use args::Args;
+
+use crate::logic::self_play::{SelfPlaySettings, TrainingDataFormat, DEFAULT_SHARD_SIZE};
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct SelfPlayArgs {
+    pub settings: SelfPlaySettings,
+    pub engine: String,
+    pub opponent: String,
+    pub format: TrainingDataFormat,
+    pub output: String,
+    pub shard_size: usize
+}
+
+impl SelfPlayArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Plays seeded games and writes every position with its search score, chosen move and outcome as training data",
+        );
+        args.option(
+            "e",
+            "engine",
+            "The engine playing team one",
+            "ENGINE",
+            Occur::Req,
+            Some("pvs".to_string()),
+        );
+        args.option(
+            "",
+            "opponent",
+            "The engine playing team two, the same as team one if not given",
+            "ENGINE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "g",
+            "games",
+            "The amount of games, every board is started by team one in even and by team two in odd games",
+            "GAMES",
+            Occur::Req,
+            Some("100".to_string()),
+        );
+        args.option(
+            "",
+            "seed",
+            "The seed of the boards and random moves",
+            "SEED",
+            Occur::Req,
+            Some("0".to_string()),
+        );
+        args.option(
+            "",
+            "time",
+            "The time limit per move in milliseconds",
+            "MILLIS",
+            Occur::Req,
+            Some("100".to_string()),
+        );
+        args.option(
+            "",
+            "exploration",
+            "The probability of playing a random move instead of the engine's",
+            "PROBABILITY",
+            Occur::Req,
+            Some("0.1".to_string()),
+        );
+        args.option(
+            "",
+            "exploration-plies",
+            "The amount of plies at the start of every game in which random moves are played",
+            "PLIES",
+            Occur::Req,
+            Some("16".to_string()),
+        );
+        args.option(
+            "f",
+            "format",
+            "Either jsonl or binary",
+            "FORMAT",
+            Occur::Req,
+            Some("jsonl".to_string()),
+        );
+        args.option(
+            "o",
+            "output",
+            "The directory the shards are written to",
+            "DIRECTORY",
+            Occur::Req,
+            Some("selfplay".to_string()),
+        );
+        args.option(
+            "",
+            "shard-size",
+            "The amount of positions per shard",
+            "POSITIONS",
+            Occur::Req,
+            Some(DEFAULT_SHARD_SIZE.to_string()),
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        let settings = SelfPlaySettings {
+            games: args.value_of::<String>("games")?.parse()?,
+            seed: args.value_of::<String>("seed")?.parse()?,
+            time_limit_in_millis: args.value_of::<String>("time")?.parse()?,
+            exploration: args.value_of::<String>("exploration")?.parse()?,
+            exploration_plies: args.value_of::<String>("exploration-plies")?.parse()?,
+        };
+        let engine = args.value_of::<String>("engine")?;
+        Ok(Self {
+            settings,
+            opponent: args.optional_value_of::<String>("opponent")?.unwrap_or_else(|| engine.clone()),
+            engine,
+            format: TrainingDataFormat::from_name(&args.value_of::<String>("format")?)?,
+            output: args.value_of::<String>("output")?,
+            shard_size: args.value_of::<String>("shard-size")?.parse()?,
+        })
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use getopts::Occur;
+use args::Args;
+
+use crate::logic::tournament::{EngineConfig, Sprt, TournamentFormat, TournamentSettings};
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct TournamentArgs {
+    pub engines: Vec<EngineConfig>,
+    pub settings: TournamentSettings,
+    pub output: String
+}
+
+impl TournamentArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Plays matches between engines and estimates their Elo differences",
+        );
+        args.option(
+            "e",
+            "engines",
+            "Comma separated engines as NAME=ENGINE@MILLIS, name and time limit are optional. The first one is the candidate of a gauntlet",
+            "ENGINES",
+            Occur::Req,
+            Some("pvs,pvs-weighted".to_string()),
+        );
+        args.option(
+            "f",
+            "format",
+            "Either round-robin or gauntlet",
+            "FORMAT",
+            Occur::Req,
+            Some("round-robin".to_string()),
+        );
+        args.option(
+            "g",
+            "games",
+            "The amount of game pairs per pairing, both engines start once on every board",
+            "PAIRS",
+            Occur::Req,
+            Some("50".to_string()),
+        );
+        args.option(
+            "",
+            "seed",
+            "The seed of the first board",
+            "SEED",
+            Occur::Req,
+            Some("0".to_string()),
+        );
+        args.option(
+            "",
+            "time",
+            "The time limit per move in milliseconds of engines without their own",
+            "MILLIS",
+            Occur::Req,
+            Some("100".to_string()),
+        );
+        args.option(
+            "",
+            "threads",
+            "The amount of game pairs played at the same time",
+            "THREADS",
+            Occur::Req,
+            Some("1".to_string()),
+        );
+        args.option(
+            "",
+            "record",
+            "Writes the record of every game to a subdirectory per pairing in the given directory",
+            "DIRECTORY",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "sprt",
+            "Stops a pairing once the first engine is shown to be at most ELO0 or at least ELO1 stronger",
+            "ELO0,ELO1",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "alpha",
+            "The false positive rate of the SPRT",
+            "ALPHA",
+            Occur::Req,
+            Some("0.05".to_string()),
+        );
+        args.option(
+            "",
+            "beta",
+            "The false negative rate of the SPRT",
+            "BETA",
+            Occur::Req,
+            Some("0.05".to_string()),
+        );
+        args.option(
+            "o",
+            "output",
+            "The summary file, rewritten after every game pair",
+            "FILE",
+            Occur::Req,
+            Some("tournament.txt".to_string()),
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        let sprt = match args.optional_value_of::<String>("sprt")? {
+            Some(bounds) => {
+                let Some((elo0, elo1)) = bounds.split_once(',') else {
+                    anyhow::bail!("Expected the SPRT bounds as ELO0,ELO1, got '{}'", bounds);
+                };
+                Some(Sprt {
+                    elo0: elo0.trim().parse()?,
+                    elo1: elo1.trim().parse()?,
+                    alpha: args.value_of::<String>("alpha")?.parse()?,
+                    beta: args.value_of::<String>("beta")?.parse()?,
+                })
+            },
+            None => None
+        };
+        let settings = TournamentSettings {
+            format: TournamentFormat::from_name(&args.value_of::<String>("format")?)?,
+            game_pairs: args.value_of::<String>("games")?.parse()?,
+            first_board_seed: args.value_of::<String>("seed")?.parse()?,
+            time_limit_in_millis: args.value_of::<String>("time")?.parse()?,
+            threads: args.value_of::<String>("threads")?.parse()?,
+            record_directory: args.optional_value_of::<String>("record")?.map(PathBuf::from),
+            sprt,
+        };
+        Ok(Self {
+            engines: args.value_of::<String>("engines")?.split(',').map(EngineConfig::from_spec).collect::<anyhow::Result<_>>()?,
+            settings,
+            output: args.value_of::<String>("output")?,
+        })
+    }
+}
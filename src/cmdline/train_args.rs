@@ -0,0 +1,111 @@
+use getopts::Occur;
+use args::Args;
+
+use crate::logic::features::FeatureLayout;
+use crate::logic::linear_trainer::{TrainingMethod, TrainingSettings};
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct TrainArgs {
+    pub settings: TrainingSettings,
+    pub input: String,
+    pub output: String
+}
+
+impl TrainArgs {
+    fn setup_args(program: &str) -> Args {
+        let defaults = TrainingSettings::default();
+        let mut args = Args::new(
+            program,
+            "Trains a linear evaluation model on self-play training data",
+        );
+        args.option(
+            "i",
+            "input",
+            "A training data shard or a directory of shards",
+            "PATH",
+            Occur::Req,
+            Some("selfplay".to_string()),
+        );
+        args.option(
+            "o",
+            "output",
+            "The file the model is written to",
+            "FILE",
+            Occur::Req,
+            Some("model.txt".to_string()),
+        );
+        args.option(
+            "m",
+            "method",
+            "Either least-squares to fit the search scores or logistic to fit the game outcomes",
+            "METHOD",
+            Occur::Req,
+            Some("least-squares".to_string()),
+        );
+        args.option(
+            "",
+            "layout",
+            "The version of the feature layout",
+            "VERSION",
+            Occur::Req,
+            Some(defaults.layout.version().to_string()),
+        );
+        args.option(
+            "",
+            "l2",
+            "The ridge penalty on the weights of the standardized features",
+            "PENALTY",
+            Occur::Req,
+            Some(defaults.l2.to_string()),
+        );
+        args.option(
+            "",
+            "iterations",
+            "The amount of gradient descent steps of the logistic regression",
+            "ITERATIONS",
+            Occur::Req,
+            Some(defaults.iterations.to_string()),
+        );
+        args.option(
+            "",
+            "learning-rate",
+            "The step size of the logistic regression",
+            "RATE",
+            Occur::Req,
+            Some(defaults.learning_rate.to_string()),
+        );
+        args.option(
+            "",
+            "scale",
+            "The evaluation of even log odds of winning for logistic models",
+            "SCALE",
+            Occur::Req,
+            Some(defaults.scale.to_string()),
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        let settings = TrainingSettings {
+            method: TrainingMethod::from_name(&args.value_of::<String>("method")?)?,
+            layout: FeatureLayout::from_version(args.value_of::<String>("layout")?.parse()?)?,
+            l2: args.value_of::<String>("l2")?.parse()?,
+            iterations: args.value_of::<String>("iterations")?.parse()?,
+            learning_rate: args.value_of::<String>("learning-rate")?.parse()?,
+            scale: args.value_of::<String>("scale")?.parse()?,
+        };
+        Ok(Self {
+            settings,
+            input: args.value_of::<String>("input")?,
+            output: args.value_of::<String>("output")?,
+        })
+    }
+}
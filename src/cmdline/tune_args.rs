@@ -0,0 +1,148 @@
+use getopts::Occur;
+use args::Args;
+
+use crate::logic::spsa_tuner::{SpsaSettings, DEFAULT_LEARNING_RATE, DEFAULT_PERTURBATION};
+
+use super::parse_or_print_usage;
+
+#[derive(Debug, Clone)]
+pub struct TuneArgs {
+    pub settings: SpsaSettings,
+    pub start: Option<String>,
+    pub first_iteration: usize,
+    pub output: String,
+    pub checkpoint_directory: Option<String>,
+    pub checkpoint_interval: usize
+}
+
+impl TuneArgs {
+    fn setup_args(program: &str) -> Args {
+        let mut args = Args::new(
+            program,
+            "Tunes the weights of the evaluation with SPSA by letting perturbed weights play against each other",
+        );
+        args.option(
+            "",
+            "start",
+            "The evaluation config to start from, the client's default weights if not given",
+            "FILE",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "o",
+            "output",
+            "The file the tuned weights are written to, loadable with the client's --evaluation option",
+            "FILE",
+            Occur::Req,
+            Some("weights.txt".to_string()),
+        );
+        args.option(
+            "i",
+            "iterations",
+            "The amount of SPSA iterations",
+            "ITERATIONS",
+            Occur::Req,
+            Some("200".to_string()),
+        );
+        args.option(
+            "",
+            "first-iteration",
+            "The iteration to continue with when resuming from a checkpoint",
+            "ITERATION",
+            Occur::Req,
+            Some("0".to_string()),
+        );
+        args.option(
+            "g",
+            "games",
+            "The amount of boards per iteration, each played with both start teams",
+            "GAMES",
+            Occur::Req,
+            Some("4".to_string()),
+        );
+        args.option(
+            "",
+            "threads",
+            "The amount of games played in parallel",
+            "THREADS",
+            Occur::Req,
+            Some("1".to_string()),
+        );
+        args.option(
+            "",
+            "time",
+            "The time limit per move in milliseconds",
+            "MILLIS",
+            Occur::Req,
+            Some("30".to_string()),
+        );
+        args.option(
+            "",
+            "learning-rate",
+            "The initial step size of the weights",
+            "RATE",
+            Occur::Req,
+            Some(DEFAULT_LEARNING_RATE.to_string()),
+        );
+        args.option(
+            "",
+            "perturbation",
+            "The initial perturbation of the weights",
+            "SIZE",
+            Occur::Req,
+            Some(DEFAULT_PERTURBATION.to_string()),
+        );
+        args.option(
+            "",
+            "seed",
+            "The seed of the perturbations and boards",
+            "SEED",
+            Occur::Req,
+            Some("0".to_string()),
+        );
+        args.option(
+            "",
+            "checkpoints",
+            "A directory in which the weights are kept after every checkpoint",
+            "DIRECTORY",
+            Occur::Optional,
+            None,
+        );
+        args.option(
+            "",
+            "checkpoint-interval",
+            "The amount of iterations between checkpoints",
+            "ITERATIONS",
+            Occur::Req,
+            Some("10".to_string()),
+        );
+
+        args
+    }
+
+    pub fn parse(env_args: Vec<String>) -> anyhow::Result<Self> {
+        let program = env_args.first().unwrap();
+
+        let mut args = Self::setup_args(program);
+        parse_or_print_usage(&mut args, env_args)?;
+
+        let settings = SpsaSettings {
+            iterations: args.value_of::<String>("iterations")?.parse()?,
+            games_per_iteration: args.value_of::<String>("games")?.parse()?,
+            threads: args.value_of::<String>("threads")?.parse()?,
+            time_limit_in_millis: args.value_of::<String>("time")?.parse()?,
+            learning_rate: args.value_of::<String>("learning-rate")?.parse()?,
+            perturbation: args.value_of::<String>("perturbation")?.parse()?,
+            seed: args.value_of::<String>("seed")?.parse()?,
+        };
+        Ok(Self {
+            settings,
+            start: args.optional_value_of::<String>("start")?,
+            first_iteration: args.value_of::<String>("first-iteration")?.parse()?,
+            output: args.value_of::<String>("output")?,
+            checkpoint_directory: args.optional_value_of::<String>("checkpoints")?,
+            checkpoint_interval: args.value_of::<String>("checkpoint-interval")?.parse::<usize>()?.max(1),
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::record::GameRecord;
+use rostware23_lib::game::state::State;
+use rostware23_lib::game::svg::SvgRenderer;
+use rostware23_lib::xml::common::Team;
+
+use crate::cmdline::analyze_args::AnalyzeArgs;
+use crate::logic::{Evaluator, MoveGetter};
+use crate::logic::board_view::labelled_board;
+use crate::logic::pvs_getter::PVSMoveGetter;
+use crate::logic::selected_evaluator::SelectedEvaluator;
+use crate::logic::time_measurer::TimeMeasurer;
+
+fn notation_of_moves(moves: &[Move]) -> String {
+    moves.iter().map(Move::to_notation).collect::<Vec<String>>().join(" ")
+}
+
+// The state in which the given move of the record is played, counting from 1
+fn state_of_record(record: &GameRecord, move_number: Option<usize>) -> anyhow::Result<State> {
+    let mut states = record.states()?;
+    let index = match move_number {
+        Some(0) => anyhow::bail!("Moves are counted from 1"),
+        Some(number) if number > states.len() => anyhow::bail!("The record only has {} moves", record.moves.len()),
+        Some(number) => number - 1,
+        None => states.len() - 1
+    };
+    Ok(states.swap_remove(index))
+}
+
+// The search gets half of the time, ranking the root moves the rest
+fn create_time_measurer(args: &AnalyzeArgs, used_millis: Option<u128>) -> TimeMeasurer {
+    match (args.time_in_millis, used_millis) {
+        (Some(millis), None) => TimeMeasurer::new(millis / 2),
+        (Some(millis), Some(used_millis)) => TimeMeasurer::new(millis.saturating_sub(used_millis)),
+        (None, _) => TimeMeasurer::new_infinite()
+    }
+}
+
+fn analyze<Heuristic: Evaluator + Sync>(evaluator: Heuristic, state: &State, args: &AnalyzeArgs) -> anyhow::Result<()> {
+    let mut move_getter = PVSMoveGetter::with_evaluator(evaluator).with_evaluation_trace();
+    if let Some(depth) = args.depth {
+        move_getter = move_getter.with_max_depth(depth);
+    }
+    let report = move_getter.search(state, &create_time_measurer(args, None))?;
+
+    println!("Iterations");
+    for iteration in &report.iterations {
+        let best_move = iteration.best_move.as_ref().map(Move::to_notation).unwrap_or_else(|| "-".to_string());
+        println!("  depth {:>2}{}: {:<6} score {:>7}, {} nodes, {}ms, pv {}", iteration.depth, if iteration.partial { " (partial)" } else { "" },
+            best_move, iteration.score, iteration.nodes, iteration.elapsed_millis, notation_of_moves(&iteration.principal_variation));
+    }
+    println!("Best move {} with score {} at depth {}", report.best_move.to_notation(), report.score, report.completed_depth);
+    println!("Principal variation: {}", notation_of_moves(&report.principal_variation));
+    if let Some(evaluation_trace) = &report.evaluation_trace {
+        println!("\n{}", evaluation_trace);
+    }
+    if let Some(path) = &args.svg {
+        let svg = SvgRenderer::new().with_principal_variation(state, &report.principal_variation)?.render_state(state)?;
+        fs::write(path, svg).with_context(|| format!("Could not write {}", path))?;
+        println!("Drew the principal variation to {}", path);
+    }
+
+    let depth = report.completed_depth.max(1);
+    let scored_moves = move_getter.score_root_moves(state, depth, &create_time_measurer(args, Some(report.time_used_millis)))?;
+    println!("\nRoot moves at depth {}", depth);
+    for (rank, (root_move, score)) in scored_moves.iter().enumerate() {
+        println!("  {:>3}. {:<6} {:>7}", rank + 1, root_move.to_notation(), score);
+    }
+    let unscored = state.possible_moves().count() - scored_moves.len();
+    if unscored > 0 {
+        println!("  {} moves were not searched in time", unscored);
+    }
+    Ok(())
+}
+
+pub fn run(args: AnalyzeArgs) -> anyhow::Result<()> {
+    let state = match (&args.state, &args.record) {
+        (Some(notation), _) => State::from_notation(notation)?,
+        (None, Some(path)) => state_of_record(&GameRecord::load(Path::new(path))?, args.move_number)?,
+        (None, None) => anyhow::bail!("No position given")
+    };
+    if state.is_over() {
+        anyhow::bail!("The game is already over");
+    }
+    let state = state.with_moveless_player_skipped()?;
+
+    println!("{}", labelled_board(&state.board));
+    println!("{}", state.to_notation());
+    println!("Turn {}, team {:?} to move, fish {}:{}\n", state.turn + 1, state.current_team(), state.score_of_team(Team::One), state.score_of_team(Team::Two));
+
+    let evaluator = SelectedEvaluator::from_files(args.model.as_deref(), args.evaluation_config.as_deref())?;
+    analyze(evaluator, &state, &args)
+}
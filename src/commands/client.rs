@@ -0,0 +1,33 @@
+use rostware23_lib::client::GameClient;
+use rostware23_lib::game::protocol::Protocol;
+
+use crate::cmdline::ClientArgs;
+use crate::logic::{Evaluator, MoveGetter};
+use crate::logic::move_getter_player::MoveGetterPlayer;
+use crate::logic::pvs_getter::PVSMoveGetter;
+use crate::logic::selected_evaluator::SelectedEvaluator;
+
+fn create_move_getter<Heuristic: Evaluator + Send + Sync + 'static>(evaluator: Heuristic, args: &ClientArgs) -> Box<dyn MoveGetter + Send> {
+    let move_getter = PVSMoveGetter::with_evaluator(evaluator)
+        .with_transposition_table_size_in_megabytes(args.hash_size_in_megabytes)
+        .with_threads(args.threads);
+    if args.trace_evaluation {
+        Box::new(move_getter.with_evaluation_trace())
+    } else {
+        Box::new(move_getter)
+    }
+}
+
+pub fn run(args: ClientArgs) -> anyhow::Result<()> {
+    let safety_margin_in_millis = args.safety_margin_in_millis;
+    let evaluator = SelectedEvaluator::from_files(args.model.as_deref(), args.evaluation_config.as_deref())?;
+    let move_getter = create_move_getter(evaluator, &args);
+    let protocol: Protocol = args.try_into()?;
+
+    let player = MoveGetterPlayer::new(move_getter);
+    let mut client = GameClient::new(protocol).with_safety_margin_in_millis(safety_margin_in_millis);
+    let (finished_games, _) = client.run(player)?;
+    println!("Finished {} game(s)", finished_games);
+
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use std::io;
+
+use crate::cmdline::engine_args::EngineArgs;
+use crate::logic::MoveGetter;
+use crate::logic::engine_protocol::serve;
+use crate::logic::move_getter_factory::create_move_getter;
+
+pub fn run(args: EngineArgs) -> anyhow::Result<()> {
+    let create_engine = || -> anyhow::Result<Box<dyn MoveGetter>> { Ok(create_move_getter(&args.engine)?) };
+    serve(&args.name, &create_engine, io::stdin().lock(), &mut io::stdout().lock())
+}
@@ -0,0 +1,33 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use anyhow::Context;
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::SeedableRng;
+use rostware23_lib::game::state::State;
+use rostware23_lib::game::state_generator::create_board_from_seed;
+
+use crate::cmdline::export_features_args::ExportFeaturesArgs;
+use crate::logic::features::FeatureWriter;
+
+pub fn run(args: ExportFeaturesArgs) -> anyhow::Result<()> {
+    let file = File::create(&args.output).with_context(|| format!("Could not create {}", args.output))?;
+    let mut writer = FeatureWriter::new(BufWriter::new(file), args.layout, args.format)?;
+
+    for game in 0..args.games {
+        let seed = args.seed.wrapping_add(game);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut state = State::from_initial_board_with_start_team_one(create_board_from_seed(seed));
+        while !state.is_over() {
+            state = state.with_moveless_player_skipped()?;
+            writer.write_state(&state)?;
+            let chosen_move = state.possible_moves().choose(&mut rng).context("No possible moves found")?;
+            state.perform_move(chosen_move)?;
+        }
+    }
+
+    println!("Wrote {} feature vectors of layout version {} to {}", writer.rows(), args.layout.version(), args.output);
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,11 @@
+pub mod analyze;
+pub mod client;
+pub mod engine;
+pub mod export_features;
+pub mod play;
+pub mod replay;
+pub mod replay_transcript;
+pub mod self_play;
+pub mod tournament;
+pub mod train;
+pub mod tune;
@@ -0,0 +1,98 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use rostware23_lib::game::state::State;
+use rostware23_lib::game::state_generator::{create_any_board, create_board_from_seed};
+
+use crate::cmdline::play_args::PlayArgs;
+use crate::logic::move_getter_factory::create_move_getter;
+use crate::logic::play_session::PlaySession;
+
+const HELP: &str = "Commands:
+  c3          place a penguin on c3
+  a2-e2       move the penguin on a2 to e2
+  moves       list all possible moves
+  hint        let the engine suggest a move
+  undo        take back your last move
+  save FILE   save the game record to FILE
+  help        show this help
+  quit        end the game";
+
+fn play_engine_moves(session: &mut PlaySession) -> anyhow::Result<()> {
+    while !session.is_human_turn()? && !session.state()?.is_over() {
+        let report = session.play_engine_move()?;
+        println!("Engine plays {} (score {}, depth {})", report.best_move.to_notation(), report.score, report.completed_depth);
+    }
+    Ok(())
+}
+
+fn handle_input(session: &mut PlaySession, input: &str) -> anyhow::Result<()> {
+    let (command, argument) = input.split_once(' ').unwrap_or((input, ""));
+    match command {
+        "help" => println!("{}", HELP),
+        "moves" => {
+            let moves: Vec<String> = session.state()?.possible_moves().map(|possible_move| possible_move.to_notation()).collect();
+            println!("{}", moves.join(" "));
+        },
+        "hint" => {
+            let report = session.hint()?;
+            println!("Hint: {} (score {}, depth {})", report.best_move.to_notation(), report.score, report.completed_depth);
+        },
+        "undo" => {
+            let undone = session.undo()?;
+            println!("Took back {} moves", undone);
+        },
+        "save" => {
+            anyhow::ensure!(!argument.trim().is_empty(), "Usage: save FILE");
+            session.save(Path::new(argument.trim()))?;
+            println!("Saved the game to {}", argument.trim());
+        },
+        _ => {
+            session.play_human_move(input)?;
+            play_engine_moves(session)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: PlayArgs) -> anyhow::Result<()> {
+    let board = match args.board_seed {
+        Some(seed) => create_board_from_seed(seed),
+        None => create_any_board()
+    };
+    let engine = create_move_getter(&args.engine)?;
+    let mut session = PlaySession::new(State::from_initial_board_with_start_team_one(board), args.human_team, engine, &args.engine, args.think_time_in_millis);
+    println!("{}", HELP);
+    play_engine_moves(&mut session)?;
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        println!("\n{}", session);
+        if session.state()?.is_over() {
+            break;
+        }
+        print!("> ");
+        io::stdout().flush()?;
+        let input = match lines.next() {
+            Some(line) => line?,
+            None => break
+        };
+        let input = input.trim();
+        if input == "quit" {
+            break;
+        }
+        if !input.is_empty() {
+            if let Err(error) = handle_input(&mut session, input) {
+                println!("{}", error);
+            }
+        }
+    }
+
+    println!("{} moves played", session.record().moves.len());
+    if let Some(output) = &args.output {
+        session.save(output)?;
+        println!("Saved the game to {}", output.display());
+    }
+    Ok(())
+}
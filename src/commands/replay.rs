@@ -0,0 +1,80 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use anyhow::Context;
+
+use rostware23_lib::game::html_export::export_html;
+use rostware23_lib::game::record::GameRecord;
+use rostware23_lib::game::svg::SvgRenderer;
+
+use crate::cmdline::replay_args::ReplayArgs;
+use crate::logic::Evaluator;
+use crate::logic::game_replay::GameReplay;
+use crate::logic::pvs_getter::PVSMoveGetter;
+use crate::logic::selected_evaluator::SelectedEvaluator;
+
+const HELP: &str = "Commands:
+  (empty), n  next move
+  p           previous move
+  first       start of the game
+  last        end of the game
+  NUMBER      position after NUMBER moves
+  help        show this help
+  quit        end the replay";
+
+fn handle_input(replay: &mut GameReplay, input: &str) -> anyhow::Result<()> {
+    match input {
+        "" | "n" => anyhow::ensure!(replay.forward(), "This is the end of the game"),
+        "p" => anyhow::ensure!(replay.back(), "This is the start of the game"),
+        "first" => replay.go_to(0)?,
+        "last" => replay.go_to(replay.last_position())?,
+        "help" => println!("{}", HELP),
+        number => replay.go_to(number.parse().map_err(|_| anyhow::anyhow!("Unknown command '{}', type 'help' for all commands", number))?)?
+    }
+    Ok(())
+}
+
+fn replay_game<Heuristic: Evaluator>(mut replay: GameReplay, reviewer: Option<(PVSMoveGetter<Heuristic>, i32)>) -> anyhow::Result<()> {
+    println!("{}", HELP);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        println!("\n{}", replay);
+        if let Some((move_getter, depth)) = &reviewer {
+            if let Some(review) = replay.review(move_getter, *depth)? {
+                println!("{}", review);
+            }
+        }
+        print!("> ");
+        io::stdout().flush()?;
+        let input = match lines.next() {
+            Some(line) => line?,
+            None => break
+        };
+        let input = input.trim();
+        if input == "quit" {
+            break;
+        }
+        if let Err(error) = handle_input(&mut replay, input) {
+            println!("{}", error);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: ReplayArgs) -> anyhow::Result<()> {
+    let record = GameRecord::load(Path::new(&args.record))?;
+    if let Some(path) = &args.html {
+        fs::write(path, export_html(&record, &SvgRenderer::new())?).with_context(|| format!("Could not write {}", path))?;
+        println!("Exported the game to {}", path);
+        return Ok(());
+    }
+    let mut replay = GameReplay::new(record)?;
+    replay.go_to(args.start_position)?;
+    let reviewer = match args.review_depth {
+        Some(depth) => Some((PVSMoveGetter::with_evaluator(SelectedEvaluator::from_files(args.model.as_deref(), args.evaluation_config.as_deref())?), depth)),
+        None => None
+    };
+    replay_game(replay, reviewer)
+}
@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use rostware23_lib::client::replay::replay_transcript;
+use rostware23_lib::game::transcript::read_transcript_file;
+
+use crate::cmdline::replay_transcript_args::ReplayTranscriptArgs;
+use crate::logic::move_getter_factory::create_move_getter;
+use crate::logic::move_getter_player::MoveGetterPlayer;
+
+pub fn run(args: ReplayTranscriptArgs) -> anyhow::Result<()> {
+    let entries = read_transcript_file(Path::new(&args.transcript))?;
+    let player = MoveGetterPlayer::new(create_move_getter(&args.engine)?);
+    let report = replay_transcript(&entries, player)?;
+
+    for (index, comparison) in report.comparisons.iter().enumerate() {
+        let verdict = if comparison.matches() { "same" } else { "DIFFERENT" };
+        let chosen = match &comparison.chosen_move {
+            Ok(chosen_move) => format!("{:?}", chosen_move),
+            Err(error) => format!("nothing, choosing failed: {}", error)
+        };
+        println!("Move request {}: recorded {:?}, chosen {} ({})",
+                 index + 1, comparison.recorded_move, chosen, verdict);
+    }
+
+    println!("{} of {} moves match the transcript", report.matching_move_count(), report.comparisons.len());
+    if let Some((index, divergence)) = report.first_divergence() {
+        println!("First divergence at move request {} in state:\n{}", index + 1, divergence.state);
+    }
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use crate::cmdline::self_play_args::SelfPlayArgs;
+use crate::logic::move_getter_factory::create_move_getter;
+use crate::logic::self_play::{play_game, TrainingDataWriter};
+
+pub fn run(args: SelfPlayArgs) -> anyhow::Result<()> {
+    let engine_team_one = create_move_getter(&args.engine)?;
+    let engine_team_two = create_move_getter(&args.opponent)?;
+    let mut writer = TrainingDataWriter::new(Path::new(&args.output), args.format, args.shard_size)?;
+
+    for game in 0..args.settings.games {
+        let positions = play_game(engine_team_one.as_ref(), engine_team_two.as_ref(), game, &args.settings)?;
+        for position in &positions {
+            writer.write(position)?;
+        }
+        let outcome = positions.first().map_or(0, |position| position.outcome);
+        println!("Game {}: {} positions, outcome {:+} for the start team", game + 1, positions.len(), outcome);
+    }
+
+    let positions = writer.positions();
+    let shards = writer.finish()?;
+    println!("Wrote {} positions in {} shards to {}", positions, shards.len(), args.output);
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use std::fs;
+
+use anyhow::Context;
+
+use crate::cmdline::tournament_args::TournamentArgs;
+use crate::logic::tournament::Tournament;
+
+pub fn run(args: TournamentArgs) -> anyhow::Result<()> {
+    let mut tournament = Tournament::new(args.engines, args.settings)?;
+    let output = args.output;
+
+    tournament.run(|tournament| {
+        if let Some(result) = tournament.results().last() {
+            let sprt = result.sprt.map_or(String::new(), |status| format!(", SPRT {}", status));
+            println!("{} vs {}: {}, elo {}{}", result.first, result.second, result.score, result.score.elo(), sprt);
+        }
+        fs::write(&output, tournament.to_string()).with_context(|| format!("Could not write summary to {}", output))
+    })?;
+
+    println!("\n{}", tournament);
+    println!("Summary written to {}", output);
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::cmdline::train_args::TrainArgs;
+use crate::logic::Evaluator;
+use crate::logic::learned_evaluator::LearnedEvaluator;
+use crate::logic::linear_trainer::train;
+use crate::logic::self_play::read_training_data;
+
+pub fn run(args: TrainArgs) -> anyhow::Result<()> {
+    let positions = read_training_data(Path::new(&args.input))?;
+    println!("Training on {} positions from {}", positions.len(), args.input);
+    let result = train(&positions, &args.settings)?;
+    let evaluator = LearnedEvaluator::new(result.model);
+
+    let mut quantization_error: f64 = 0.0;
+    for position in &positions {
+        let features = evaluator.model().layout.extract(&position.state)?;
+        quantization_error = quantization_error.max((evaluator.model().predict(&features) - evaluator.evaluate(&position.state) as f64).abs());
+    }
+    std::fs::write(&args.output, evaluator.model().to_string()).with_context(|| format!("Could not write {}", args.output))?;
+    println!("Wrote model to {}, loss {:.4} on {} positions, quantization error at most {:.2}", args.output, result.loss, result.samples, quantization_error);
+    Ok(())
+}
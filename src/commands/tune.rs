@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::cmdline::tune_args::TuneArgs;
+use crate::logic::spsa_tuner::SpsaTuner;
+use crate::logic::weighted_evaluator::WeightedEvaluator;
+
+fn write_weights(path: &Path, tuner: &SpsaTuner) -> anyhow::Result<()> {
+    let content = format!("# SPSA iteration {}\n{}", tuner.iteration(), tuner.evaluator());
+    fs::write(path, content).with_context(|| format!("Could not write weights to {}", path.display()))
+}
+
+fn write_checkpoint(args: &TuneArgs, tuner: &SpsaTuner) -> anyhow::Result<()> {
+    write_weights(Path::new(&args.output), tuner)?;
+    if let Some(checkpoint_directory) = &args.checkpoint_directory {
+        let checkpoint_directory = Path::new(checkpoint_directory);
+        fs::create_dir_all(checkpoint_directory)?;
+        let path = checkpoint_directory.join(format!("weights-{}.txt", tuner.iteration()));
+        write_weights(&path, tuner)?;
+        println!("Wrote checkpoint {}", path.display());
+    }
+    Ok(())
+}
+
+pub fn run(args: TuneArgs) -> anyhow::Result<()> {
+    let evaluator = match &args.start {
+        Some(path) => WeightedEvaluator::from_file(Path::new(path))?,
+        None => WeightedEvaluator::default()
+    };
+    let mut tuner = SpsaTuner::new(evaluator, args.settings.clone()).with_first_iteration(args.first_iteration);
+
+    while !tuner.is_finished() {
+        let step = tuner.step()?;
+        println!("Iteration {}: score {:+.3}, weights {:?}", step.iteration, step.score, step.weights);
+        if step.iteration % args.checkpoint_interval == 0 {
+            write_checkpoint(&args, &tuner)?;
+        }
+    }
+
+    write_weights(Path::new(&args.output), &tuner)?;
+    println!("Tuned weights written to {}:\n{}", args.output, tuner.evaluator());
+    Ok(())
+}
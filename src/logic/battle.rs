@@ -1,6 +1,14 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use anyhow::Context;
+use rostware23_lib::game::record::GameRecord;
 use rostware23_lib::game::result::GameResult;
 use rostware23_lib::game::state::State;
-use rostware23_lib::game::state_generator::create_any;
+use rostware23_lib::game::state_generator::create_board_from_seed;
 use rostware23_lib::xml::common::Team;
 
 use super::MoveGetter;
@@ -30,12 +38,23 @@ impl BattleOutcome {
         Self(win_amount_team_one, win_amount_team_two)
     }
 
+    pub fn from_records(records: &[GameRecord]) -> anyhow::Result<Self> {
+        Ok(Self::from_results(records.iter().map(GameRecord::result).collect::<anyhow::Result<_>>()?))
+    }
+
     fn combined(outcomes: Vec<BattleOutcome>) -> Self {
         outcomes.into_iter().fold(Self::empty(), |first, second| {
             Self(first.0 + second.0, first.1 + second.1)
         })
     }
 
+    pub fn wins(&self, team: Team) -> u32 {
+        match team {
+            Team::One => self.0,
+            Team::Two => self.1
+        }
+    }
+
     pub fn winner(&self) -> Option<Team> {
         if self.0 > self.1 { Some(Team::One) }
         else if self.1 > self.0 { Some(Team::Two) }
@@ -45,12 +64,30 @@ impl BattleOutcome {
 
 pub struct Battle<'playout> {
     getter_team_one: &'playout dyn MoveGetter,
-    getter_team_two: &'playout dyn MoveGetter
+    getter_team_two: &'playout dyn MoveGetter,
+    // Indexed by team, getters without a limit search until they are done
+    time_limits_in_millis: [Option<u128>; 2]
 }
 
 impl<'playout> Battle<'playout> {
     pub fn between(getter_team_one: &'playout dyn MoveGetter, getter_team_two: &'playout dyn MoveGetter) -> Self {
-        Self { getter_team_one, getter_team_two }
+        Self { getter_team_one, getter_team_two, time_limits_in_millis: [None; 2] }
+    }
+
+    pub fn with_time_limit_in_millis(self, time_limit_in_millis: u128) -> Self {
+        self.with_time_limits_in_millis(Some(time_limit_in_millis), Some(time_limit_in_millis))
+    }
+
+    pub fn with_time_limits_in_millis(mut self, team_one: Option<u128>, team_two: Option<u128>) -> Self {
+        self.time_limits_in_millis = [team_one, team_two];
+        self
+    }
+
+    fn create_time_measurer(&self, team: Team) -> TimeMeasurer {
+        match self.time_limits_in_millis[team as usize] {
+            Some(time_limit_in_millis) => TimeMeasurer::new(time_limit_in_millis),
+            None => TimeMeasurer::new_infinite()
+        }
     }
 
     fn move_getter_for_team(&'playout self, team: Team) -> &'playout dyn MoveGetter {
@@ -60,16 +97,26 @@ impl<'playout> Battle<'playout> {
         }
     }
 
-    pub fn mono_directional_with_start_team(&self, mut state: State, start_team: Team) -> anyhow::Result<BattleOutcome> {
-        state.start_team = start_team.clone();
+    pub fn record_with_start_team(&self, mut state: State, start_team: Team) -> anyhow::Result<GameRecord> {
+        state.start_team = start_team;
+        let mut record = GameRecord::new(state.clone());
         while !state.is_over() {
             state = state.with_moveless_player_skipped()?;
-            let current_getter = self.move_getter_for_team(state.current_team().clone());
-            let performed_move = current_getter.get_move(&state, &TimeMeasurer::new_infinite())?;
-            state = state.with_move_performed(performed_move)?;
+            let current_team = state.current_team();
+            let current_getter = self.move_getter_for_team(current_team);
+            let performed_move = current_getter.get_move(&state, &self.create_time_measurer(current_team))?;
+            state.perform_move(performed_move.clone())?;
+            record.moves.push(performed_move);
         }
-        let result = state.get_result()?;
+        Ok(record)
+    }
+
+    pub fn play_with_start_team(&self, state: State, start_team: Team) -> anyhow::Result<GameResult> {
+        self.record_with_start_team(state, start_team)?.result()
+    }
 
+    pub fn mono_directional_with_start_team(&self, state: State, start_team: Team) -> anyhow::Result<BattleOutcome> {
+        let result = self.play_with_start_team(state, start_team)?;
         Ok(BattleOutcome::from_results(vec![result]))
     }
 
@@ -82,12 +129,175 @@ impl<'playout> Battle<'playout> {
         ]))
     }
 
+    // Plays the boards of the seeds 0..amount, see ParallelBattle for other seeds
     pub fn multiple_bi_directional(&self, amount: usize) -> anyhow::Result<BattleOutcome> {
         let mut outcomes = vec![];
-        for _ in 0..amount {
-            outcomes.push(self.bi_directional(create_any())?);
+        for index in 0..amount {
+            outcomes.push(self.bi_directional(create_state_of_pair(0, index))?);
         }
         Ok(BattleOutcome::combined(outcomes))
     }
 }
 
+fn create_state_of_pair(first_board_seed: u64, pair: usize) -> State {
+    State::from_initial_board_with_start_team_one(create_board_from_seed(first_board_seed.wrapping_add(pair as u64)))
+}
+
+pub type MoveGetterFactory<'factory> = dyn Fn() -> anyhow::Result<Box<dyn MoveGetter>> + Sync + 'factory;
+
+// Plays pairs of games on seeded boards on multiple threads, both teams start once on every board.
+// Every game gets new move getters, so getters keep no state between games.
+pub struct ParallelBattle<'factory> {
+    create_getter_team_one: &'factory MoveGetterFactory<'factory>,
+    create_getter_team_two: &'factory MoveGetterFactory<'factory>,
+    names: (String, String),
+    time_limits_in_millis: [Option<u128>; 2],
+    first_board_seed: u64,
+    threads: usize,
+    record_directory: Option<PathBuf>
+}
+
+impl<'factory> ParallelBattle<'factory> {
+    pub fn between(create_getter_team_one: &'factory MoveGetterFactory<'factory>, create_getter_team_two: &'factory MoveGetterFactory<'factory>) -> Self {
+        Self {
+            create_getter_team_one,
+            create_getter_team_two,
+            names: ("one".to_string(), "two".to_string()),
+            time_limits_in_millis: [None; 2],
+            first_board_seed: 0,
+            threads: 1,
+            record_directory: None
+        }
+    }
+
+    pub fn with_names(mut self, team_one: &str, team_two: &str) -> Self {
+        self.names = (team_one.to_string(), team_two.to_string());
+        self
+    }
+
+    pub fn with_time_limit_in_millis(self, time_limit_in_millis: u128) -> Self {
+        self.with_time_limits_in_millis(Some(time_limit_in_millis), Some(time_limit_in_millis))
+    }
+
+    pub fn with_time_limits_in_millis(mut self, team_one: Option<u128>, team_two: Option<u128>) -> Self {
+        self.time_limits_in_millis = [team_one, team_two];
+        self
+    }
+
+    pub fn with_first_board_seed(mut self, first_board_seed: u64) -> Self {
+        self.first_board_seed = first_board_seed;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    // Writes the record of every game to pair-NNNNN-start-TEAM.txt in the directory
+    pub fn with_record_directory(mut self, record_directory: &Path) -> Self {
+        self.record_directory = Some(record_directory.to_path_buf());
+        self
+    }
+
+    fn record_pair(&self, pair: usize) -> anyhow::Result<Vec<GameRecord>> {
+        let state = create_state_of_pair(self.first_board_seed, pair);
+        let mut records = vec![];
+        for (start_team, start_team_name) in [(Team::One, "one"), (Team::Two, "two")] {
+            let getter_team_one = (self.create_getter_team_one)()?;
+            let getter_team_two = (self.create_getter_team_two)()?;
+            let battle = Battle::between(getter_team_one.as_ref(), getter_team_two.as_ref())
+                .with_time_limits_in_millis(self.time_limits_in_millis[0], self.time_limits_in_millis[1]);
+            let record = battle.record_with_start_team(state.clone(), start_team)?
+                .with_team_names(&self.names.0, &self.names.1);
+            if let Some(record_directory) = &self.record_directory {
+                record.save(&record_directory.join(format!("pair-{:05}-start-{}.txt", pair, start_team_name)))?;
+            }
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    // Plays the pairs first_pair..first_pair + pairs, the records are ordered by pair and start team.
+    pub fn run(&self, first_pair: usize, pairs: usize) -> anyhow::Result<Vec<GameRecord>> {
+        if let Some(record_directory) = &self.record_directory {
+            fs::create_dir_all(record_directory).with_context(|| format!("Could not create {}", record_directory.display()))?;
+        }
+        let next_pair = AtomicUsize::new(first_pair);
+        let finished_pairs = Mutex::new(vec![]);
+        thread::scope(|scope| -> anyhow::Result<()> {
+            let handles: Vec<_> = (0..self.threads.min(pairs))
+                .map(|_| scope.spawn(|| -> anyhow::Result<()> {
+                    loop {
+                        let pair = next_pair.fetch_add(1, Ordering::Relaxed);
+                        if pair >= first_pair + pairs {
+                            return Ok(());
+                        }
+                        let records = self.record_pair(pair).with_context(|| format!("Game pair {} failed", pair))?;
+                        finished_pairs.lock().expect("Battle thread panicked").push((pair, records));
+                    }
+                }))
+                .collect();
+            handles.into_iter().try_for_each(|handle| handle.join().expect("Battle thread panicked"))
+        })?;
+        let mut finished_pairs = finished_pairs.into_inner().expect("Battle thread panicked");
+        finished_pairs.sort_by_key(|(pair, _)| *pair);
+        Ok(finished_pairs.into_iter().flat_map(|(_, records)| records).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::fish_difference_rater::FishDifferenceRater;
+    use crate::logic::pvs_getter::PVSMoveGetter;
+    use crate::logic::random_getter::RandomGetter;
+
+    fn create_random_getter() -> anyhow::Result<Box<dyn MoveGetter>> {
+        Ok(Box::new(RandomGetter::new()))
+    }
+
+    fn create_fixed_getter() -> anyhow::Result<Box<dyn MoveGetter>> {
+        Ok(Box::new(PVSMoveGetter::<FishDifferenceRater>::new_fixed().with_transposition_table_size_in_megabytes(1)))
+    }
+
+    #[test]
+    fn parallel_battle_plays_both_start_teams_on_seeded_boards() {
+        let directory = std::env::temp_dir().join("rostware23_parallel_battle_test");
+        let records = ParallelBattle::between(&create_random_getter, &create_random_getter)
+            .with_names("first", "second")
+            .with_first_board_seed(20)
+            .with_threads(2)
+            .with_record_directory(&directory)
+            .run(1, 3)
+            .unwrap();
+        let saved = GameRecord::load(&directory.join("pair-00002-start-two.txt"));
+        let file_count = fs::read_dir(&directory).unwrap().count();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(6, records.len());
+        assert_eq!(6, file_count);
+        for (index, record) in records.iter().enumerate() {
+            let board = create_board_from_seed(21 + index as u64 / 2);
+            assert_eq!(board, record.initial_state.board);
+            assert_eq!(if index % 2 == 0 { Team::One } else { Team::Two }, record.initial_state.start_team);
+            assert_eq!("first", record.team_one);
+            assert!(record.final_state().unwrap().is_over());
+        }
+        assert_eq!(records[3], saved.unwrap());
+        let outcome = BattleOutcome::from_records(&records).unwrap();
+        let draws = records.iter().filter(|record| record.result().unwrap().winner.is_none()).count() as u32;
+        assert_eq!(6, outcome.wins(Team::One) + outcome.wins(Team::Two) + draws);
+    }
+
+    #[test]
+    fn parallel_battles_of_deterministic_getters_are_reproducible() {
+        let play = |threads| ParallelBattle::between(&create_fixed_getter, &create_fixed_getter)
+            .with_time_limits_in_millis(Some(1000), None)
+            .with_first_board_seed(4)
+            .with_threads(threads)
+            .run(0, 2)
+            .unwrap();
+        assert_eq!(play(1), play(2));
+    }
+}
@@ -0,0 +1,73 @@
+use rostware23_lib::game::board::Board;
+use rostware23_lib::game::common::{BOARD_WIDTH, Coordinate};
+
+// Resets the colours the board's display leaves behind, it also separates the fields
+const RESET_COLOUR: &str = "\u{001b}[0m";
+const FIELD_SEPARATOR: &str = "\u{001b}[0m ";
+const HIGHLIGHT_COLOUR: &str = "\u{001b}[43m";
+
+// The board's display with the column letters and row numbers of the coordinate notation.
+pub fn labelled_board(board: &Board) -> String {
+    labelled_board_with_highlights(board, &[])
+}
+
+// Like labelled_board, the given fields (in doubled coordinates, like moves) get a highlighted background.
+pub fn labelled_board_with_highlights(board: &Board, highlighted: &[Coordinate]) -> String {
+    let highlighted: Vec<Coordinate> = highlighted.iter().map(|coordinate| coordinate.clone().doubled_to_odd_r()).collect();
+    let columns: Vec<String> = (0..BOARD_WIDTH).map(|x| ((b'a' + x as u8) as char).to_string()).collect();
+    let header = format!("  {}", columns.join(" "));
+    let mut lines = vec![header.clone()];
+    for (y, row) in board.to_string().lines().enumerate() {
+        // Odd rows are indented by half a field
+        let padding = if y % 2 == 0 { " " } else { "" };
+        let row = if highlighted.iter().any(|coordinate| coordinate.y() == y as u64) {
+            highlight_row(row, y as u64, &highlighted)
+        } else {
+            row.to_string()
+        };
+        lines.push(format!("{} {}{}{}{}", y + 1, row, RESET_COLOUR, padding, y + 1));
+    }
+    lines.push(header);
+    lines.join("\n")
+}
+
+fn highlight_row(row: &str, y: u64, highlighted: &[Coordinate]) -> String {
+    let mut fields: Vec<String> = row.split(FIELD_SEPARATOR).map(str::to_string).collect();
+    // The indentation of odd rows is a separator in front of the first field
+    let offset = if y % 2 == 1 { 1 } else { 0 };
+    for coordinate in highlighted.iter().filter(|coordinate| coordinate.y() == y) {
+        let field = &mut fields[coordinate.x() as usize + offset];
+        // The symbol is the last character, after the field's own colour
+        field.insert_str(field.len() - 1, HIGHLIGHT_COLOUR);
+    }
+    fields.join(FIELD_SEPARATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::state_generator::create_board_from_seed;
+
+    use super::*;
+
+    #[test]
+    fn board_is_framed_by_coordinates() {
+        let view = labelled_board(&create_board_from_seed(2));
+        let lines: Vec<&str> = view.lines().collect();
+        assert_eq!(10, lines.len());
+        assert_eq!("  a b c d e f g h", lines[0]);
+        assert_eq!(lines[0], lines[9]);
+        assert!(lines[1].starts_with("1 ") && lines[1].ends_with(" 1"));
+        assert!(lines[8].starts_with("8 ") && lines[8].ends_with("8"));
+    }
+
+    #[test]
+    fn highlighted_fields_only_change_colours() {
+        let board = create_board_from_seed(2);
+        let highlighted = [Coordinate::from_notation("b2").unwrap(), Coordinate::from_notation("h1").unwrap()];
+        let view = labelled_board_with_highlights(&board, &highlighted);
+        assert_eq!(2, view.matches(HIGHLIGHT_COLOUR).count());
+        assert_eq!(labelled_board(&board), view.replace(HIGHLIGHT_COLOUR, ""));
+        let lines: Vec<&str> = view.lines().collect();
+        assert!(lines[1].contains(HIGHLIGHT_COLOUR) && lines[2].contains(HIGHLIGHT_COLOUR));
+    }
+}
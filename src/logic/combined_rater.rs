@@ -10,6 +10,7 @@ use super::quadrant_occupation_rater::QuadrantOccupationRater;
 use super::penguin_cutoff_rater::PenguinCutOffRater;
 use super::staged_rater::StagedRater;
 
+#[derive(Default)]
 pub struct CombinedRater {}
 
 impl Rater for CombinedRater {
@@ -1,5 +1,6 @@
 use rostware23_lib::game::common::{Coordinate, RIGHTMOST_X, BOARD_HEIGHT};
 use rostware23_lib::game::state::State;
+use rostware23_lib::xml::common::Team;
 
 use super::Rater;
 
@@ -14,20 +15,23 @@ impl EdgePenguinPenalty {
             y == 0 || y == BOARD_HEIGHT - 1;
         on_edge as i32
     }
+
+    fn penguins_on_edge_of_team(state: &State, team: Team) -> i32 {
+        state.board.get_penguin_iterator(team)
+            .map(|penguin| Self::rate_penguin_coordinate(penguin.coordinate))
+            .sum::<i32>()
+    }
 }
 
 impl Rater for EdgePenguinPenalty {
     fn rate(state: &State) -> i32 {
         let current_team = state.current_team();
-        let own_penguins = state.board.get_penguin_iterator(current_team.clone());
-        let own_rating = own_penguins
-            .map(|penguin| Self::rate_penguin_coordinate(penguin.coordinate))
-            .sum::<i32>();
-        let opponent_penguins = state.board.get_penguin_iterator(current_team.opponent());
-        let opponent_rating = opponent_penguins
-            .map(|penguin| Self::rate_penguin_coordinate(penguin.coordinate))
-            .sum::<i32>();
-        opponent_rating - own_rating
+        Self::penguins_on_edge_of_team(state, current_team.opponent()) - Self::penguins_on_edge_of_team(state, current_team)
+    }
+
+    fn rate_per_team(state: &State) -> Option<(i32, i32)> {
+        let current_team = state.current_team();
+        Some((-Self::penguins_on_edge_of_team(state, current_team), Self::penguins_on_edge_of_team(state, current_team.opponent())))
     }
 }
 
@@ -35,7 +39,6 @@ impl Rater for EdgePenguinPenalty {
 mod tests {
     use rostware23_lib::game::board::Board;
     use rostware23_lib::game::moves::Move;
-    use rostware23_lib::xml::common::Team;
     use rostware23_lib::xml::state::FieldState;
 
     use super::*;
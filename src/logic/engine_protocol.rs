@@ -0,0 +1,433 @@
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::state::State;
+
+use super::MoveGetter;
+use super::battle::MoveGetterFactory;
+use super::search_report::SearchReport;
+use super::time_measurer::TimeMeasurer;
+
+// How long an external engine gets to exit after quit before it is killed
+const QUIT_TIMEOUT_IN_MILLIS: u64 = 500;
+// How long an external engine gets to answer hello
+const HELLO_TIMEOUT_IN_MILLIS: u64 = 5000;
+// How long an external engine may take beyond the move time before it is killed
+const MOVE_TIME_GRACE_IN_MILLIS: u64 = 1000;
+// Used when there is no time limit, as engines always search with a move time.
+const DEFAULT_MOVE_TIME_IN_MILLIS: u128 = 5000;
+
+// A line based protocol between a controller and an engine process in the spirit of UCI.
+//
+// Controller to engine:
+//   hello                              answered by "id name NAME" and "hellook"
+//   isready                            answered by "readyok"
+//   newgame                            the next position belongs to a new game
+//   position STATE [moves MOVE...]     STATE in state notation, the moves are played from it
+//   go movetime MILLIS                 answered by "info ..." lines and "bestmove MOVE"
+//   quit
+//
+// Engine to controller:
+//   info depth DEPTH score SCORE pv MOVE...
+//   info string TEXT
+//   bestmove MOVE | bestmove none      none if the position has no moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineCommand {
+    Hello,
+    IsReady,
+    NewGame,
+    Position { state: State, moves: Vec<Move> },
+    // Search time in milliseconds
+    Go(u128),
+    Quit
+}
+
+fn parse_moves(notations: &[&str]) -> anyhow::Result<Vec<Move>> {
+    notations.iter().map(|notation| Move::from_notation(notation)).collect()
+}
+
+fn join_moves(moves: &[Move]) -> String {
+    moves.iter().map(Move::to_notation).collect::<Vec<_>>().join(" ")
+}
+
+impl EngineCommand {
+    pub fn parse(line: &str) -> anyhow::Result<Self> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens[..] {
+            ["hello"] => Ok(Self::Hello),
+            ["isready"] => Ok(Self::IsReady),
+            ["newgame"] => Ok(Self::NewGame),
+            ["quit"] => Ok(Self::Quit),
+            ["go", "movetime", millis] => Ok(Self::Go(millis.parse().with_context(|| format!("Invalid move time '{}'", millis))?)),
+            ["position", ..] if tokens.len() >= 6 => {
+                let moves = match tokens[6..] {
+                    [] => vec![],
+                    ["moves", ref moves @ ..] => parse_moves(moves)?,
+                    _ => anyhow::bail!("Expected moves after the state in '{}'", line)
+                };
+                Ok(Self::Position { state: State::from_notation(&tokens[1..6].join(" "))?, moves })
+            },
+            _ => anyhow::bail!("Unknown command '{}'", line)
+        }
+    }
+
+    // The state to search with the moves played and moveless players skipped
+    fn position_to_search(state: &State, moves: &[Move]) -> anyhow::Result<State> {
+        let mut state = state.clone();
+        for performed_move in moves {
+            state = state.with_moveless_player_skipped()?;
+            state.perform_move(performed_move.clone())?;
+        }
+        if state.is_over() {
+            return Ok(state);
+        }
+        state.with_moveless_player_skipped()
+    }
+}
+
+impl fmt::Display for EngineCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hello => write!(f, "hello"),
+            Self::IsReady => write!(f, "isready"),
+            Self::NewGame => write!(f, "newgame"),
+            Self::Position { state, moves } if moves.is_empty() => write!(f, "position {}", state.to_notation()),
+            Self::Position { state, moves } => write!(f, "position {} moves {}", state.to_notation(), join_moves(moves)),
+            Self::Go(millis) => write!(f, "go movetime {}", millis),
+            Self::Quit => write!(f, "quit")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineResponse {
+    Id(String),
+    HelloOk,
+    ReadyOk,
+    Info { depth: i32, score: i32, principal_variation: Vec<Move> },
+    InfoString(String),
+    BestMove(Option<Move>)
+}
+
+impl EngineResponse {
+    pub fn parse(line: &str) -> anyhow::Result<Self> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens[..] {
+            ["id", "name", ..] => Ok(Self::Id(tokens[2..].join(" "))),
+            ["hellook"] => Ok(Self::HelloOk),
+            ["readyok"] => Ok(Self::ReadyOk),
+            ["bestmove", "none"] => Ok(Self::BestMove(None)),
+            ["bestmove", best_move] => Ok(Self::BestMove(Some(Move::from_notation(best_move)?))),
+            ["info", "string", ..] => Ok(Self::InfoString(tokens[2..].join(" "))),
+            ["info", "depth", depth, "score", score, "pv", ref moves @ ..] => Ok(Self::Info {
+                depth: depth.parse().with_context(|| format!("Invalid depth '{}'", depth))?,
+                score: score.parse().with_context(|| format!("Invalid score '{}'", score))?,
+                principal_variation: parse_moves(moves)?
+            }),
+            _ => anyhow::bail!("Unknown response '{}'", line)
+        }
+    }
+}
+
+impl fmt::Display for EngineResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(name) => write!(f, "id name {}", name),
+            Self::HelloOk => write!(f, "hellook"),
+            Self::ReadyOk => write!(f, "readyok"),
+            Self::Info { depth, score, principal_variation } => write!(f, "info depth {} score {} pv {}", depth, score, join_moves(principal_variation)),
+            Self::InfoString(text) => write!(f, "info string {}", text),
+            Self::BestMove(Some(best_move)) => write!(f, "bestmove {}", best_move.to_notation()),
+            Self::BestMove(None) => write!(f, "bestmove none")
+        }
+    }
+}
+
+fn search_position(move_getter: &dyn MoveGetter, state: &State, time_limit_in_millis: u128) -> anyhow::Result<Vec<EngineResponse>> {
+    if state.is_over() {
+        return Ok(vec![EngineResponse::InfoString("the game is over".to_string()), EngineResponse::BestMove(None)]);
+    }
+    let report = move_getter.search(state, &TimeMeasurer::new(time_limit_in_millis))?;
+    Ok(vec![
+        EngineResponse::Info { depth: report.completed_depth, score: report.score, principal_variation: report.principal_variation },
+        EngineResponse::BestMove(Some(report.best_move))
+    ])
+}
+
+// Answers commands until quit or the end of the input, invalid commands are reported as info strings.
+pub fn serve(name: &str, create_move_getter: &MoveGetterFactory, input: impl BufRead, output: &mut impl Write) -> anyhow::Result<()> {
+    let mut move_getter = create_move_getter()?;
+    let mut position = None;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let responses = match EngineCommand::parse(&line) {
+            Ok(EngineCommand::Hello) => vec![EngineResponse::Id(name.to_string()), EngineResponse::HelloOk],
+            Ok(EngineCommand::IsReady) => vec![EngineResponse::ReadyOk],
+            Ok(EngineCommand::NewGame) => {
+                move_getter = create_move_getter()?;
+                vec![]
+            },
+            Ok(EngineCommand::Position { state, moves }) => match EngineCommand::position_to_search(&state, &moves) {
+                Ok(state) => {
+                    position = Some(state);
+                    vec![]
+                },
+                Err(error) => vec![EngineResponse::InfoString(format!("invalid position: {:#}", error))]
+            },
+            Ok(EngineCommand::Go(time_limit_in_millis)) => match &position {
+                Some(state) => search_position(move_getter.as_ref(), state, time_limit_in_millis)?,
+                None => vec![EngineResponse::InfoString("no position".to_string()), EngineResponse::BestMove(None)]
+            },
+            Ok(EngineCommand::Quit) => return Ok(()),
+            Err(error) => vec![EngineResponse::InfoString(format!("{:#}", error))]
+        };
+        for response in responses {
+            writeln!(output, "{}", response)?;
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+struct EngineProcess {
+    child: Child,
+    input: ChildStdin,
+    // Lines of the engine output, read on another thread so receiving can time out
+    output: Receiver<String>
+}
+
+impl EngineProcess {
+    fn send(&mut self, command: &EngineCommand) -> anyhow::Result<()> {
+        writeln!(self.input, "{}", command)?;
+        self.input.flush()?;
+        Ok(())
+    }
+
+    // Skips lines that are no responses, like debug output of the engine.
+    // The engine is killed if it does not answer before the deadline.
+    fn receive(&mut self, deadline: Instant) -> anyhow::Result<EngineResponse> {
+        loop {
+            let line = match self.output.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = self.child.kill();
+                    let _ = self.child.wait();
+                    anyhow::bail!("The engine did not answer in time and was killed");
+                },
+                Err(RecvTimeoutError::Disconnected) => anyhow::bail!("The engine closed its output")
+            };
+            if let Ok(response) = EngineResponse::parse(&line) {
+                return Ok(response);
+            }
+        }
+    }
+}
+
+fn forward_lines(output: ChildStdout) -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            if sender.send(line).is_err() {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+// Plays with an engine process speaking the engine protocol on its standard input and output.
+pub struct ExternalMoveGetter {
+    name: String,
+    process: Mutex<EngineProcess>
+}
+
+impl ExternalMoveGetter {
+    // The command line is split at whitespace into the program and its arguments
+    pub fn launch(command_line: &str) -> anyhow::Result<Self> {
+        let mut parts = command_line.split_whitespace();
+        let program = parts.next().context("Empty engine command")?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Could not start engine '{}'", command_line))?;
+        let input = child.stdin.take().context("No engine input")?;
+        let output = forward_lines(child.stdout.take().context("No engine output")?);
+        let mut process = EngineProcess { child, input, output };
+
+        process.send(&EngineCommand::Hello)?;
+        let deadline = Instant::now() + Duration::from_millis(HELLO_TIMEOUT_IN_MILLIS);
+        let mut name = command_line.to_string();
+        loop {
+            match process.receive(deadline).with_context(|| format!("Engine '{}' did not answer hello", command_line))? {
+                EngineResponse::Id(id) => name = id,
+                EngineResponse::HelloOk => break,
+                _ => {}
+            }
+        }
+        Ok(Self { name, process: Mutex::new(process) })
+    }
+}
+
+impl MoveGetter for ExternalMoveGetter {
+    fn get_move(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<Move> {
+        Ok(self.search(state, time_measurer)?.best_move)
+    }
+
+    fn search(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<SearchReport> {
+        let start = Instant::now();
+        let mut process = self.process.lock().expect("Engine user panicked");
+        process.send(&EngineCommand::Position { state: state.clone(), moves: vec![] })?;
+        let move_time_in_millis = time_measurer.remaining_millis().unwrap_or(DEFAULT_MOVE_TIME_IN_MILLIS);
+        process.send(&EngineCommand::Go(move_time_in_millis))?;
+        let deadline = Instant::now() + Duration::from_millis(move_time_in_millis as u64 + MOVE_TIME_GRACE_IN_MILLIS);
+        let mut last_info = None;
+        let best_move = loop {
+            match process.receive(deadline).with_context(|| format!("Engine {} did not answer go", self.name))? {
+                EngineResponse::BestMove(best_move) => break best_move.with_context(|| format!("Engine {} found no move", self.name))?,
+                EngineResponse::Info { depth, score, principal_variation } => last_info = Some((depth, score, principal_variation)),
+                _ => {}
+            }
+        };
+        let mut report = SearchReport::from_move(best_move, start.elapsed().as_millis());
+        if let Some((depth, score, principal_variation)) = last_info {
+            report.completed_depth = depth;
+            report.score = score;
+            if principal_variation.first() == Some(&report.best_move) {
+                report.principal_variation = principal_variation;
+            }
+        }
+        Ok(report)
+    }
+}
+
+impl Drop for ExternalMoveGetter {
+    fn drop(&mut self) {
+        let Ok(process) = self.process.get_mut() else {
+            return;
+        };
+        if process.send(&EngineCommand::Quit).is_ok() {
+            let deadline = Instant::now() + Duration::from_millis(QUIT_TIMEOUT_IN_MILLIS);
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = process.child.try_wait() {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let _ = process.child.kill();
+        let _ = process.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::state_generator::create_board_from_seed;
+
+    use super::*;
+    use crate::logic::random_getter::RandomGetter;
+
+    fn create_random_getter() -> anyhow::Result<Box<dyn MoveGetter>> {
+        Ok(Box::new(RandomGetter::new()))
+    }
+
+    fn create_state() -> State {
+        State::from_initial_board_with_start_team_one(create_board_from_seed(8))
+    }
+
+    #[test]
+    fn commands_and_responses_can_be_written_and_read_again() {
+        let state = create_state();
+        let first_move = state.possible_moves().next().unwrap();
+        let commands = [
+            EngineCommand::Hello,
+            EngineCommand::Position { state: state.clone(), moves: vec![] },
+            EngineCommand::Position { state, moves: vec![first_move.clone()] },
+            EngineCommand::Go(150),
+            EngineCommand::Quit
+        ];
+        for command in commands {
+            assert_eq!(command, EngineCommand::parse(&command.to_string()).unwrap());
+        }
+        let responses = [
+            EngineResponse::Id("rostware23 pvs".to_string()),
+            EngineResponse::Info { depth: 4, score: -20, principal_variation: vec![first_move.clone()] },
+            EngineResponse::BestMove(Some(first_move)),
+            EngineResponse::BestMove(None)
+        ];
+        for response in responses {
+            assert_eq!(response, EngineResponse::parse(&response.to_string()).unwrap());
+        }
+        assert!(EngineCommand::parse("go fast").is_err());
+        assert!(EngineCommand::parse("go infinite").is_err());
+        assert!(EngineResponse::parse("bestmove z9").is_err());
+    }
+
+    #[test]
+    fn engine_answers_commands_in_order() {
+        let state = create_state();
+        let first_move = state.possible_moves().next().unwrap();
+        let input = format!("hello\nisready\nposition {} moves {}\ngo movetime 20\nfly\nquit\ngo movetime 20\n", state.to_notation(), first_move.to_notation());
+        let mut output = vec![];
+        serve("test", &create_random_getter, input.as_bytes(), &mut output).unwrap();
+
+        let lines: Vec<String> = String::from_utf8(output).unwrap().lines().map(String::from).collect();
+        assert_eq!(["id name test", "hellook", "readyok"], lines[..3]);
+        let EngineResponse::BestMove(Some(best_move)) = EngineResponse::parse(&lines[4]).unwrap() else {
+            panic!("Expected a best move, got {}", lines[4]);
+        };
+        assert!(state.with_move_performed(first_move).unwrap().possible_moves().any(|possible_move| possible_move == best_move));
+        assert!(lines[5].starts_with("info string Unknown command"));
+        assert_eq!(6, lines.len());
+    }
+
+    #[test]
+    fn external_move_getter_speaks_with_engine_process() {
+        let state = create_state();
+        let placement = state.possible_moves().next().unwrap().to_notation();
+        let script = std::env::temp_dir().join("rostware23_external_engine_test.sh");
+        std::fs::write(&script, format!("while read line; do case \"$line\" in\n\
+            hello) echo \"id name fake engine\"; echo hellook;;\n\
+            go*) echo \"debug output\"; echo \"info depth 3 score 17 pv {0}\"; echo \"bestmove {0}\";;\n\
+            quit) exit 0;;\n\
+            esac; done\n", placement)).unwrap();
+
+        let move_getter = ExternalMoveGetter::launch(&format!("sh {}", script.display())).unwrap();
+        let report = move_getter.search(&state, &TimeMeasurer::new(100)).unwrap();
+        drop(move_getter);
+        std::fs::remove_file(&script).unwrap();
+        assert_eq!(placement, report.best_move.to_notation());
+        assert_eq!((3, 17), (report.completed_depth, report.score));
+        assert!(ExternalMoveGetter::launch("rostware23-engine-that-does-not-exist").is_err());
+    }
+
+    #[test]
+    fn external_engine_that_does_not_answer_in_time_is_killed() {
+        let script = std::env::temp_dir().join("rostware23_silent_engine_test.sh");
+        std::fs::write(&script, "while read line; do case \"$line\" in\n\
+            hello) echo hellook;;\n\
+            esac; done\n").unwrap();
+
+        let move_getter = ExternalMoveGetter::launch(&format!("sh {}", script.display())).unwrap();
+        let start = Instant::now();
+        let result = move_getter.search(&create_state(), &TimeMeasurer::new(100));
+        std::fs::remove_file(&script).unwrap();
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_millis(100 + MOVE_TIME_GRACE_IN_MILLIS + 500));
+        assert!(move_getter.process.lock().unwrap().child.try_wait().unwrap().is_some());
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::state::State;
+use rostware23_lib::xml::common::Team;
+
+use super::Evaluator;
+use super::staged_rater::GamePhase;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentTrace {
+    pub name: String,
+    pub raw: i32,
+    pub weight: i32,
+    // Shares of the current team and its opponent in the raw value, adding up to it
+    pub per_team: Option<(i32, i32)>
+}
+
+impl ComponentTrace {
+    pub fn contribution(&self) -> i32 {
+        self.weight * self.raw
+    }
+}
+
+// The rating of a state broken down into the rating components, from the current team's perspective.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationTrace {
+    pub team: Team,
+    pub phase: GamePhase,
+    pub components: Vec<ComponentTrace>
+}
+
+impl EvaluationTrace {
+    pub fn new(state: &State, components: Vec<ComponentTrace>) -> Self {
+        Self {
+            team: state.current_team(),
+            phase: GamePhase::of_state(state),
+            components
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.components.iter().map(ComponentTrace::contribution).sum()
+    }
+
+    // Orders the shares of a component by team instead of by turn.
+    fn shares_of_team_one_and_two(&self, component: &ComponentTrace) -> Option<(i32, i32)> {
+        component.per_team.map(|(own, opponent)| match self.team {
+            Team::One => (own, opponent),
+            Team::Two => (opponent, own)
+        })
+    }
+}
+
+impl fmt::Display for EvaluationTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Evaluation for team {:?} in {:?} game: {}", self.team, self.phase, self.total())?;
+        write!(f, "{:<26} {:>6} {:>6} {:>12} {:>6} {:>6}", "component", "raw", "weight", "contribution", "One", "Two")?;
+        for component in &self.components {
+            let (team_one, team_two) = match self.shares_of_team_one_and_two(component) {
+                Some((team_one, team_two)) => (team_one.to_string(), team_two.to_string()),
+                None => ("-".to_string(), "-".to_string())
+            };
+            write!(f, "\n{:<26} {:>6} {:>6} {:>12} {:>6} {:>6}", component.name, component.raw, component.weight, component.contribution(), team_one, team_two)?;
+        }
+        Ok(())
+    }
+}
+
+// Explains the rating of the root and of the position at the end of the principal variation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrincipalVariationTrace {
+    pub root: EvaluationTrace,
+    pub leaf: EvaluationTrace,
+    pub leaf_ply: usize
+}
+
+impl PrincipalVariationTrace {
+    pub fn of(evaluator: &dyn Evaluator, state: &State, principal_variation: &[Move]) -> anyhow::Result<Self> {
+        let mut leaf = state.clone();
+        for principal_move in principal_variation {
+            leaf = leaf.with_moveless_player_skipped()?.with_move_performed(principal_move.clone())?;
+        }
+        Ok(Self {
+            root: evaluator.trace(state),
+            leaf: evaluator.trace(&leaf),
+            leaf_ply: principal_variation.len()
+        })
+    }
+}
+
+impl fmt::Display for PrincipalVariationTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Root position")?;
+        writeln!(f, "{}", self.root)?;
+        writeln!(f, "Principal variation leaf after {} moves", self.leaf_ply)?;
+        write!(f, "{}", self.leaf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::state_generator::create_any;
+
+    use super::*;
+    use crate::logic::Rater;
+    use crate::logic::fish_difference_rater::FishDifferenceRater;
+    use crate::logic::penguin_cutoff_rater::PenguinCutOffRater;
+
+    fn create_state_after_moves(count: usize) -> State {
+        let mut state = create_any();
+        for _ in 0..count {
+            state = state.with_moveless_player_skipped().unwrap();
+            let possible_move = state.possible_moves().next().unwrap();
+            state.perform_move(possible_move).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn trace_of_rater_has_one_component_split_per_team() {
+        let mut state = create_state_after_moves(9);
+        state.team_one_fish = 7;
+        state.team_two_fish = 3;
+        let trace = FishDifferenceRater {}.trace(&state);
+        assert_eq!(state.current_team(), trace.team);
+        assert_eq!(1, trace.components.len());
+        assert_eq!(FishDifferenceRater::rate(&state), trace.total());
+        assert_eq!(Some((7, -3)).map(|(one, two)| if trace.team == Team::One { (one, two) } else { (-one, -two) }),
+            trace.shares_of_team_one_and_two(&trace.components[0]));
+        assert!(trace.to_string().contains(&format!("team {:?}", state.current_team())));
+    }
+
+    #[test]
+    fn per_team_shares_add_up_to_rating() {
+        let state = create_state_after_moves(12);
+        let trace = PenguinCutOffRater {}.trace(&state);
+        let (own, opponent) = trace.components[0].per_team.unwrap();
+        assert_eq!(PenguinCutOffRater::rate(&state), own + opponent);
+    }
+
+    #[test]
+    fn principal_variation_leaf_is_traced_after_all_moves() {
+        let state = create_state_after_moves(8);
+        let principal_variation: Vec<Move> = state.possible_moves().take(1).collect();
+        let trace = PrincipalVariationTrace::of(&FishDifferenceRater {}, &state, &principal_variation).unwrap();
+        let leaf = state.with_move_performed(principal_variation[0].clone()).unwrap();
+        assert_eq!(FishDifferenceRater {}.trace(&state), trace.root);
+        assert_eq!(FishDifferenceRater {}.trace(&leaf), trace.leaf);
+        assert_eq!(1, trace.leaf_ply);
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::VecDeque;
+use std::io::Write;
+
+use rostware23_lib::game::board::Board;
+use rostware23_lib::game::common::{Coordinate, BOARD_HEIGHT, BOARD_WIDTH};
+use rostware23_lib::game::direction::DirectionIterator;
+use rostware23_lib::game::penguin::PenguinPossibleMoveIterator;
+use rostware23_lib::game::state::State;
+use rostware23_lib::xml::common::Team;
+use rostware23_lib::xml::state::FieldState;
+
+use super::staged_rater::GamePhase;
+use super::weighted_evaluator::rate_component;
+
+const FIELD_COUNT: usize = (BOARD_WIDTH * BOARD_HEIGHT) as usize;
+const PENGUINS_PER_TEAM: usize = 4;
+
+// Starts every binary export, followed by the layout version and the amount of features per row
+const BINARY_MAGIC: &[u8; 4] = b"RWFT";
+
+const V1_REGION_FEATURES: [&str; 6] = [
+    "region_count",
+    "largest_region_fish",
+    // Fish in regions only penguins of one team are next to
+    "own_region_fish",
+    "opponent_region_fish",
+    "contested_region_fish",
+    "unreachable_fish"
+];
+
+const V1_RATER_FEATURES: [&str; 8] = [
+    "fish_difference",
+    "penguin_cutoff",
+    "quadrant_occupation",
+    "restricted_reachable_fish",
+    "edge_penguin_penalty",
+    "potential_fish",
+    "reachable_fish",
+    "territory"
+];
+
+// The layouts of feature vectors, a layout never changes once models were trained with it.
+// All features are integers seen from the perspective of the current team.
+//
+// V1 in this order:
+// - fish_X_Y: fish on each field, fields ordered row by row in odd-r coordinates
+// - own_penguin_X_Y and opponent_penguin_X_Y: 1 if a penguin of the team is on the field
+// - own_score, opponent_score, turn
+// - early_phase, mid_phase, end_phase: 1 for the phase of the game
+// - own_mobility_I and opponent_mobility_I: moves of each penguin, sorted descending
+// - regions of fish fields connected by neighbouring fields, see V1_REGION_FEATURES
+// - rater_NAME: the unweighted ratings of the components of the weighted evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureLayout {
+    V1
+}
+
+impl FeatureLayout {
+    pub const LATEST: Self = Self::V1;
+
+    pub fn version(self) -> u16 {
+        match self {
+            Self::V1 => 1
+        }
+    }
+
+    pub fn from_version(version: u16) -> anyhow::Result<Self> {
+        match version {
+            1 => Ok(Self::V1),
+            _ => anyhow::bail!("Unknown feature layout version {}", version)
+        }
+    }
+
+    pub fn names(self) -> Vec<String> {
+        match self {
+            Self::V1 => v1_names()
+        }
+    }
+
+    pub fn feature_count(self) -> usize {
+        self.names().len()
+    }
+
+    pub fn extract(self, state: &State) -> anyhow::Result<Vec<i32>> {
+        match self {
+            Self::V1 => extract_v1(state)
+        }
+    }
+}
+
+fn field_names(prefix: &str) -> impl Iterator<Item = String> + '_ {
+    (0..FIELD_COUNT).map(move |index| format!("{}_{}_{}", prefix, index % BOARD_WIDTH as usize, index / BOARD_WIDTH as usize))
+}
+
+fn v1_names() -> Vec<String> {
+    let mut names: Vec<String> = field_names("fish")
+        .chain(field_names("own_penguin"))
+        .chain(field_names("opponent_penguin"))
+        .collect();
+    names.extend(["own_score", "opponent_score", "turn", "early_phase", "mid_phase", "end_phase"].map(String::from));
+    names.extend((0..PENGUINS_PER_TEAM).map(|index| format!("own_mobility_{}", index)));
+    names.extend((0..PENGUINS_PER_TEAM).map(|index| format!("opponent_mobility_{}", index)));
+    names.extend(V1_REGION_FEATURES.map(String::from));
+    names.extend(V1_RATER_FEATURES.map(|name| format!("rater_{}", name)));
+    names
+}
+
+fn extract_v1(state: &State) -> anyhow::Result<Vec<i32>> {
+    let own_team = state.current_team();
+    let fields: Vec<FieldState> = (0..FIELD_COUNT as u64)
+        .map(|index| state.board.get(Coordinate::from_field_index(index)))
+        .collect::<anyhow::Result<_>>()?;
+    let mut features: Vec<i32> = fields.iter()
+        .map(|field| field.get_fish_count().unwrap_or(0) as i32)
+        .collect();
+    for team in [own_team, own_team.opponent()] {
+        features.extend(fields.iter().map(|field| i32::from(*field == FieldState::Team(team))));
+    }
+
+    features.push(state.score_of_team(own_team) as i32);
+    features.push(state.score_of_team(own_team.opponent()) as i32);
+    features.push(state.turn as i32);
+    let phase = GamePhase::of_state(state);
+    features.extend([GamePhase::Early, GamePhase::Mid, GamePhase::End].map(|candidate| i32::from(candidate == phase)));
+
+    for team in [own_team, own_team.opponent()] {
+        features.extend(mobility_of_penguins(&state.board, team));
+    }
+    features.extend(region_features(&fields, own_team));
+    for name in V1_RATER_FEATURES {
+        features.push(rate_component(name, state)?);
+    }
+    Ok(features)
+}
+
+fn mobility_of_penguins(board: &Board, team: Team) -> [i32; PENGUINS_PER_TEAM] {
+    let mut mobility = [0; PENGUINS_PER_TEAM];
+    for (index, penguin) in board.get_penguin_iterator(team).take(PENGUINS_PER_TEAM).enumerate() {
+        mobility[index] = PenguinPossibleMoveIterator::from(penguin, board.clone()).count() as i32;
+    }
+    mobility.sort_unstable_by(|first, second| second.cmp(first));
+    mobility
+}
+
+fn neighbour_indices(index: usize) -> impl Iterator<Item = usize> {
+    let coordinate = Coordinate::from_field_index(index as u64);
+    DirectionIterator::new()
+        .map(move |direction| coordinate.add(direction.vector()))
+        .filter(Coordinate::is_valid)
+        .map(|neighbour| neighbour.to_field_index() as usize)
+}
+
+fn region_features(fields: &[FieldState], own_team: Team) -> [i32; 6] {
+    let mut visited = [false; FIELD_COUNT];
+    let (mut region_count, mut largest_region_fish) = (0, 0);
+    let (mut own_fish, mut opponent_fish, mut contested_fish, mut unreachable_fish) = (0, 0, 0, 0);
+    for start in 0..FIELD_COUNT {
+        if visited[start] || !matches!(fields[start], FieldState::Fish(_)) {
+            continue;
+        }
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        let (mut region_fish, mut own_adjacent, mut opponent_adjacent) = (0, false, false);
+        while let Some(index) = queue.pop_front() {
+            region_fish += fields[index].get_fish_count().unwrap_or(0) as i32;
+            for neighbour in neighbour_indices(index) {
+                match fields[neighbour] {
+                    FieldState::Fish(_) if !visited[neighbour] => {
+                        visited[neighbour] = true;
+                        queue.push_back(neighbour);
+                    },
+                    FieldState::Team(team) => {
+                        own_adjacent |= team == own_team;
+                        opponent_adjacent |= team != own_team;
+                    },
+                    _ => {}
+                }
+            }
+        }
+        region_count += 1;
+        largest_region_fish = largest_region_fish.max(region_fish);
+        match (own_adjacent, opponent_adjacent) {
+            (true, false) => own_fish += region_fish,
+            (false, true) => opponent_fish += region_fish,
+            (true, true) => contested_fish += region_fish,
+            (false, false) => unreachable_fish += region_fish
+        }
+    }
+    [region_count, largest_region_fish, own_fish, opponent_fish, contested_fish, unreachable_fish]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    // Little endian: magic, version as u16, feature count as u32, then every row as i32s
+    Binary,
+    // A header with the feature names, then one line per row
+    Csv
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> anyhow::Result<Self> {
+        match name {
+            "binary" => Ok(Self::Binary),
+            "csv" => Ok(Self::Csv),
+            _ => anyhow::bail!("Unknown export format '{}', expected binary or csv", name)
+        }
+    }
+}
+
+// Writes feature vectors of one layout, the same rows always result in the same bytes.
+pub struct FeatureWriter<Output: Write> {
+    output: Output,
+    layout: FeatureLayout,
+    format: ExportFormat,
+    rows: usize
+}
+
+impl<Output: Write> FeatureWriter<Output> {
+    pub fn new(mut output: Output, layout: FeatureLayout, format: ExportFormat) -> anyhow::Result<Self> {
+        match format {
+            ExportFormat::Binary => {
+                output.write_all(BINARY_MAGIC)?;
+                output.write_all(&layout.version().to_le_bytes())?;
+                output.write_all(&(layout.feature_count() as u32).to_le_bytes())?;
+            },
+            ExportFormat::Csv => writeln!(output, "{}", layout.names().join(","))?
+        }
+        Ok(Self { output, layout, format, rows: 0 })
+    }
+
+    pub fn write_state(&mut self, state: &State) -> anyhow::Result<()> {
+        let features = self.layout.extract(state)?;
+        match self.format {
+            ExportFormat::Binary => {
+                for feature in &features {
+                    self.output.write_all(&feature.to_le_bytes())?;
+                }
+            },
+            ExportFormat::Csv => {
+                let values: Vec<String> = features.iter().map(i32::to_string).collect();
+                writeln!(self.output, "{}", values.join(","))?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn finish(mut self) -> anyhow::Result<Output> {
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::moves::Move;
+    use rostware23_lib::game::state_generator::create_board_from_seed;
+
+    use super::*;
+
+    fn create_state_after_moves(count: usize) -> State {
+        let mut state = State::from_initial_board_with_start_team_one(create_board_from_seed(3));
+        for _ in 0..count {
+            state = state.with_moveless_player_skipped().unwrap();
+            let possible_move = state.possible_moves().next().unwrap();
+            state.perform_move(possible_move).unwrap();
+        }
+        state
+    }
+
+    fn feature(layout: FeatureLayout, features: &[i32], name: &str) -> i32 {
+        let index = layout.names().iter().position(|feature_name| feature_name == name).unwrap();
+        features[index]
+    }
+
+    #[test]
+    fn every_feature_of_latest_layout_has_a_unique_name() {
+        let names = FeatureLayout::LATEST.names();
+        let unique: std::collections::HashSet<&String> = names.iter().collect();
+        assert_eq!(names.len(), unique.len());
+        assert_eq!(3 * 64 + 6 + 8 + 6 + 8, names.len());
+        let features = FeatureLayout::LATEST.extract(&create_state_after_moves(10)).unwrap();
+        assert_eq!(names.len(), features.len());
+        assert_eq!(FeatureLayout::LATEST, FeatureLayout::from_version(FeatureLayout::LATEST.version()).unwrap());
+        assert!(FeatureLayout::from_version(0).is_err());
+    }
+
+    #[test]
+    fn features_are_seen_from_the_current_team() {
+        let mut board = Board::empty();
+        board.set(Coordinate::new(2, 0), FieldState::Fish(3)).unwrap();
+        board.set(Coordinate::new(4, 0), FieldState::Fish(1)).unwrap();
+        board.set(Coordinate::new(12, 6), FieldState::Fish(2)).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(0, 0)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(14, 6)), Team::Two).unwrap();
+        let mut state = State::from_initial_board_with_start_team_one(board);
+        state.team_two_fish = 5;
+        state.turn = 9;
+        let layout = FeatureLayout::V1;
+        let features = layout.extract(&state).unwrap();
+        assert_eq!(3, feature(layout, &features, "fish_1_0"));
+        assert_eq!(1, feature(layout, &features, "own_penguin_7_6"));
+        assert_eq!(1, feature(layout, &features, "opponent_penguin_0_0"));
+        assert_eq!(5, feature(layout, &features, "own_score"));
+        assert_eq!(1, feature(layout, &features, "mid_phase"));
+        assert_eq!(1, feature(layout, &features, "own_mobility_0"));
+        assert_eq!(2, feature(layout, &features, "opponent_mobility_0"));
+        assert_eq!(0, feature(layout, &features, "opponent_mobility_1"));
+        assert_eq!(2, feature(layout, &features, "region_count"));
+        assert_eq!(4, feature(layout, &features, "largest_region_fish"));
+        assert_eq!(2, feature(layout, &features, "own_region_fish"));
+        assert_eq!(4, feature(layout, &features, "opponent_region_fish"));
+        assert_eq!(5, feature(layout, &features, "rater_fish_difference"));
+    }
+
+    #[test]
+    fn regions_are_rated_by_the_teams_next_to_them() {
+        let mut board = Board::empty();
+        for (x, y, fish) in [(0, 0, 1), (2, 0, 1), (6, 0, 2), (9, 1, 1), (12, 0, 3), (14, 0, 1)] {
+            board.set(Coordinate::new(x, y), FieldState::Fish(fish)).unwrap();
+        }
+        board.perform_move(Move::Place(Coordinate::new(4, 0)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(8, 0)), Team::Two).unwrap();
+        let fields: Vec<FieldState> = (0..FIELD_COUNT as u64).map(|index| board.get(Coordinate::from_field_index(index)).unwrap()).collect();
+        assert_eq!([4, 4, 2, 1, 2, 4], region_features(&fields, Team::One));
+        assert_eq!([4, 4, 1, 2, 2, 4], region_features(&fields, Team::Two));
+    }
+
+    #[test]
+    fn exports_are_deterministic() {
+        let states: Vec<State> = (8..12).map(create_state_after_moves).collect();
+        let export = |format| {
+            let mut writer = FeatureWriter::new(vec![], FeatureLayout::V1, format).unwrap();
+            for state in &states {
+                writer.write_state(state).unwrap();
+            }
+            assert_eq!(states.len(), writer.rows());
+            writer.finish().unwrap()
+        };
+        let binary = export(ExportFormat::Binary);
+        assert_eq!(binary, export(ExportFormat::Binary));
+        let feature_count = FeatureLayout::V1.feature_count();
+        assert_eq!(10 + states.len() * feature_count * 4, binary.len());
+        assert_eq!(BINARY_MAGIC, &binary[..4]);
+        assert_eq!(1, u16::from_le_bytes([binary[4], binary[5]]));
+        let first_feature = i32::from_le_bytes([binary[10], binary[11], binary[12], binary[13]]);
+        assert_eq!(FeatureLayout::V1.extract(&states[0]).unwrap()[0], first_feature);
+
+        let csv = String::from_utf8(export(ExportFormat::Csv)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(states.len() + 1, lines.len());
+        assert!(lines[0].starts_with("fish_0_0,fish_1_0,"));
+        assert_eq!(feature_count, lines[1].split(',').count());
+        assert!(ExportFormat::from_name("parquet").is_err());
+    }
+}
@@ -1,10 +1,15 @@
 use rostware23_lib::game::state::State;
 use super::Rater;
 
+#[derive(Default)]
 pub struct FishDifferenceRater {}
 
 impl Rater for FishDifferenceRater {
     fn rate(game_state: &State) -> i32 {
         game_state.score_of_team(game_state.current_team()) as i32 - game_state.score_of_team(game_state.current_team().opponent()) as i32
     }
+
+    fn rate_per_team(game_state: &State) -> Option<(i32, i32)> {
+        Some((game_state.score_of_team(game_state.current_team()) as i32, -(game_state.score_of_team(game_state.current_team().opponent()) as i32)))
+    }
 }
@@ -0,0 +1,175 @@
+use std::fmt;
+
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::record::GameRecord;
+use rostware23_lib::game::state::State;
+use rostware23_lib::xml::common::Team;
+
+use super::Evaluator;
+use super::board_view::labelled_board_with_highlights;
+use super::pvs_getter::PVSMoveGetter;
+use super::time_measurer::TimeMeasurer;
+
+// Steps through the positions of a recorded game, position n is the state after n moves.
+pub struct GameReplay {
+    record: GameRecord,
+    states: Vec<State>,
+    position: usize
+}
+
+impl GameReplay {
+    pub fn new(record: GameRecord) -> anyhow::Result<Self> {
+        let states = record.states()?;
+        Ok(Self { record, states, position: 0 })
+    }
+
+    pub fn last_position(&self) -> usize {
+        self.record.moves.len()
+    }
+
+    pub fn go_to(&mut self, position: usize) -> anyhow::Result<()> {
+        if position > self.last_position() {
+            anyhow::bail!("The game only has {} moves", self.last_position());
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    pub fn forward(&mut self) -> bool {
+        self.go_to(self.position + 1).is_ok()
+    }
+
+    pub fn back(&mut self) -> bool {
+        self.position.checked_sub(1).is_some_and(|position| self.go_to(position).is_ok())
+    }
+
+    pub fn state(&self) -> &State {
+        &self.states[self.position]
+    }
+
+    // The move leading to the current position and the state it was played in
+    pub fn played_move(&self) -> Option<(&State, &Move)> {
+        let index = self.position.checked_sub(1)?;
+        Some((&self.states[index], &self.record.moves[index]))
+    }
+
+    // Compares the move leading to the current position with the moves the engine finds at the given depth.
+    pub fn review<Heuristic: Evaluator>(&self, move_getter: &PVSMoveGetter<Heuristic>, depth: i32) -> anyhow::Result<Option<MoveReview>> {
+        let Some((state, played_move)) = self.played_move() else {
+            return Ok(None);
+        };
+        let scored_moves = move_getter.score_root_moves(state, depth, &TimeMeasurer::new_infinite())?;
+        let played_score = scored_moves.iter()
+            .find(|(scored_move, _)| scored_move == played_move)
+            .map(|(_, score)| *score);
+        let Some(((best_move, best_score), played_score)) = scored_moves.first().cloned().zip(played_score) else {
+            anyhow::bail!("The played move {} was not searched", played_move.to_notation());
+        };
+        Ok(Some(MoveReview { played_move: played_move.clone(), played_score, best_move, best_score, depth }))
+    }
+}
+
+impl fmt::Display for GameReplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        writeln!(f, "Position {}/{}", self.position, self.last_position())?;
+        let highlighted = match self.played_move() {
+            Some((_, Move::Place(to))) => vec![to.clone()],
+            Some((_, Move::Normal { from, to })) => vec![from.clone(), to.clone()],
+            None => vec![]
+        };
+        writeln!(f, "{}", labelled_board_with_highlights(&state.board, &highlighted))?;
+        if let Some((played_state, played_move)) = self.played_move() {
+            writeln!(f, "Move {}: {} played {}", self.position, self.record.team_name(played_state.current_team()), played_move.to_notation())?;
+        }
+        for team in [Team::One, Team::Two] {
+            let symbol = if team == Team::One { 'G' } else { 'P' };
+            writeln!(f, "{} {}: {} fish", symbol, self.record.team_name(team), state.score_of_team(team))?;
+        }
+        if state.is_over() {
+            return match state.get_result().map_err(|_| fmt::Error)?.winner {
+                Some(winner) => write!(f, "Game over, {} wins", self.record.team_name(winner)),
+                None => write!(f, "Game over, draw")
+            };
+        }
+        let state = state.with_moveless_player_skipped().map_err(|_| fmt::Error)?;
+        write!(f, "{} to move", self.record.team_name(state.current_team()))
+    }
+}
+
+// The engine's view of a played move, scores are from the perspective of the team that played it.
+#[derive(Debug, Clone)]
+pub struct MoveReview {
+    pub played_move: Move,
+    pub played_score: i32,
+    pub best_move: Move,
+    pub best_score: i32,
+    pub depth: i32
+}
+
+impl MoveReview {
+    // How much worse the played move is than the engine's choice
+    pub fn swing(&self) -> i32 {
+        self.best_score - self.played_score
+    }
+}
+
+impl fmt::Display for MoveReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Engine at depth {}: {} ({}), played {} ({}), swing {}", self.depth, self.best_move.to_notation(), self.best_score,
+            self.played_move.to_notation(), self.played_score, self.swing())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::state_generator::create_board_from_seed;
+
+    use super::*;
+    use crate::logic::fish_difference_rater::FishDifferenceRater;
+
+    fn create_record() -> GameRecord {
+        let mut record = GameRecord::new(State::from_initial_board_with_start_team_one(create_board_from_seed(4))).with_team_names("alpha", "beta");
+        let mut state = record.initial_state.clone();
+        while !state.is_over() {
+            state = state.with_moveless_player_skipped().unwrap();
+            let last_move = state.possible_moves().last().unwrap();
+            record.moves.push(last_move.clone());
+            state.perform_move(last_move).unwrap();
+        }
+        record
+    }
+
+    #[test]
+    fn replay_steps_through_all_positions() {
+        let record = create_record();
+        let mut replay = GameReplay::new(record.clone()).unwrap();
+        assert!(!replay.back());
+        assert!(replay.played_move().is_none());
+        assert!(replay.to_string().contains("alpha to move"));
+        while replay.forward() {}
+        assert_eq!(Some(record.moves.last().unwrap()), replay.played_move().map(|(_, played_move)| played_move));
+        assert!(replay.state().is_over());
+        assert!(replay.to_string().contains("Game over"));
+        assert!(replay.go_to(record.moves.len() + 1).is_err());
+        replay.go_to(9).unwrap();
+        assert!(replay.back());
+        let (_, played_move) = replay.played_move().unwrap();
+        assert_eq!(&record.moves[7], played_move);
+        assert!(replay.to_string().contains(&format!("Move 8: beta played {}", played_move.to_notation())));
+    }
+
+    #[test]
+    fn review_compares_played_move_with_best_move() {
+        let mut replay = GameReplay::new(create_record()).unwrap();
+        let move_getter = PVSMoveGetter::<FishDifferenceRater>::new();
+        assert!(replay.review(&move_getter, 2).unwrap().is_none());
+        replay.go_to(12).unwrap();
+        let review = replay.review(&move_getter, 2).unwrap().unwrap();
+        assert!(review.swing() >= 0);
+        assert_eq!(replay.played_move().unwrap().1, &review.played_move);
+        if review.best_move == review.played_move {
+            assert_eq!(0, review.swing());
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use rostware23_lib::game::common::Coordinate;
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::state::State;
+
+use super::transposition_table::{Bound, TranspositionEntry};
+
+pub const DEFAULT_SIZE_IN_MEGABYTES: usize = 32;
+
+const BYTES_PER_MEGABYTE: usize = 1024 * 1024;
+
+// Layout of the packed entry data, from the lowest bit:
+// rating (32 bits), depth (8), bound (2), move kind (2), from (6), to (6), generation (8).
+const DEPTH_SHIFT: u32 = 32;
+const BOUND_SHIFT: u32 = 40;
+const MOVE_KIND_SHIFT: u32 = 42;
+const FROM_SHIFT: u32 = 44;
+const TO_SHIFT: u32 = 50;
+const GENERATION_SHIFT: u32 = 56;
+
+const NO_MOVE: u64 = 0;
+const PLACE_MOVE: u64 = 1;
+const NORMAL_MOVE: u64 = 2;
+
+fn hash_state(state: &State) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn pack(entry: &TranspositionEntry, generation: u8) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3
+    };
+    let (kind, from, to) = match &entry.best_move {
+        None => (NO_MOVE, 0, 0),
+        Some(Move::Place(to)) => (PLACE_MOVE, 0, to.to_field_index()),
+        Some(Move::Normal { from, to }) => (NORMAL_MOVE, from.to_field_index(), to.to_field_index())
+    };
+    entry.rating as u32 as u64
+        | (entry.depth as i8 as u8 as u64) << DEPTH_SHIFT
+        | bound << BOUND_SHIFT
+        | kind << MOVE_KIND_SHIFT
+        | from << FROM_SHIFT
+        | to << TO_SHIFT
+        | (generation as u64) << GENERATION_SHIFT
+}
+
+fn unpack(data: u64) -> TranspositionEntry {
+    let bound = match (data >> BOUND_SHIFT) & 0b11 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        _ => Bound::Upper
+    };
+    let from = Coordinate::from_field_index((data >> FROM_SHIFT) & 0b111111);
+    let to = Coordinate::from_field_index((data >> TO_SHIFT) & 0b111111);
+    let best_move = match (data >> MOVE_KIND_SHIFT) & 0b11 {
+        PLACE_MOVE => Some(Move::Place(to)),
+        NORMAL_MOVE => Some(Move::Normal { from, to }),
+        _ => None
+    };
+    TranspositionEntry {
+        rating: data as u32 as i32,
+        depth: (data >> DEPTH_SHIFT) as u8 as i8 as i32,
+        bound,
+        best_move
+    }
+}
+
+fn depth_of(data: u64) -> i32 {
+    (data >> DEPTH_SHIFT) as u8 as i8 as i32
+}
+
+fn generation_of(data: u64) -> u8 {
+    (data >> GENERATION_SHIFT) as u8
+}
+
+// The key is stored xor-ed with the data, so a slot torn by concurrent writes
+// no longer matches its key and is treated as a miss instead of a wrong entry.
+#[derive(Debug, Default)]
+struct Slot {
+    checked_key: AtomicU64,
+    // Zero marks an empty slot, packed entries always have a bound
+    data: AtomicU64
+}
+
+impl Slot {
+    fn load(&self) -> (u64, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.checked_key.load(Ordering::Relaxed) ^ data;
+        (key, data)
+    }
+
+    fn store(&self, key: u64, data: u64) {
+        self.checked_key.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+// Every bucket has a slot keeping the deepest entry of the current search
+// and a slot that is always overwritten.
+#[derive(Debug, Default)]
+struct Bucket {
+    depth_preferred: Slot,
+    always_replace: Slot
+}
+
+// Can be shared between search threads without locking.
+pub struct HashTranspositionTable {
+    buckets: Vec<Bucket>,
+    generation: AtomicU8
+}
+
+impl HashTranspositionTable {
+    pub fn with_size_in_megabytes(megabytes: usize) -> Self {
+        let maximum_bucket_count = (megabytes * BYTES_PER_MEGABYTE / std::mem::size_of::<Bucket>()).max(1);
+        let bucket_count = 1 << maximum_bucket_count.ilog2();
+        Self {
+            buckets: (0..bucket_count).map(|_| Bucket::default()).collect(),
+            generation: AtomicU8::new(0)
+        }
+    }
+
+    // Called once per search, so entries of earlier turns get replaced first.
+    pub fn start_new_generation(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn bucket_index(&self, key: u64) -> usize {
+        key as usize & (self.buckets.len() - 1)
+    }
+
+    fn find(&self, state: &State) -> Option<u64> {
+        let key = hash_state(state);
+        let bucket = &self.buckets[self.bucket_index(key)];
+        [&bucket.depth_preferred, &bucket.always_replace].into_iter()
+            .map(Slot::load)
+            .find(|(slot_key, data)| *data != 0 && *slot_key == key)
+            .map(|(_, data)| data)
+    }
+
+    pub fn store(&self, state: &State, entry: TranspositionEntry) {
+        let key = hash_state(state);
+        let generation = self.generation.load(Ordering::Relaxed);
+        let data = pack(&entry, generation);
+        let bucket = &self.buckets[self.bucket_index(key)];
+        let (preferred_key, preferred_data) = bucket.depth_preferred.load();
+        let preferred_is_empty = preferred_data == 0;
+        let preferred_is_current = generation_of(preferred_data) == generation;
+        let keeps_deeper_result_of_same_state = !preferred_is_empty
+            && preferred_key == key
+            && preferred_is_current
+            && depth_of(preferred_data) > entry.depth;
+        let replaces_preferred = preferred_is_empty
+            || preferred_key == key
+            || !preferred_is_current
+            || depth_of(preferred_data) <= entry.depth;
+        if replaces_preferred && !keeps_deeper_result_of_same_state {
+            bucket.depth_preferred.store(key, data);
+        } else {
+            bucket.always_replace.store(key, data);
+        }
+    }
+
+    pub fn lookup(&self, state: &State) -> Option<TranspositionEntry> {
+        self.find(state).map(unpack)
+    }
+}
+
+#[cfg(test)]
+impl HashTranspositionTable {
+    fn contains(&self, state: &State) -> bool {
+        self.find(state).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter()
+            .all(|bucket| bucket.depth_preferred.load().1 == 0 && bucket.always_replace.load().1 == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::board::Board;
+
+    use super::*;
+
+    fn state_with_turn(turn: u32) -> State {
+        let mut state = State::from_initial_board_with_start_team_one(Board::empty());
+        state.turn = turn;
+        state
+    }
+
+    #[test]
+    fn table_size_is_power_of_two_within_budget() {
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(3);
+        assert!(transposition_table.buckets.len().is_power_of_two());
+        assert!(transposition_table.buckets.len() * std::mem::size_of::<Bucket>() <= 3 * BYTES_PER_MEGABYTE);
+        assert!(transposition_table.buckets.len() * 2 * std::mem::size_of::<Bucket>() > 3 * BYTES_PER_MEGABYTE);
+    }
+
+    #[test]
+    fn new_hash_transposition_table_is_empty() {
+        assert!(HashTranspositionTable::with_size_in_megabytes(1).is_empty());
+    }
+
+    #[test]
+    fn packed_entries_keep_all_fields() {
+        let entries = [
+            TranspositionEntry::new(-1234, -1, Bound::Exact, None),
+            TranspositionEntry::new(i32::MAX, 64, Bound::Lower, Some(Move::Place(Coordinate::new(15, 7)))),
+            TranspositionEntry::new(i32::MIN, 3, Bound::Upper, Some(Move::Normal { from: Coordinate::new(0, 0), to: Coordinate::new(9, 3) }))
+        ];
+        for entry in entries {
+            assert_eq!(entry, unpack(pack(&entry, 7)));
+        }
+    }
+
+    #[test]
+    fn added_entry_can_be_retrieved() {
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let entry = TranspositionEntry::new(22, 2, Bound::Exact, Some(Move::Place(Coordinate::new(2, 0))));
+        transposition_table.store(&state_with_turn(3), entry.clone());
+        assert!(transposition_table.contains(&state_with_turn(3)));
+        assert!(!transposition_table.contains(&state_with_turn(4)));
+        assert_eq!(entry, transposition_table.lookup(&state_with_turn(3)).unwrap());
+    }
+
+    #[test]
+    fn shallower_entry_of_same_state_does_not_replace_deeper_entry() {
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        transposition_table.store(&state_with_turn(3), TranspositionEntry::new(5, 4, Bound::Exact, None));
+        transposition_table.store(&state_with_turn(3), TranspositionEntry::new(9, 1, Bound::Exact, None));
+        assert_eq!(5, transposition_table.lookup(&state_with_turn(3)).unwrap().rating);
+    }
+
+    #[test]
+    fn colliding_shallow_entry_goes_to_always_replace_slot() {
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(0);
+        assert_eq!(1, transposition_table.buckets.len());
+        transposition_table.store(&state_with_turn(1), TranspositionEntry::new(1, 5, Bound::Exact, None));
+        transposition_table.store(&state_with_turn(2), TranspositionEntry::new(2, 1, Bound::Exact, None));
+        transposition_table.store(&state_with_turn(3), TranspositionEntry::new(3, 1, Bound::Exact, None));
+        assert!(transposition_table.contains(&state_with_turn(1)));
+        assert!(!transposition_table.contains(&state_with_turn(2)));
+        assert!(transposition_table.contains(&state_with_turn(3)));
+    }
+
+    #[test]
+    fn torn_slot_is_treated_as_missing() {
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(0);
+        let state = state_with_turn(1);
+        transposition_table.store(&state, TranspositionEntry::new(1, 5, Bound::Exact, None));
+        let slot = &transposition_table.buckets[0].depth_preferred;
+        slot.data.store(pack(&TranspositionEntry::new(2, 5, Bound::Exact, None), 0), Ordering::Relaxed);
+        assert!(transposition_table.lookup(&state).is_none());
+    }
+
+    #[test]
+    fn entries_of_older_generation_are_replaced_regardless_of_depth() {
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(0);
+        transposition_table.store(&state_with_turn(1), TranspositionEntry::new(1, 5, Bound::Exact, None));
+        transposition_table.start_new_generation();
+        transposition_table.store(&state_with_turn(2), TranspositionEntry::new(2, 1, Bound::Exact, None));
+        transposition_table.store(&state_with_turn(3), TranspositionEntry::new(3, 0, Bound::Exact, None));
+        assert!(!transposition_table.contains(&state_with_turn(1)));
+        assert!(transposition_table.contains(&state_with_turn(2)));
+        assert!(transposition_table.contains(&state_with_turn(3)));
+    }
+}
@@ -0,0 +1,287 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context;
+use rostware23_lib::game::state::State;
+
+use super::Evaluator;
+use super::features::FeatureLayout;
+
+// Weights and activations are fixed point numbers with this many fractional bits
+const FRACTION_BITS: u32 = 14;
+
+// The model used by the default evaluator, see the file header for where its weights come from
+const EMBEDDED_MODEL: &str = include_str!("learned_model.txt");
+
+// Reads the numbers of the next line, which has to start with the key unless the key is "values".
+fn values_of<'text>(lines: &mut impl Iterator<Item = (usize, &'text str)>, key: &str) -> anyhow::Result<Vec<f64>> {
+    let (line_number, line) = lines.next().with_context(|| format!("Missing '{}'", key))?;
+    let mut tokens = line.split_whitespace();
+    if key != "values" && tokens.next() != Some(key) {
+        anyhow::bail!("Expected '{}' in line {}", key, line_number);
+    }
+    tokens.map(|token| token.parse::<f64>().with_context(|| format!("Invalid number '{}' in line {}", token, line_number)))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseLayer {
+    pub biases: Vec<f64>,
+    // One row of input weights per output
+    pub weights: Vec<Vec<f64>>
+}
+
+impl DenseLayer {
+    pub fn inputs(&self) -> usize {
+        self.weights.first().map_or(0, Vec::len)
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.biases.len()
+    }
+}
+
+// Dense layers on a feature vector, every layer but the last one is followed by a ReLU.
+// The single output is multiplied by the scale to get the rating of the current team.
+//
+// Stored as text: "layout VERSION", "scale SCALE" and for every layer "layer INPUTS OUTPUTS"
+// followed by one line per output with the bias and the input weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearnedModel {
+    pub layout: FeatureLayout,
+    pub scale: f64,
+    pub layers: Vec<DenseLayer>
+}
+
+impl LearnedModel {
+    pub fn linear(layout: FeatureLayout, scale: f64, bias: f64, weights: Vec<f64>) -> Self {
+        Self { layout, scale, layers: vec![DenseLayer { biases: vec![bias], weights: vec![weights] }] }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut inputs = self.layout.feature_count();
+        for (index, layer) in self.layers.iter().enumerate() {
+            if layer.outputs() == 0 || layer.weights.len() != layer.outputs() || layer.weights.iter().any(|row| row.len() != inputs) {
+                anyhow::bail!("Layer {} doesn't take the {} outputs of the previous layer", index + 1, inputs);
+            }
+            inputs = layer.outputs();
+        }
+        if inputs != 1 || self.layers.is_empty() {
+            anyhow::bail!("The last layer must have a single output");
+        }
+        Ok(())
+    }
+
+    pub fn from_text(text: &str) -> anyhow::Result<Self> {
+        let mut lines = text.lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.split('#').next().unwrap_or_default().trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+        let layout = match values_of(&mut lines, "layout")?[..] {
+            [version] => FeatureLayout::from_version(version as u16)?,
+            _ => anyhow::bail!("Expected the version of the feature layout")
+        };
+        let scale = match values_of(&mut lines, "scale")?[..] {
+            [scale] => scale,
+            _ => anyhow::bail!("Expected a single scale")
+        };
+        let mut layers = vec![];
+        // Every line left has to belong to a layer
+        while lines.peek().is_some() {
+            let dimensions = values_of(&mut lines, "layer")?;
+            let [inputs, outputs] = dimensions[..] else {
+                anyhow::bail!("Expected the inputs and outputs of layer {}", layers.len() + 1);
+            };
+            let mut layer = DenseLayer { biases: vec![], weights: vec![] };
+            for _ in 0..outputs as usize {
+                let values = values_of(&mut lines, "values")?;
+                if values.len() != inputs as usize + 1 {
+                    anyhow::bail!("Expected a bias and {} weights in layer {}", inputs, layers.len() + 1);
+                }
+                layer.biases.push(values[0]);
+                layer.weights.push(values[1..].to_vec());
+            }
+            layers.push(layer);
+        }
+        let model = Self { layout, scale, layers };
+        model.validate()?;
+        Ok(model)
+    }
+
+    // The unquantized output, the learned evaluator only differs by rounding.
+    pub fn predict(&self, features: &[i32]) -> f64 {
+        let mut activations: Vec<f64> = features.iter().map(|feature| *feature as f64).collect();
+        for (index, layer) in self.layers.iter().enumerate() {
+            activations = layer.biases.iter().zip(&layer.weights)
+                .map(|(bias, row)| bias + row.iter().zip(&activations).map(|(weight, input)| weight * input).sum::<f64>())
+                .map(|output| if index + 1 < self.layers.len() { output.max(0.0) } else { output })
+                .collect();
+        }
+        activations[0] * self.scale
+    }
+}
+
+impl fmt::Display for LearnedModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "layout {}", self.layout.version())?;
+        writeln!(f, "scale {}", self.scale)?;
+        for layer in &self.layers {
+            writeln!(f, "layer {} {}", layer.inputs(), layer.outputs())?;
+            for (bias, row) in layer.biases.iter().zip(&layer.weights) {
+                let weights: Vec<String> = row.iter().map(f64::to_string).collect();
+                writeln!(f, "{} {}", bias, weights.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn quantize(value: f64) -> i64 {
+    (value * (1 << FRACTION_BITS) as f64).round() as i64
+}
+
+#[derive(Debug, Clone)]
+struct QuantizedLayer {
+    inputs: usize,
+    biases: Vec<i64>,
+    // Row major, one row per output
+    weights: Vec<i64>
+}
+
+// Evaluates a learned model with integer arithmetic only.
+#[derive(Debug, Clone)]
+pub struct LearnedEvaluator {
+    model: LearnedModel,
+    layers: Vec<QuantizedLayer>,
+    scale: i64
+}
+
+impl LearnedEvaluator {
+    pub fn new(model: LearnedModel) -> Self {
+        let layers = model.layers.iter()
+            .map(|layer| QuantizedLayer {
+                inputs: layer.inputs(),
+                biases: layer.biases.iter().map(|bias| quantize(*bias)).collect(),
+                weights: layer.weights.iter().flatten().map(|weight| quantize(*weight)).collect()
+            })
+            .collect();
+        Self { scale: quantize(model.scale), model, layers }
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read evaluation model {}", path.display()))?;
+        let model = LearnedModel::from_text(&text).with_context(|| format!("Invalid evaluation model {}", path.display()))?;
+        Ok(Self::new(model))
+    }
+
+    pub fn model(&self) -> &LearnedModel {
+        &self.model
+    }
+
+    fn evaluate_features(&self, features: &[i32]) -> i32 {
+        let mut activations: Vec<i64> = features.iter().map(|feature| i64::from(*feature) << FRACTION_BITS).collect();
+        for (index, layer) in self.layers.iter().enumerate() {
+            activations = layer.biases.iter().zip(layer.weights.chunks(layer.inputs))
+                .map(|(bias, row)| bias + (row.iter().zip(&activations).map(|(weight, input)| weight * input).sum::<i64>() >> FRACTION_BITS))
+                .map(|output| if index + 1 < self.layers.len() { output.max(0) } else { output })
+                .collect();
+        }
+        ((activations[0] * self.scale) >> (2 * FRACTION_BITS)) as i32
+    }
+}
+
+impl Default for LearnedEvaluator {
+    fn default() -> Self {
+        Self::new(LearnedModel::from_text(EMBEDDED_MODEL).expect("The embedded model is valid"))
+    }
+}
+
+impl Evaluator for LearnedEvaluator {
+    fn evaluate(&self, state: &State) -> i32 {
+        let features = self.model.layout.extract(state).expect("Feature layouts only use known raters");
+        self.evaluate_features(&features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::state_generator::create_board_from_seed;
+
+    use super::*;
+    use crate::logic::weighted_evaluator::WeightedEvaluator;
+
+    fn create_state_after_moves(count: usize) -> State {
+        let mut state = State::from_initial_board_with_start_team_one(create_board_from_seed(5));
+        for _ in 0..count {
+            state = state.with_moveless_player_skipped().unwrap();
+            let possible_move = state.possible_moves().last().unwrap();
+            state.perform_move(possible_move).unwrap();
+        }
+        state
+    }
+
+    fn create_network() -> LearnedModel {
+        let layout = FeatureLayout::V1;
+        let inputs = layout.feature_count();
+        let hidden = DenseLayer {
+            biases: vec![0.5, -2.0],
+            weights: vec![
+                (0..inputs).map(|index| (index % 7) as f64 * 0.01).collect(),
+                (0..inputs).map(|index| if index % 3 == 0 { -0.25 } else { 0.125 }).collect()
+            ]
+        };
+        let output = DenseLayer { biases: vec![1.0], weights: vec![vec![1.5, -0.75]] };
+        LearnedModel { layout, scale: 10.0, layers: vec![hidden, output] }
+    }
+
+    #[test]
+    fn embedded_model_rates_like_mid_game_weights() {
+        let evaluator = LearnedEvaluator::default();
+        let state = create_state_after_moves(12);
+        assert_eq!(WeightedEvaluator::default().evaluate(&state), evaluator.evaluate(&state));
+    }
+
+    #[test]
+    fn quantized_network_is_close_to_float_model() {
+        let evaluator = LearnedEvaluator::new(create_network());
+        for moves in [8, 14, 20] {
+            let state = create_state_after_moves(moves);
+            let features = FeatureLayout::V1.extract(&state).unwrap();
+            let expected = evaluator.model().predict(&features);
+            assert!((expected - evaluator.evaluate(&state) as f64).abs() <= 1.0, "{} {}", expected, evaluator.evaluate(&state));
+        }
+    }
+
+    #[test]
+    fn model_text_can_be_read_again() {
+        let model = create_network();
+        assert_eq!(model, LearnedModel::from_text(&model.to_string()).unwrap());
+        let path = std::env::temp_dir().join("rostware23_learned_model_test.txt");
+        std::fs::write(&path, model.to_string()).unwrap();
+        let loaded = LearnedEvaluator::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&model, loaded.unwrap().model());
+    }
+
+    #[test]
+    fn models_with_mismatching_layers_are_rejected() {
+        let mut model = create_network();
+        model.layers[1].weights[0].push(1.0);
+        assert!(LearnedModel::from_text(&model.to_string()).is_err());
+        assert!(LearnedModel::from_text("layout 1\nscale 1\n").is_err());
+        assert!(LearnedModel::from_text("layout 1\nscale 1\nlayer 2 1\n0 1 1\n").is_err());
+        assert!(LearnedModel::from_text("layout 7\nscale 1\n").is_err());
+    }
+
+    #[test]
+    fn models_with_malformed_or_trailing_layer_lines_are_rejected() {
+        let text = create_network().to_string();
+        assert!(LearnedModel::from_text(&text).is_ok());
+        assert!(LearnedModel::from_text(&text.replacen("layer 2 1", "layr 2 1", 1)).is_err());
+        assert!(LearnedModel::from_text(&text.replacen("layer 2 1", "layer 2 one", 1)).is_err());
+        assert!(LearnedModel::from_text(&format!("{}layer 1 1\n", text)).is_err());
+        assert!(LearnedModel::from_text(&format!("{}0 1\n", text)).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+use super::features::FeatureLayout;
+use super::learned_evaluator::LearnedModel;
+use super::self_play::TrainingPosition;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrainingMethod {
+    // Fits the search scores of the positions
+    LeastSquares,
+    // Fits the outcomes of the games, the model rates the log odds of winning times the scale
+    Logistic
+}
+
+impl TrainingMethod {
+    pub fn from_name(name: &str) -> anyhow::Result<Self> {
+        match name {
+            "least-squares" => Ok(Self::LeastSquares),
+            "logistic" => Ok(Self::Logistic),
+            _ => anyhow::bail!("Unknown training method '{}', expected least-squares or logistic", name)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingSettings {
+    pub method: TrainingMethod,
+    pub layout: FeatureLayout,
+    // Ridge penalty on the weights of the standardized features
+    pub l2: f64,
+    // Gradient descent only, least squares is solved directly
+    pub iterations: usize,
+    pub learning_rate: f64,
+    pub scale: f64
+}
+
+impl Default for TrainingSettings {
+    fn default() -> Self {
+        Self { method: TrainingMethod::LeastSquares, layout: FeatureLayout::LATEST, l2: 0.001, iterations: 500, learning_rate: 0.5, scale: 100.0 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingResult {
+    pub model: LearnedModel,
+    pub samples: usize,
+    // Mean squared error for least squares, mean log loss for logistic regression
+    pub loss: f64
+}
+
+// Features are shifted to a mean of 0 and scaled to a standard deviation of 1 while training,
+// constant features keep a weight of 0.
+struct Standardization {
+    means: Vec<f64>,
+    deviations: Vec<f64>
+}
+
+impl Standardization {
+    fn of(rows: &[Vec<i32>], feature_count: usize) -> Self {
+        let samples = rows.len() as f64;
+        let means: Vec<f64> = (0..feature_count)
+            .map(|feature| rows.iter().map(|row| row[feature] as f64).sum::<f64>() / samples)
+            .collect();
+        let deviations = (0..feature_count)
+            .map(|feature| (rows.iter().map(|row| (row[feature] as f64 - means[feature]).powi(2)).sum::<f64>() / samples).sqrt())
+            .collect();
+        Self { means, deviations }
+    }
+
+    fn apply(&self, row: &[i32]) -> Vec<f64> {
+        row.iter().enumerate()
+            .map(|(feature, value)| if self.deviations[feature] > 0.0 { (*value as f64 - self.means[feature]) / self.deviations[feature] } else { 0.0 })
+            .collect()
+    }
+
+    // Turns weights of standardized features into weights of the raw features.
+    fn fold(&self, bias: f64, weights: &[f64]) -> (f64, Vec<f64>) {
+        let raw_weights: Vec<f64> = weights.iter().enumerate()
+            .map(|(feature, weight)| if self.deviations[feature] > 0.0 { weight / self.deviations[feature] } else { 0.0 })
+            .collect();
+        let raw_bias = bias - raw_weights.iter().zip(&self.means).map(|(weight, mean)| weight * mean).sum::<f64>();
+        (raw_bias, raw_weights)
+    }
+}
+
+fn dot(weights: &[f64], row: &[f64]) -> f64 {
+    weights.iter().zip(row).map(|(weight, value)| weight * value).sum()
+}
+
+fn sigmoid(value: f64) -> f64 {
+    1.0 / (1.0 + (-value).exp())
+}
+
+// Gaussian elimination with partial pivoting, the matrix is extended by the right hand side.
+fn solve(mut matrix: Vec<Vec<f64>>) -> anyhow::Result<Vec<f64>> {
+    let size = matrix.len();
+    for column in 0..size {
+        let pivot = (column..size)
+            .max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs()))
+            .unwrap_or(column);
+        if matrix[pivot][column].abs() < 1e-12 {
+            anyhow::bail!("The features are linearly dependent, use a larger l2 penalty");
+        }
+        matrix.swap(column, pivot);
+        let (pivot_rows, rows) = matrix.split_at_mut(column + 1);
+        let pivot_row = &pivot_rows[column];
+        for row in rows {
+            let factor = row[column] / pivot_row[column];
+            for (entry, pivot_entry) in row.iter_mut().zip(pivot_row).skip(column) {
+                *entry -= factor * pivot_entry;
+            }
+        }
+    }
+    let mut solution = vec![0.0; size];
+    for row in (0..size).rev() {
+        let sum: f64 = (row + 1..size).map(|column| matrix[row][column] * solution[column]).sum();
+        solution[row] = (matrix[row][size] - sum) / matrix[row][row];
+    }
+    Ok(solution)
+}
+
+// Ridge regression by the normal equations, the bias is the mean target.
+fn fit_least_squares(rows: &[Vec<f64>], targets: &[f64], l2: f64) -> anyhow::Result<(f64, Vec<f64>)> {
+    let feature_count = rows[0].len();
+    let samples = rows.len() as f64;
+    let mean_target = targets.iter().sum::<f64>() / samples;
+    let mut matrix = vec![vec![0.0; feature_count + 1]; feature_count];
+    for (row, target) in rows.iter().zip(targets) {
+        for i in 0..feature_count {
+            if row[i] == 0.0 {
+                continue;
+            }
+            for j in 0..feature_count {
+                matrix[i][j] += row[i] * row[j];
+            }
+            matrix[i][feature_count] += row[i] * (target - mean_target);
+        }
+    }
+    for (i, equation) in matrix.iter_mut().enumerate() {
+        equation[i] += l2 * samples;
+        // Constant features are all zero after standardization
+        if equation[i] == 0.0 {
+            equation[i] = 1.0;
+        }
+    }
+    Ok((mean_target, solve(matrix)?))
+}
+
+// Full batch gradient descent on the mean log loss.
+fn fit_logistic(rows: &[Vec<f64>], targets: &[f64], settings: &TrainingSettings) -> (f64, Vec<f64>) {
+    let samples = rows.len() as f64;
+    let mut bias = 0.0;
+    let mut weights = vec![0.0; rows[0].len()];
+    for _ in 0..settings.iterations {
+        let mut bias_gradient = 0.0;
+        let mut gradient: Vec<f64> = weights.iter().map(|weight| settings.l2 * weight).collect();
+        for (row, target) in rows.iter().zip(targets) {
+            let error = sigmoid(bias + dot(&weights, row)) - target;
+            bias_gradient += error / samples;
+            for (gradient, value) in gradient.iter_mut().zip(row) {
+                *gradient += error * value / samples;
+            }
+        }
+        bias -= settings.learning_rate * bias_gradient;
+        for (weight, gradient) in weights.iter_mut().zip(&gradient) {
+            *weight -= settings.learning_rate * gradient;
+        }
+    }
+    (bias, weights)
+}
+
+fn loss(method: TrainingMethod, rows: &[Vec<f64>], targets: &[f64], bias: f64, weights: &[f64]) -> f64 {
+    let losses = rows.iter().zip(targets).map(|(row, target)| {
+        let prediction = bias + dot(weights, row);
+        match method {
+            TrainingMethod::LeastSquares => (prediction - target).powi(2),
+            TrainingMethod::Logistic => {
+                let probability = sigmoid(prediction).clamp(1e-12, 1.0 - 1e-12);
+                -(target * probability.ln() + (1.0 - target) * (1.0 - probability).ln())
+            }
+        }
+    });
+    losses.sum::<f64>() / rows.len() as f64
+}
+
+pub fn train(positions: &[TrainingPosition], settings: &TrainingSettings) -> anyhow::Result<TrainingResult> {
+    if positions.is_empty() {
+        anyhow::bail!("No training positions");
+    }
+    let raw_rows: Vec<Vec<i32>> = positions.iter()
+        .map(|position| settings.layout.extract(&position.state))
+        .collect::<anyhow::Result<_>>()?;
+    let standardization = Standardization::of(&raw_rows, settings.layout.feature_count());
+    let rows: Vec<Vec<f64>> = raw_rows.iter().map(|row| standardization.apply(row)).collect();
+    let targets: Vec<f64> = positions.iter()
+        .map(|position| match settings.method {
+            TrainingMethod::LeastSquares => position.score as f64,
+            TrainingMethod::Logistic => (position.outcome as f64 + 1.0) / 2.0
+        })
+        .collect();
+
+    let (bias, weights) = match settings.method {
+        TrainingMethod::LeastSquares => fit_least_squares(&rows, &targets, settings.l2)?,
+        TrainingMethod::Logistic => fit_logistic(&rows, &targets, settings)
+    };
+    let loss = loss(settings.method, &rows, &targets, bias, &weights);
+    let (raw_bias, raw_weights) = standardization.fold(bias, &weights);
+    let scale = match settings.method {
+        TrainingMethod::LeastSquares => 1.0,
+        TrainingMethod::Logistic => settings.scale
+    };
+    Ok(TrainingResult { model: LearnedModel::linear(settings.layout, scale, raw_bias, raw_weights), samples: positions.len(), loss })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::random_getter::RandomGetter;
+    use crate::logic::self_play::{SelfPlaySettings, play_game};
+
+    fn create_positions() -> Vec<TrainingPosition> {
+        let engine = RandomGetter::new();
+        let settings = SelfPlaySettings { games: 4, seed: 3, time_limit_in_millis: 100, exploration: 0.0, exploration_plies: 0 };
+        (0..settings.games).flat_map(|game| play_game(&engine, &engine, game, &settings).unwrap()).collect()
+    }
+
+    fn fish_difference(position: &TrainingPosition) -> i32 {
+        let team = position.state.current_team();
+        position.state.score_of_team(team) as i32 - position.state.score_of_team(team.opponent()) as i32
+    }
+
+    #[test]
+    fn least_squares_recovers_linear_scores() {
+        let mut positions = create_positions();
+        for position in &mut positions {
+            position.score = 3 * fish_difference(position) + 7;
+        }
+        let settings = TrainingSettings { l2: 1e-9, ..TrainingSettings::default() };
+        let result = train(&positions, &settings).unwrap();
+        assert_eq!(positions.len(), result.samples);
+        assert!(result.loss < 0.01, "{}", result.loss);
+        for position in &positions {
+            let features = settings.layout.extract(&position.state).unwrap();
+            assert!((result.model.predict(&features) - position.score as f64).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn logistic_regression_separates_outcomes() {
+        let mut positions = create_positions();
+        for position in &mut positions {
+            position.outcome = if fish_difference(position) > 0 { 1 } else { -1 };
+        }
+        let settings = TrainingSettings { method: TrainingMethod::Logistic, ..TrainingSettings::default() };
+        let result = train(&positions, &settings).unwrap();
+        assert!(result.loss < 2f64.ln());
+        assert_eq!(settings.scale, result.model.scale);
+        let correct = positions.iter()
+            .filter(|position| {
+                let features = settings.layout.extract(&position.state).unwrap();
+                (result.model.predict(&features) > 0.0) == (position.outcome > 0)
+            })
+            .count();
+        assert!(correct * 10 >= positions.len() * 9, "{} of {}", correct, positions.len());
+    }
+
+    #[test]
+    fn linear_systems_are_solved() {
+        let solution = solve(vec![vec![0.0, 2.0, 4.0], vec![1.0, 1.0, 3.0]]).unwrap();
+        assert!((solution[0] - 1.0).abs() < 1e-9 && (solution[1] - 2.0).abs() < 1e-9);
+        assert!(solve(vec![vec![1.0, 1.0, 1.0], vec![2.0, 2.0, 2.0]]).is_err());
+        assert!(TrainingMethod::from_name("adam").is_err());
+    }
+}
@@ -0,0 +1,399 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::time::Instant;
+
+use anyhow::Context;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rostware23_lib::game::common::Team;
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::state::State;
+
+use super::MoveGetter;
+use super::Rater;
+use super::search_report::SearchReport;
+use super::time_measurer::TimeMeasurer;
+
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+// Used when there is no time limit, so a search always ends.
+pub const DEFAULT_ITERATION_LIMIT_WITHOUT_TIME: u32 = 2000;
+
+// Ratings are mapped to a winning chance by a logistic curve of this width.
+const HEURISTIC_SCALE: f64 = 10.0;
+// Chance of a guided playout to choose a random move instead of the best rated one.
+const GUIDED_PLAYOUT_RANDOMNESS: f64 = 0.25;
+// Our move and the opponent's answer lie between two searches.
+const REUSE_SEARCH_DEPTH: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafEvaluation {
+    RandomPlayout,
+    // Playouts mostly choose the move with the best rating for the moving team
+    GuidedPlayout,
+    // Leaves are rated by the heuristic directly instead of being played out
+    Heuristic
+}
+
+struct Node {
+    state: State,
+    last_move: Option<Move>,
+    // The team that performed last_move, values are from its perspective
+    mover: Option<Team>,
+    children: Vec<usize>,
+    untried_moves: Vec<Move>,
+    visits: u32,
+    total_value: f64
+}
+
+impl Node {
+    fn new(state: State, last_move: Option<Move>, mover: Option<Team>, rng: &mut impl Rng) -> anyhow::Result<Self> {
+        let state = if state.is_over() { state } else { state.with_moveless_player_skipped()? };
+        let mut untried_moves: Vec<Move> = state.possible_moves().collect();
+        untried_moves.shuffle(rng);
+        Ok(Self {
+            state,
+            last_move,
+            mover,
+            children: vec![],
+            untried_moves,
+            visits: 0,
+            total_value: 0.0
+        })
+    }
+
+    fn mean_value(&self) -> f64 {
+        if self.visits == 0 { 0.0 } else { self.total_value / self.visits as f64 }
+    }
+}
+
+// All nodes live in one vector, the root is always at index 0.
+struct Tree {
+    nodes: Vec<Node>
+}
+
+impl Tree {
+    fn new(state: State, rng: &mut impl Rng) -> anyhow::Result<Self> {
+        Ok(Self { nodes: vec![Node::new(state, None, None, rng)?] })
+    }
+
+    fn most_visited_child(&self, index: usize) -> Option<usize> {
+        self.nodes[index].children.iter()
+            .copied()
+            .max_by_key(|child| self.nodes[*child].visits)
+    }
+
+    fn principal_variation(&self) -> Vec<Move> {
+        let mut principal_variation = vec![];
+        let mut index = 0;
+        while let Some(child) = self.most_visited_child(index) {
+            principal_variation.extend(self.nodes[child].last_move.clone());
+            index = child;
+        }
+        principal_variation
+    }
+
+    fn find_within_depth(&self, state: &State, maximum_depth: usize) -> Option<usize> {
+        let mut queue = VecDeque::from([(0, 0)]);
+        while let Some((index, depth)) = queue.pop_front() {
+            if &self.nodes[index].state == state {
+                return Some(index);
+            }
+            if depth < maximum_depth {
+                queue.extend(self.nodes[index].children.iter().map(|child| (*child, depth + 1)));
+            }
+        }
+        None
+    }
+
+    // Keeps only the subtree below the given node, which becomes the new root.
+    fn rerooted(self, new_root: usize) -> Self {
+        let mut kept_indices = vec![new_root];
+        let mut position = 0;
+        while position < kept_indices.len() {
+            kept_indices.extend(self.nodes[kept_indices[position]].children.iter().copied());
+            position += 1;
+        }
+        let mut new_indices = vec![0; self.nodes.len()];
+        for (new_index, old_index) in kept_indices.iter().enumerate() {
+            new_indices[*old_index] = new_index;
+        }
+
+        let mut old_nodes: Vec<Option<Node>> = self.nodes.into_iter().map(Some).collect();
+        let mut nodes: Vec<Node> = kept_indices.iter()
+            .filter_map(|old_index| old_nodes[*old_index].take())
+            .map(|mut node| {
+                node.children = node.children.iter().map(|child| new_indices[*child]).collect();
+                node
+            })
+            .collect();
+        nodes[0].last_move = None;
+        nodes[0].mover = None;
+        Self { nodes }
+    }
+}
+
+pub struct MctsMoveGetter<Heuristic: Rater> {
+    phantom: PhantomData<Heuristic>,
+    leaf_evaluation: LeafEvaluation,
+    iteration_limit: Option<u32>,
+    // Orders the untried moves and chooses the playout moves
+    rng: RefCell<StdRng>,
+    // Kept between turns, so the subtree of the reached state can be reused
+    tree: RefCell<Option<Tree>>
+}
+
+impl<Heuristic: Rater> MctsMoveGetter<Heuristic> {
+    pub fn new(leaf_evaluation: LeafEvaluation) -> Self {
+        Self {
+            phantom: PhantomData,
+            leaf_evaluation,
+            iteration_limit: None,
+            rng: RefCell::new(StdRng::from_entropy()),
+            tree: RefCell::new(None)
+        }
+    }
+
+    #[cfg(test)]
+    fn with_iteration_limit(mut self, iteration_limit: u32) -> Self {
+        self.iteration_limit = Some(iteration_limit);
+        self
+    }
+
+    // Makes the searches reproducible, as long as the same states are searched in the same order.
+    #[cfg(test)]
+    fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = RefCell::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    fn iteration_limit(&self, time_measurer: &TimeMeasurer) -> u32 {
+        match (self.iteration_limit, time_measurer) {
+            (Some(iteration_limit), _) => iteration_limit,
+            (None, TimeMeasurer::Infinite) => DEFAULT_ITERATION_LIMIT_WITHOUT_TIME,
+            (None, TimeMeasurer::Normal { .. }) => u32::MAX
+        }
+    }
+
+    fn take_reusable_tree(&self, state: &State) -> anyhow::Result<Tree> {
+        let normalized_state = if state.is_over() { state.clone() } else { state.with_moveless_player_skipped()? };
+        let reused_tree = self.tree.borrow_mut().take().and_then(|tree| {
+            let new_root = tree.find_within_depth(&normalized_state, REUSE_SEARCH_DEPTH)?;
+            Some(tree.rerooted(new_root))
+        });
+        match reused_tree {
+            Some(tree) => Ok(tree),
+            None => Tree::new(state.clone(), &mut *self.rng.borrow_mut())
+        }
+    }
+
+    fn select_child(&self, tree: &Tree, index: usize) -> usize {
+        let parent_visits = tree.nodes[index].visits.max(1) as f64;
+        let uct = |child: &usize| {
+            let node = &tree.nodes[*child];
+            let visits = node.visits.max(1) as f64;
+            node.mean_value() + EXPLORATION * (parent_visits.ln() / visits).sqrt()
+        };
+        *tree.nodes[index].children.iter()
+            .max_by(|first, second| uct(first).total_cmp(&uct(second)))
+            .unwrap()
+    }
+
+    fn expand(&self, tree: &mut Tree, index: usize) -> anyhow::Result<usize> {
+        let next_move = match tree.nodes[index].untried_moves.pop() {
+            Some(next_move) => next_move,
+            None => return Ok(index)
+        };
+        let parent_state = &tree.nodes[index].state;
+        let mover = parent_state.current_team();
+        let child_state = parent_state.with_move_performed(next_move.clone())?;
+        let child = Node::new(child_state, Some(next_move), Some(mover), &mut *self.rng.borrow_mut())?;
+        tree.nodes.push(child);
+        let child_index = tree.nodes.len() - 1;
+        tree.nodes[index].children.push(child_index);
+        Ok(child_index)
+    }
+
+    fn choose_playout_move(&self, state: &State, rng: &mut impl Rng) -> Option<Move> {
+        let possible_moves: Vec<Move> = state.possible_moves().collect();
+        if self.leaf_evaluation == LeafEvaluation::RandomPlayout || rng.gen_bool(GUIDED_PLAYOUT_RANDOMNESS) {
+            return possible_moves.choose(rng).cloned();
+        }
+        // The rating after the move is from the opponent's perspective
+        possible_moves.into_iter()
+            .filter_map(|possible_move| {
+                let rating = Heuristic::rate(&state.with_move_performed(possible_move.clone()).ok()?);
+                Some((possible_move, -rating))
+            })
+            .max_by_key(|(_, rating)| *rating)
+            .map(|(best_move, _)| best_move)
+    }
+
+    // Returns the chance of team one winning from the given state.
+    fn evaluate(&self, mut state: State) -> anyhow::Result<f64> {
+        if !state.is_over() && self.leaf_evaluation == LeafEvaluation::Heuristic {
+            let winning_chance = 1.0 / (1.0 + (-Heuristic::rate(&state) as f64 / HEURISTIC_SCALE).exp());
+            return Ok(if state.current_team() == Team::One { winning_chance } else { 1.0 - winning_chance });
+        }
+
+        let mut rng = self.rng.borrow_mut();
+        while !state.is_over() {
+            state = state.with_moveless_player_skipped()?;
+            let playout_move = self.choose_playout_move(&state, &mut *rng).context("No playout move found")?;
+            state.perform_move(playout_move)?;
+        }
+        Ok(match state.get_result()?.winner {
+            Some(Team::One) => 1.0,
+            Some(Team::Two) => 0.0,
+            None => 0.5
+        })
+    }
+
+    fn run_iteration(&self, tree: &mut Tree) -> anyhow::Result<()> {
+        let mut path = vec![0];
+        let mut index = 0;
+        while tree.nodes[index].untried_moves.is_empty() && !tree.nodes[index].children.is_empty() {
+            index = self.select_child(tree, index);
+            path.push(index);
+        }
+        let leaf = self.expand(tree, index)?;
+        if leaf != index {
+            path.push(leaf);
+        }
+
+        let team_one_value = self.evaluate(tree.nodes[leaf].state.clone())?;
+        for index in path {
+            let node = &mut tree.nodes[index];
+            node.visits += 1;
+            node.total_value += match node.mover {
+                Some(Team::Two) => 1.0 - team_one_value,
+                _ => team_one_value
+            };
+        }
+        Ok(())
+    }
+}
+
+impl<Heuristic: Rater> MoveGetter for MctsMoveGetter<Heuristic> {
+    fn get_move(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<Move> {
+        self.search(state, time_measurer).map(|report| report.best_move)
+    }
+
+    fn search(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<SearchReport> {
+        let start = Instant::now();
+        let mut tree = self.take_reusable_tree(state)?;
+        let iteration_limit = self.iteration_limit(time_measurer);
+        let mut iterations = 0;
+        while iterations < iteration_limit && time_measurer.has_time_left() {
+            self.run_iteration(&mut tree)?;
+            iterations += 1;
+        }
+
+        let best_child = tree.most_visited_child(0).context("MoveGetter invoked without possible moves!")?;
+        let best_node = &tree.nodes[best_child];
+        let mut report = SearchReport::from_move(best_node.last_move.clone().context("Child node without move")?, 0);
+        // Permille chance of winning, as there is no rating scale for the outcome of playouts
+        report.score = (best_node.mean_value() * 1000.0) as i32;
+        report.principal_variation = tree.principal_variation();
+        report.completed_depth = report.principal_variation.len() as i32;
+        report.nodes = iterations as u64;
+        report.time_used_millis = start.elapsed().as_millis();
+        *self.tree.borrow_mut() = Some(tree);
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::board::Board;
+    use rostware23_lib::game::common::Coordinate;
+    use rostware23_lib::xml::state::FieldState;
+
+    use super::*;
+    use crate::logic::fish_difference_rater::FishDifferenceRater;
+
+    fn create_one_or_two_fish_state() -> State {
+        let mut board = Board::empty();
+        board.perform_move(Move::Place(Coordinate::new(2, 0)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(4, 0)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(6, 0)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(12, 0)), Team::One).unwrap();
+        board.set(Coordinate::new(10, 0), FieldState::Fish(2)).unwrap();
+        board.set(Coordinate::new(14, 0), FieldState::Fish(1)).unwrap();
+        // Team two is already stuck, so it can not place a penguin on the remaining fish
+        for x in [0, 4, 8, 12] {
+            board.perform_move(Move::Place(Coordinate::new(x, 4)), Team::Two).unwrap();
+        }
+        let mut state = State::from_initial_board_with_start_team_one(board);
+        // Only taking two fish wins, one fish results in a draw
+        state.team_two_fish = 1;
+        state
+    }
+
+    #[test]
+    fn mcts_chooses_winning_move_over_draw() {
+        let mcts_getter = MctsMoveGetter::<FishDifferenceRater>::new(LeafEvaluation::RandomPlayout).with_iteration_limit(50);
+        let expected_move = Move::Normal { from: Coordinate::new(12, 0), to: Coordinate::new(10, 0) };
+        assert_eq!(expected_move, mcts_getter.get_move(&create_one_or_two_fish_state(), &TimeMeasurer::Infinite).unwrap());
+    }
+
+    #[test]
+    fn mcts_report_counts_iterations_and_starts_principal_variation_with_best_move() {
+        let mcts_getter = MctsMoveGetter::<FishDifferenceRater>::new(LeafEvaluation::RandomPlayout).with_iteration_limit(30);
+        let report = mcts_getter.search(&create_one_or_two_fish_state(), &TimeMeasurer::Infinite).unwrap();
+        assert_eq!(30, report.nodes);
+        assert_eq!(Some(&report.best_move), report.principal_variation.first());
+    }
+
+    #[test]
+    fn subtree_of_reached_state_is_reused_in_next_search() {
+        let mcts_getter = MctsMoveGetter::<FishDifferenceRater>::new(LeafEvaluation::GuidedPlayout).with_iteration_limit(200);
+        let state = rostware23_lib::game::state_generator::create_any();
+        let our_move = mcts_getter.get_move(&state, &TimeMeasurer::Infinite).unwrap();
+        let state_after_our_move = state.with_move_performed(our_move).unwrap();
+        let tree = mcts_getter.tree.borrow_mut().take().unwrap();
+        let our_node = tree.find_within_depth(&state_after_our_move, 1).unwrap();
+        let answer_node = tree.most_visited_child(our_node).unwrap();
+        let expected_visits = tree.nodes[answer_node].visits;
+        let next_state = tree.nodes[answer_node].state.clone();
+        *mcts_getter.tree.borrow_mut() = Some(tree);
+
+        let reused_tree = mcts_getter.take_reusable_tree(&next_state).unwrap();
+        assert!(expected_visits > 0);
+        assert_eq!(expected_visits, reused_tree.nodes[0].visits);
+        assert_eq!(next_state, reused_tree.nodes[0].state);
+        assert!(reused_tree.nodes[0].children.iter().all(|child| *child < reused_tree.nodes.len()));
+    }
+
+    fn create_smaller_catch_first_state() -> State {
+        let mut board = Board::empty();
+        for x in [2, 4, 6, 12] {
+            board.perform_move(Move::Place(Coordinate::new(x, 0)), Team::One).unwrap();
+        }
+        board.set(Coordinate::new(10, 0), FieldState::Fish(1)).unwrap();
+        board.set(Coordinate::new(9, 1), FieldState::Fish(3)).unwrap();
+        board.set(Coordinate::new(14, 0), FieldState::Fish(2)).unwrap();
+        // Team two is already stuck
+        for x in [0, 4, 8, 12] {
+            board.perform_move(Move::Place(Coordinate::new(x, 4)), Team::Two).unwrap();
+        }
+        let mut state = State::from_initial_board_with_start_team_one(board);
+        // Only taking one and then three fish wins, taking two fish at once loses
+        state.team_two_fish = 3;
+        state
+    }
+
+    #[test]
+    fn mcts_chooses_winning_line_over_more_fish_now_with_every_leaf_evaluation_and_seed() {
+        let expected_move = Move::Normal { from: Coordinate::new(12, 0), to: Coordinate::new(10, 0) };
+        let state = create_smaller_catch_first_state();
+        assert_eq!(2, state.possible_moves().count());
+        for leaf_evaluation in [LeafEvaluation::RandomPlayout, LeafEvaluation::GuidedPlayout, LeafEvaluation::Heuristic] {
+            for seed in 0..10 {
+                let mcts_getter = MctsMoveGetter::<FishDifferenceRater>::new(leaf_evaluation).with_iteration_limit(50).with_seed(seed);
+                assert_eq!(expected_move, mcts_getter.get_move(&state, &TimeMeasurer::Infinite).unwrap(), "{:?} with seed {}", leaf_evaluation, seed);
+            }
+        }
+    }
+}
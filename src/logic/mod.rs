@@ -1,12 +1,29 @@
 pub mod battle;
 pub mod time_measurer;
-pub mod ordered_move_generator;
+pub mod move_getter_player;
+pub mod move_getter_factory;
+pub mod engine_protocol;
+pub mod board_view;
+pub mod play_session;
+pub mod game_replay;
+pub mod search_report;
+pub mod move_ordering;
 
 pub mod random_getter;
 pub mod pvs_getter;
+pub mod mcts_getter;
 
 pub mod staged_rater;
 pub mod combined_rater;
+pub mod weighted_evaluator;
+pub mod evaluation_trace;
+pub mod features;
+pub mod spsa_tuner;
+pub mod self_play;
+pub mod tournament;
+pub mod learned_evaluator;
+pub mod selected_evaluator;
+pub mod linear_trainer;
 
 pub mod fish_difference_rater;
 pub mod potential_fish_rater;
@@ -18,21 +35,72 @@ pub mod restricted_reachable_fish_rater;
 pub mod quadrant_occupation_rater;
 pub mod penguin_cutoff_rater;
 pub mod edge_penguin_penalty;
-pub mod state_selector;
+pub mod territory_rater;
 pub mod transposition_table;
-pub mod simple_transposition_table;
-pub mod selective_transposition_table;
+pub mod hash_transposition_table;
 mod board_parser;
 
+use std::time::Instant;
+
 use rostware23_lib::game::state::State;
 use rostware23_lib::game::moves::Move;
 
+use evaluation_trace::{ComponentTrace, EvaluationTrace};
+use search_report::SearchReport;
 use time_measurer::TimeMeasurer;
 
 pub trait MoveGetter {
     fn get_move(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<Move>;
+
+    fn search(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<SearchReport> {
+        let start = Instant::now();
+        let best_move = self.get_move(state, time_measurer)?;
+        Ok(SearchReport::from_move(best_move, start.elapsed().as_millis()))
+    }
+}
+
+impl<Getter: MoveGetter + ?Sized> MoveGetter for Box<Getter> {
+    fn get_move(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<Move> {
+        self.as_ref().get_move(state, time_measurer)
+    }
+
+    fn search(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<SearchReport> {
+        self.as_ref().search(state, time_measurer)
+    }
 }
 
 pub trait Rater {
     fn rate(state: &State) -> i32;
+
+    // The shares of the current team and its opponent in the rating, for raters adding up ratings of both teams.
+    fn rate_per_team(_state: &State) -> Option<(i32, i32)> {
+        None
+    }
+}
+
+// Like Rater, but can be configured at runtime and used as a trait object.
+pub trait Evaluator {
+    fn evaluate(&self, state: &State) -> i32;
+
+    fn trace(&self, state: &State) -> EvaluationTrace {
+        let component = ComponentTrace { name: "evaluation".to_string(), raw: self.evaluate(state), weight: 1, per_team: None };
+        EvaluationTrace::new(state, vec![component])
+    }
+}
+
+impl<Heuristic: Rater> Evaluator for Heuristic {
+    fn evaluate(&self, state: &State) -> i32 {
+        Heuristic::rate(state)
+    }
+
+    fn trace(&self, state: &State) -> EvaluationTrace {
+        let type_name = std::any::type_name::<Heuristic>();
+        let component = ComponentTrace {
+            name: type_name.split('<').next().unwrap_or(type_name).rsplit("::").next().unwrap_or(type_name).to_string(),
+            raw: Heuristic::rate(state),
+            weight: 1,
+            per_team: Heuristic::rate_per_team(state)
+        };
+        EvaluationTrace::new(state, vec![component])
+    }
 }
@@ -0,0 +1,54 @@
+use super::MoveGetter;
+use super::combined_rater::CombinedRater;
+use super::engine_protocol::ExternalMoveGetter;
+use super::learned_evaluator::LearnedEvaluator;
+use super::mcts_getter::{LeafEvaluation, MctsMoveGetter};
+use super::pvs_getter::PVSMoveGetter;
+use super::random_getter::RandomGetter;
+use super::weighted_evaluator::WeightedEvaluator;
+
+pub const MOVE_GETTER_NAMES: [&str; 8] = ["pvs", "pvs-fixed", "pvs-weighted", "pvs-learned", "mcts", "mcts-guided", "mcts-rated", "random"];
+
+// Engines running in another process are named by their command line
+const EXTERNAL_PREFIX: &str = "external:";
+
+pub fn is_move_getter_name(name: &str) -> bool {
+    MOVE_GETTER_NAMES.contains(&name) || name.strip_prefix(EXTERNAL_PREFIX).is_some_and(|command_line| !command_line.trim().is_empty())
+}
+
+pub fn create_move_getter(name: &str) -> anyhow::Result<Box<dyn MoveGetter + Send>> {
+    if let Some(command_line) = name.strip_prefix(EXTERNAL_PREFIX) {
+        return Ok(Box::new(ExternalMoveGetter::launch(command_line)?));
+    }
+    match name {
+        "pvs" => Ok(Box::new(PVSMoveGetter::<CombinedRater>::new())),
+        "pvs-fixed" => Ok(Box::new(PVSMoveGetter::<CombinedRater>::new_fixed())),
+        "pvs-weighted" => Ok(Box::new(PVSMoveGetter::<WeightedEvaluator>::new())),
+        "pvs-learned" => Ok(Box::new(PVSMoveGetter::<LearnedEvaluator>::new())),
+        "mcts" => Ok(Box::new(MctsMoveGetter::<CombinedRater>::new(LeafEvaluation::RandomPlayout))),
+        "mcts-guided" => Ok(Box::new(MctsMoveGetter::<CombinedRater>::new(LeafEvaluation::GuidedPlayout))),
+        "mcts-rated" => Ok(Box::new(MctsMoveGetter::<CombinedRater>::new(LeafEvaluation::Heuristic))),
+        "random" => Ok(Box::new(RandomGetter::new())),
+        other => anyhow::bail!("Unknown engine '{}', expected one of {:?} or {}COMMAND", other, MOVE_GETTER_NAMES, EXTERNAL_PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_listed_move_getters_can_be_created() {
+        for name in MOVE_GETTER_NAMES {
+            assert!(create_move_getter(name).is_ok());
+        }
+    }
+
+    #[test]
+    fn creating_unknown_move_getter_fails() {
+        assert!(create_move_getter("stockfish").is_err());
+        assert!(create_move_getter("external:rostware23-engine-that-does-not-exist").is_err());
+        assert!(is_move_getter_name("external:java -jar weichware23.jar engine"));
+        assert!(!is_move_getter_name("external: "));
+    }
+}
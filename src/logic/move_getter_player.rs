@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+use rostware23_lib::client::Player;
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::state::State;
+
+use super::MoveGetter;
+use super::time_measurer::TimeMeasurer;
+
+pub struct MoveGetterPlayer<Getter: MoveGetter> {
+    move_getter: Getter
+}
+
+impl<Getter: MoveGetter> MoveGetterPlayer<Getter> {
+    pub fn new(move_getter: Getter) -> Self {
+        Self { move_getter }
+    }
+}
+
+impl<Getter: MoveGetter> Player for MoveGetterPlayer<Getter> {
+    fn choose_move(&mut self, state: &State, deadline: Instant) -> anyhow::Result<Move> {
+        let time_measurer = TimeMeasurer::until(deadline);
+        let report = self.move_getter.search(state, &time_measurer)?;
+        println!("{}", report);
+        Ok(report.best_move)
+    }
+}
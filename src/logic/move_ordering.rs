@@ -0,0 +1,144 @@
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::state::State;
+
+const KILLERS_PER_PLY: usize = 2;
+const FIELD_COUNT: usize = 64;
+// Placements have no origin field, so they get their own row in the history table
+const PLACEMENT_ROW: usize = FIELD_COUNT;
+
+const TRANSPOSITION_MOVE_PRIORITY: u64 = u64::MAX;
+const KILLER_PRIORITY: u64 = u64::MAX - KILLERS_PER_PLY as u64 - 1;
+
+fn history_index(searched_move: &Move) -> usize {
+    match searched_move {
+        Move::Place(to) => PLACEMENT_ROW * FIELD_COUNT + to.to_field_index() as usize,
+        Move::Normal { from, to } => from.to_field_index() as usize * FIELD_COUNT + to.to_field_index() as usize
+    }
+}
+
+// Orders moves by what the search has learned so far: the transposition table move first,
+// then the killer moves of the ply, then moves by how often they caused cutoffs.
+// Ties are broken by the fish on the target field.
+pub struct MoveOrdering {
+    enabled: bool,
+    killers: Vec<[Option<Move>; KILLERS_PER_PLY]>,
+    history: Vec<u64>
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            killers: vec![],
+            history: vec![0; (PLACEMENT_ROW + 1) * FIELD_COUNT]
+        }
+    }
+
+    // Keeps the moves in the order of the move generator, for comparisons.
+    #[cfg(test)]
+    pub fn unordered() -> Self {
+        Self { enabled: false, ..Self::new() }
+    }
+
+    fn priority(&self, state: &State, possible_move: &Move, transposition_move: Option<&Move>, ply: usize) -> u64 {
+        if transposition_move == Some(possible_move) {
+            return TRANSPOSITION_MOVE_PRIORITY;
+        }
+        if let Some(killers) = self.killers.get(ply) {
+            if let Some(killer_index) = killers.iter().position(|killer| killer.as_ref() == Some(possible_move)) {
+                return KILLER_PRIORITY - killer_index as u64;
+            }
+        }
+        let target_fish = state.board.get(possible_move.get_to())
+            .and_then(|field| field.get_fish_count())
+            .unwrap_or(0) as u64;
+        self.history[history_index(possible_move)] * 8 + target_fish
+    }
+
+    pub fn order(&self, state: &State, mut possible_moves: Vec<Move>, transposition_move: Option<&Move>, ply: usize) -> Vec<Move> {
+        if !self.enabled {
+            return possible_moves;
+        }
+        possible_moves.sort_by_cached_key(|possible_move| std::cmp::Reverse(self.priority(state, possible_move, transposition_move, ply)));
+        possible_moves
+    }
+
+    // Called for moves that failed high, deeper cutoffs count more.
+    pub fn record_cutoff(&mut self, cutoff_move: &Move, ply: usize, depth: i32) {
+        if !self.enabled {
+            return;
+        }
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, Default::default());
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0].as_ref() != Some(cutoff_move) {
+            killers[1] = killers[0].take();
+            killers[0] = Some(cutoff_move.clone());
+        }
+        let bonus = (depth.max(0) as u64 + 1).pow(2);
+        self.history[history_index(cutoff_move)] += bonus;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::board::Board;
+    use rostware23_lib::game::common::Coordinate;
+    use rostware23_lib::xml::common::Team;
+    use rostware23_lib::xml::state::FieldState;
+
+    use super::*;
+
+    fn create_state_with_three_moves() -> State {
+        let mut board = Board::empty();
+        board.perform_move(Move::Place(Coordinate::new(0, 0)), Team::One).unwrap();
+        board.set(Coordinate::new(2, 0), FieldState::Fish(1)).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(4, 0)), Team::One).unwrap();
+        board.set(Coordinate::new(6, 0), FieldState::Fish(4)).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(0, 2)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(2, 2)), Team::One).unwrap();
+        State::from_initial_board_with_start_team_one(board)
+    }
+
+    fn normal_move(from_x: u64, to_x: u64) -> Move {
+        Move::Normal { from: Coordinate::new(from_x, 0), to: Coordinate::new(to_x, 0) }
+    }
+
+    #[test]
+    fn without_search_information_moves_to_more_fish_come_first() {
+        let state = create_state_with_three_moves();
+        let ordered = MoveOrdering::new().order(&state, state.possible_moves().collect(), None, 0);
+        assert_eq!(normal_move(4, 6), ordered[0]);
+    }
+
+    #[test]
+    fn transposition_move_comes_before_killers_and_history() {
+        let state = create_state_with_three_moves();
+        let mut move_ordering = MoveOrdering::new();
+        move_ordering.record_cutoff(&normal_move(0, 2), 0, 5);
+        let ordered = move_ordering.order(&state, state.possible_moves().collect(), Some(&normal_move(4, 2)), 0);
+        assert_eq!(vec![normal_move(4, 2), normal_move(0, 2), normal_move(4, 6)], ordered);
+    }
+
+    #[test]
+    fn killer_moves_are_only_preferred_at_their_ply() {
+        let state = create_state_with_three_moves();
+        let mut move_ordering = MoveOrdering::new();
+        move_ordering.record_cutoff(&normal_move(4, 2), 3, 0);
+        move_ordering.record_cutoff(&normal_move(4, 6), 0, 3);
+        let ordered_at_killer_ply = move_ordering.order(&state, state.possible_moves().collect(), None, 3);
+        assert_eq!(vec![normal_move(4, 2), normal_move(4, 6), normal_move(0, 2)], ordered_at_killer_ply);
+        let ordered_at_other_ply = move_ordering.order(&state, state.possible_moves().collect(), None, 1);
+        assert_eq!(vec![normal_move(4, 6), normal_move(4, 2), normal_move(0, 2)], ordered_at_other_ply);
+    }
+
+    #[test]
+    fn unordered_move_ordering_keeps_generator_order() {
+        let state = create_state_with_three_moves();
+        let mut move_ordering = MoveOrdering::unordered();
+        move_ordering.record_cutoff(&normal_move(0, 2), 0, 5);
+        let possible_moves: Vec<Move> = state.possible_moves().collect();
+        assert_eq!(possible_moves.clone(), move_ordering.order(&state, possible_moves, Some(&normal_move(4, 2)), 0));
+    }
+}
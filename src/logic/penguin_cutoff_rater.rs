@@ -48,6 +48,11 @@ impl Rater for PenguinCutOffRater {
         Self::get_cutoff_rating_for_team(state, current_team.clone()) +
             Self::get_cutoff_rating_for_team(state, current_team.opponent())
     }
+
+    fn rate_per_team(state: &State) -> Option<(i32, i32)> {
+        let current_team = state.current_team();
+        Some((Self::get_cutoff_rating_for_team(state, current_team), Self::get_cutoff_rating_for_team(state, current_team.opponent())))
+    }
 }
 
 #[cfg(test)]
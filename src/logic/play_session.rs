@@ -0,0 +1,199 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context;
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::record::GameRecord;
+use rostware23_lib::game::state::State;
+use rostware23_lib::xml::common::Team;
+
+use super::MoveGetter;
+use super::board_view::labelled_board;
+use super::search_report::SearchReport;
+use super::time_measurer::TimeMeasurer;
+
+// A game of a human against an engine, the human's moves are given in move notation.
+pub struct PlaySession {
+    record: GameRecord,
+    human_team: Team,
+    engine: Box<dyn MoveGetter>,
+    think_time_in_millis: u128
+}
+
+impl PlaySession {
+    pub fn new(initial_state: State, human_team: Team, engine: Box<dyn MoveGetter>, engine_name: &str, think_time_in_millis: u128) -> Self {
+        let record = match human_team {
+            Team::One => GameRecord::new(initial_state).with_team_names("human", engine_name),
+            Team::Two => GameRecord::new(initial_state).with_team_names(engine_name, "human")
+        };
+        Self { record, human_team, engine, think_time_in_millis }
+    }
+
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    // The current state with a moveless player skipped
+    pub fn state(&self) -> anyhow::Result<State> {
+        let state = self.record.final_state()?;
+        if state.is_over() {
+            return Ok(state);
+        }
+        state.with_moveless_player_skipped()
+    }
+
+    pub fn is_human_turn(&self) -> anyhow::Result<bool> {
+        let state = self.state()?;
+        Ok(!state.is_over() && state.current_team() == self.human_team)
+    }
+
+    fn perform(&mut self, state: &State, performed_move: Move) -> anyhow::Result<()> {
+        state.with_move_performed(performed_move.clone())?;
+        self.record.moves.push(performed_move);
+        Ok(())
+    }
+
+    pub fn play_human_move(&mut self, notation: &str) -> anyhow::Result<Move> {
+        let state = self.state()?;
+        if !self.is_human_turn()? {
+            anyhow::bail!("It is not your turn");
+        }
+        let human_move = Move::from_notation(notation)?;
+        if !state.possible_moves().any(|possible_move| possible_move == human_move) {
+            let kind = match human_move {
+                Move::Place(_) if state.turn >= 8 => "All penguins are placed, move one with FROM-TO",
+                Move::Place(_) => "Penguins can only be placed on fields with one fish",
+                Move::Normal { .. } if state.turn < 8 => "Place your penguins first",
+                Move::Normal { .. } => "Penguins move in straight lines without passing holes or penguins"
+            };
+            anyhow::bail!("{} is not possible. {}, type 'moves' to list all possible moves", notation, kind);
+        }
+        self.perform(&state, human_move.clone())?;
+        Ok(human_move)
+    }
+
+    fn search(&self, state: &State) -> anyhow::Result<SearchReport> {
+        self.engine.search(state, &TimeMeasurer::new(self.think_time_in_millis))
+    }
+
+    pub fn play_engine_move(&mut self) -> anyhow::Result<SearchReport> {
+        let state = self.state()?;
+        if state.is_over() || state.current_team() == self.human_team {
+            anyhow::bail!("It is not the engine's turn");
+        }
+        let report = self.search(&state)?;
+        self.perform(&state, report.best_move.clone())?;
+        Ok(report)
+    }
+
+    pub fn hint(&self) -> anyhow::Result<SearchReport> {
+        if !self.is_human_turn()? {
+            anyhow::bail!("Hints are only given on your turn");
+        }
+        self.search(&self.state()?)
+    }
+
+    // Takes back the engine's replies and the last move of the human, returns the amount of moves taken back.
+    pub fn undo(&mut self) -> anyhow::Result<usize> {
+        let states = self.record.states()?;
+        let last_human_move = (0..self.record.moves.len()).rev()
+            .find(|index| states[*index].current_team() == self.human_team)
+            .context("There is no move of yours to undo")?;
+        let undone = self.record.moves.len() - last_human_move;
+        self.record.moves.truncate(last_human_move);
+        Ok(undone)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        self.record.save(path)
+    }
+}
+
+impl fmt::Display for PlaySession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state().map_err(|_| fmt::Error)?;
+        writeln!(f, "{}", labelled_board(&state.board))?;
+        for team in [Team::One, Team::Two] {
+            let symbol = if team == Team::One { 'G' } else { 'P' };
+            writeln!(f, "{} {}: {} fish", symbol, self.record.team_name(team), state.score_of_team(team))?;
+        }
+        match state.get_result() {
+            Ok(result) if state.is_over() => match result.winner {
+                Some(winner) => write!(f, "Game over, {} wins", self.record.team_name(winner)),
+                None => write!(f, "Game over, draw")
+            },
+            _ => write!(f, "Turn {}, {} to move", state.turn + 1, self.record.team_name(state.current_team()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::state_generator::create_board_from_seed;
+
+    use super::*;
+    use crate::logic::random_getter::RandomGetter;
+
+    fn create_session(human_team: Team) -> PlaySession {
+        let state = State::from_initial_board_with_start_team_one(create_board_from_seed(6));
+        PlaySession::new(state, human_team, Box::new(RandomGetter::new()), "random", 10)
+    }
+
+    fn first_possible_move(session: &PlaySession) -> String {
+        session.state().unwrap().possible_moves().next().unwrap().to_notation()
+    }
+
+    #[test]
+    fn human_and_engine_take_turns() {
+        let mut session = create_session(Team::Two);
+        assert!(!session.is_human_turn().unwrap());
+        assert!(session.play_human_move(&first_possible_move(&session)).is_err());
+        session.play_engine_move().unwrap();
+        assert!(session.is_human_turn().unwrap());
+        assert!(session.play_engine_move().is_err());
+        let human_move = session.play_human_move(&first_possible_move(&session)).unwrap();
+        assert_eq!(vec![human_move], session.record().moves[1..]);
+        assert_eq!("human", session.record().team_two);
+        assert!(session.to_string().contains("random to move"));
+    }
+
+    #[test]
+    fn invalid_moves_are_rejected() {
+        let mut session = create_session(Team::One);
+        assert!(session.play_human_move("z9").is_err());
+        assert!(session.play_human_move("a1-b2").is_err());
+        let occupied = first_possible_move(&session);
+        session.play_human_move(&occupied).unwrap();
+        session.play_engine_move().unwrap();
+        let error = session.play_human_move(&occupied).unwrap_err();
+        assert!(error.to_string().contains("is not possible"));
+        assert_eq!(2, session.record().moves.len());
+    }
+
+    #[test]
+    fn undo_takes_back_engine_reply_and_own_move() {
+        let mut session = create_session(Team::One);
+        assert!(session.undo().is_err());
+        session.play_human_move(&first_possible_move(&session)).unwrap();
+        session.play_engine_move().unwrap();
+        assert_eq!(2, session.undo().unwrap());
+        assert!(session.record().moves.is_empty());
+        assert!(session.is_human_turn().unwrap());
+    }
+
+    #[test]
+    fn games_can_be_played_to_the_end_with_hints() {
+        let mut session = create_session(Team::One);
+        while !session.state().unwrap().is_over() {
+            if session.is_human_turn().unwrap() {
+                let hint = session.hint().unwrap();
+                session.play_human_move(&hint.best_move.to_notation()).unwrap();
+            } else {
+                session.play_engine_move().unwrap();
+            }
+        }
+        assert!(session.hint().is_err());
+        assert!(session.record().result().is_ok());
+        assert!(session.to_string().contains("Game over"));
+    }
+}
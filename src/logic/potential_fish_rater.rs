@@ -7,23 +7,28 @@ use super::Rater;
 
 pub struct PotentialFishRater {}
 
-impl Rater for PotentialFishRater {
-    fn rate(state: &State) -> i32 {
+impl PotentialFishRater {
+    fn potential_fish_of_team(state: &State, team: Team) -> i32 {
         let mut result: i32 = 0;
-        let own_possible_moves = PossibleMovesIterator::make_normal_moves_iterator_for_team(state.clone(), state.current_team());
-        for current_move in own_possible_moves {
+        let possible_moves = PossibleMovesIterator::make_normal_moves_iterator_for_team(state.clone(), team);
+        for current_move in possible_moves {
             let target_fish = state.board.get(current_move.get_to()).unwrap().get_fish_count().unwrap() as i32;
             result += target_fish;
         }
-        let opponent_possible_moves = PossibleMovesIterator::make_normal_moves_iterator_for_team(state.clone(), state.current_team().opponent());
-        for current_move in opponent_possible_moves {
-            let target_fish = state.board.get(current_move.get_to()).unwrap().get_fish_count().unwrap() as i32;
-            result -= target_fish;
-        }
         result
     }
 }
 
+impl Rater for PotentialFishRater {
+    fn rate(state: &State) -> i32 {
+        Self::potential_fish_of_team(state, state.current_team()) - Self::potential_fish_of_team(state, state.current_team().opponent())
+    }
+
+    fn rate_per_team(state: &State) -> Option<(i32, i32)> {
+        Some((Self::potential_fish_of_team(state, state.current_team()), -Self::potential_fish_of_team(state, state.current_team().opponent())))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
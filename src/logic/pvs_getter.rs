@@ -1,19 +1,21 @@
-use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Instant;
 
 use anyhow::Context;
 use rostware23_lib::game::common::{BOARD_WIDTH, BOARD_HEIGHT};
 use rostware23_lib::game::moves::Move;
 use rostware23_lib::game::state::State;
-use crate::logic::selective_transposition_table::SelectiveTranspositionTable;
-use crate::logic::simple_transposition_table::SimpleTranspositionTable;
-use crate::logic::state_selector::{AnyStateSelector, StateSelector};
-use crate::logic::transposition_table::TranspositionTable;
+use crate::logic::hash_transposition_table::{HashTranspositionTable, DEFAULT_SIZE_IN_MEGABYTES};
+use crate::logic::transposition_table::{Bound, TranspositionEntry};
 
 use super::MoveGetter;
-use super::fish_difference_rater::FishDifferenceRater;
-use super::ordered_move_generator::OrderedMoveGenerator;
+use super::search_report::{IterationReport, SearchReport};
+use super::move_ordering::MoveOrdering;
 use super::time_measurer::TimeMeasurer;
-use super::Rater;
+use super::Evaluator;
+use super::evaluation_trace::PrincipalVariationTrace;
 
 const INITIAL_LOWER_BOUND: i32 = -1000000;
 const INITIAL_UPPER_BOUND: i32 = -INITIAL_LOWER_BOUND;
@@ -23,71 +25,274 @@ const WIDENING_FACTOR: i32 = 3;
 
 const MAX_DEPTH: i32 = BOARD_WIDTH as i32 * BOARD_HEIGHT as i32;
 
+// Finished games are rated far beyond any heuristic rating, a win found in fewer moves is rated higher.
+const WIN_RATING: i32 = 100000;
+// Ratings of at least this size can only result from a finished game
+const PROVEN_RATING: i32 = WIN_RATING / 2;
+
 fn is_in_search_window(value: i32, lower_bound: i32, upper_bound: i32) -> bool {
     lower_bound < value && value < upper_bound
 }
 
+fn is_proven(rating: i32) -> bool {
+    rating.abs() >= PROVEN_RATING
+}
+
+// Rates a finished game from the perspective of the current team.
+fn rate_finished_game(game_state: &State, ply: usize) -> anyhow::Result<i32> {
+    let team = game_state.current_team();
+    let fish_margin = game_state.score_of_team(team) as i32 - game_state.score_of_team(team.opponent()) as i32;
+    let distance_adjusted_win_rating = WIN_RATING - ply as i32;
+    Ok(match game_state.get_result()?.winner {
+        Some(winner) if winner == team => distance_adjusted_win_rating + fish_margin,
+        Some(_) => -distance_adjusted_win_rating + fish_margin,
+        None => 0
+    })
+}
+
+// Proven ratings in the transposition table count the distance from the stored state instead of the root,
+// as the same state can be reached at different plies.
+fn rating_for_transposition_table(rating: i32, ply: usize) -> i32 {
+    match rating {
+        rating if rating >= PROVEN_RATING => rating + ply as i32,
+        rating if rating <= -PROVEN_RATING => rating - ply as i32,
+        rating => rating
+    }
+}
+
+fn rating_from_transposition_table(rating: i32, ply: usize) -> i32 {
+    match rating {
+        rating if rating >= PROVEN_RATING => rating - ply as i32,
+        rating if rating <= -PROVEN_RATING => rating + ply as i32,
+        rating => rating
+    }
+}
+
+#[derive(Clone)]
 struct PVSResult {
     best_move: Option<Move>,
-    rating: i32
+    rating: i32,
+    principal_variation: Vec<Move>
 }
 
-pub struct PVSMoveGetter<Heuristic: Rater> {
-    phantom: PhantomData<Heuristic>,
-    fixed_depth: bool
+impl PVSResult {
+    fn leaf(rating: i32) -> Self {
+        Self {
+            best_move: None,
+            rating,
+            principal_variation: vec![]
+        }
+    }
 }
 
-impl<Heuristic: Rater> PVSMoveGetter<Heuristic> {
+// State shared by all nodes of one search iteration.
+struct SearchContext<'search> {
+    time_measurer: &'search TimeMeasurer,
+    transposition_table: &'search HashTranspositionTable,
+    evaluator: &'search dyn Evaluator,
+    // Set by the main thread to end the search of the helper threads
+    stop: Option<&'search AtomicBool>,
+    // Kept across the iterations of one search
+    move_ordering: MoveOrdering,
+    // Distance to the root of the currently searched node
+    ply: usize,
+    // Best root result of the current window whose search has finished,
+    // only set once the first root move has been searched completely
+    finished_root_result: Option<PVSResult>,
+    node_limit: Option<u64>,
+    nodes: u64,
+    transposition_hits: u64
+}
+
+impl<'search> SearchContext<'search> {
+    fn new(time_measurer: &'search TimeMeasurer, transposition_table: &'search HashTranspositionTable, evaluator: &'search dyn Evaluator) -> Self {
+        Self {
+            time_measurer,
+            transposition_table,
+            evaluator,
+            stop: None,
+            move_ordering: MoveOrdering::new(),
+            ply: 0,
+            finished_root_result: None,
+            node_limit: None,
+            nodes: 0,
+            transposition_hits: 0
+        }
+    }
+
+    fn with_stop(mut self, stop: &'search AtomicBool) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    fn with_move_ordering(mut self, move_ordering: MoveOrdering) -> Self {
+        self.move_ordering = move_ordering;
+        self
+    }
+
+    // Aborts the search deterministically, unlike the time limit.
+    #[cfg(test)]
+    fn with_node_limit(mut self, node_limit: u64) -> Self {
+        self.node_limit = Some(node_limit);
+        self
+    }
+
+    fn has_time_left(&self) -> bool {
+        let stopped = self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed));
+        let out_of_nodes = self.node_limit.is_some_and(|node_limit| self.nodes >= node_limit);
+        !stopped && !out_of_nodes && self.time_measurer.has_time_left()
+    }
+}
+
+pub struct PVSMoveGetter<Heuristic: Evaluator> {
+    evaluator: Heuristic,
+    fixed_depth: bool,
+    max_depth: i32,
+    threads: usize,
+    trace_evaluation: bool,
+    // Kept across iterations and turns, entries are only valid for the same heuristic
+    transposition_table: HashTranspositionTable
+}
+
+impl<Heuristic: Evaluator + Default> PVSMoveGetter<Heuristic> {
     pub fn new() -> Self {
-        Self {phantom: PhantomData, fixed_depth: false}
+        Self::with_evaluator(Heuristic::default())
     }
 
     pub fn new_fixed() -> Self {
-        Self {phantom: PhantomData, fixed_depth: true}
+        Self {fixed_depth: true, ..Self::new()}
     }
+}
 
-    fn pvs(game_state: State, depth: i32, mut lower_bound: i32, upper_bound: i32, time_measurer: &TimeMeasurer, transposition_table: &mut SelectiveTranspositionTable<SimpleTranspositionTable, AnyStateSelector>) -> anyhow::Result<PVSResult> {
-        if transposition_table.contains(&game_state) {
-            return Ok(PVSResult {
-                best_move: None,
-                rating: transposition_table.get(&game_state)?
-            })
+impl<Heuristic: Evaluator> PVSMoveGetter<Heuristic> {
+    pub fn with_evaluator(evaluator: Heuristic) -> Self {
+        Self {
+            evaluator,
+            fixed_depth: false,
+            max_depth: MAX_DEPTH,
+            threads: 1,
+            trace_evaluation: false,
+            transposition_table: HashTranspositionTable::with_size_in_megabytes(DEFAULT_SIZE_IN_MEGABYTES)
         }
-        if depth < 0 || game_state.is_over() || !time_measurer.has_time_left() {
-            let rating = Heuristic::rate(&game_state);
-            transposition_table.add(game_state, rating);
-            return Ok(PVSResult {
-                best_move: None,
-                rating
-            });
+    }
+
+    pub fn with_transposition_table_size_in_megabytes(mut self, megabytes: usize) -> Self {
+        self.transposition_table = HashTranspositionTable::with_size_in_megabytes(megabytes);
+        self
+    }
+
+    // Stops deepening after the given depth instead of searching until the time is over.
+    pub fn with_max_depth(mut self, depth: i32) -> Self {
+        self.max_depth = depth.clamp(1, MAX_DEPTH);
+        self
+    }
+
+    // Helper threads search the same root with the shared transposition table,
+    // only the result of the main thread is used.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    // Adds the evaluation of the root and the principal variation leaf per rating component to the search report.
+    pub fn with_evaluation_trace(mut self) -> Self {
+        self.trace_evaluation = true;
+        self
+    }
+
+    fn finish_report(&self, state: &State, report: &mut SearchReport, start: Instant) -> anyhow::Result<()> {
+        if self.trace_evaluation {
+            report.evaluation_trace = Some(PrincipalVariationTrace::of(&self.evaluator, state, &report.principal_variation)?);
+        }
+        report.time_used_millis = start.elapsed().as_millis();
+        Ok(())
+    }
+
+    // Returns None if the search was aborted, the ratings of unfinished subtrees are never returned.
+    fn pvs(game_state: State, depth: i32, mut lower_bound: i32, upper_bound: i32, context: &mut SearchContext) -> anyhow::Result<Option<PVSResult>> {
+        context.nodes += 1;
+        let mut transposition_move = None;
+        if let Some(mut entry) = context.transposition_table.lookup(&game_state) {
+            entry.rating = rating_from_transposition_table(entry.rating, context.ply);
+            if let Some(rating) = entry.usable_rating(depth, lower_bound, upper_bound) {
+                context.transposition_hits += 1;
+                return Ok(Some(PVSResult {
+                    principal_variation: entry.best_move.iter().cloned().collect(),
+                    best_move: entry.best_move,
+                    rating
+                }));
+            }
+            transposition_move = entry.best_move;
         }
-        let mut possible_moves = game_state.possible_moves_by_move_generator::<OrderedMoveGenerator<FishDifferenceRater>>();
+        if game_state.is_over() {
+            let rating = rate_finished_game(&game_state, context.ply)?;
+            // The rating of a finished game can't change by searching deeper
+            let entry = TranspositionEntry::new(rating_for_transposition_table(rating, context.ply), MAX_DEPTH, Bound::Exact, None);
+            context.transposition_table.store(&game_state, entry);
+            return Ok(Some(PVSResult::leaf(rating)));
+        }
+        if depth < 0 {
+            let rating = context.evaluator.evaluate(&game_state);
+            context.transposition_table.store(&game_state, TranspositionEntry::new(rating, depth, Bound::Exact, None));
+            return Ok(Some(PVSResult::leaf(rating)));
+        }
+        if !context.has_time_left() {
+            return Ok(None);
+        }
+        let original_lower_bound = lower_bound;
+        let ordered_moves = context.move_ordering.order(&game_state, game_state.possible_moves().collect(), transposition_move.as_ref(), context.ply);
+        let mut possible_moves = ordered_moves.into_iter();
         let mut best_move = possible_moves.next();
         let mut best_score;
+        let mut principal_variation;
         match best_move.clone() {
             None => {
-                best_score = -Self::pvs(game_state.with_moveless_player_skipped()?, depth, -upper_bound, -lower_bound, time_measurer, transposition_table)?.rating;
+                let Some(result) = Self::search_child(game_state.with_moveless_player_skipped()?, depth, -upper_bound, -lower_bound, context)? else {
+                    return Ok(None);
+                };
+                best_score = -result.rating;
+                principal_variation = result.principal_variation;
                 if best_score > lower_bound && best_score < upper_bound {
                     lower_bound = best_score;
                 }
             },
             Some(first_move) => {
                 let next_game_state = game_state.with_move_performed(first_move.clone())?;
-                best_score = -Self::pvs(next_game_state, depth - 1, -upper_bound, -lower_bound, time_measurer, transposition_table)?.rating;
+                let Some(result) = Self::search_child(next_game_state, depth - 1, -upper_bound, -lower_bound, context)? else {
+                    return Ok(None);
+                };
+                best_score = -result.rating;
+                principal_variation = vec![first_move];
+                principal_variation.extend(result.principal_variation);
                 if best_score > lower_bound && best_score < upper_bound {
                     lower_bound = best_score;
+                    if context.ply == 0 {
+                        context.finished_root_result = Some(PVSResult { best_move: best_move.clone(), rating: best_score, principal_variation: principal_variation.clone() });
+                    }
                 }
             }
         }
 
         for current_move in possible_moves {
+            if best_score >= upper_bound {
+                break;
+            }
             let next_game_state = game_state.with_move_performed(current_move.clone())?;
 
             // Zero-window search
-            let mut current_score: i32 = -Self::pvs(next_game_state.clone(), depth - 1, -lower_bound - 1, -lower_bound, time_measurer, transposition_table)?.rating;
+            let Some(mut current_result) = Self::search_child(next_game_state.clone(), depth - 1, -lower_bound - 1, -lower_bound, context)? else {
+                return Ok(None);
+            };
+            let mut current_score: i32 = -current_result.rating;
+            let mut is_exact = false;
             if current_score > lower_bound && current_score < upper_bound {
                 // Detailed search if zero-window search passes
-                current_score = -Self::pvs(next_game_state, depth - 1, -upper_bound, -lower_bound, time_measurer, transposition_table)?.rating;
+                let Some(detailed_result) = Self::search_child(next_game_state, depth - 1, -upper_bound, -lower_bound, context)? else {
+                    return Ok(None);
+                };
+                current_result = detailed_result;
+                current_score = -current_result.rating;
+                is_exact = is_in_search_window(current_score, lower_bound, upper_bound);
                 if current_score > lower_bound {
                     lower_bound = current_score;
                 }
@@ -96,89 +301,211 @@ impl<Heuristic: Rater> PVSMoveGetter<Heuristic> {
             if current_score > best_score {
                 best_move = Some(current_move.clone());
                 best_score = current_score;
-                if current_score >= upper_bound {
-                    break;
+                principal_variation = vec![current_move];
+                principal_variation.extend(current_result.principal_variation);
+                if context.ply == 0 && is_exact && context.finished_root_result.is_some() {
+                    context.finished_root_result = Some(PVSResult { best_move: best_move.clone(), rating: best_score, principal_variation: principal_variation.clone() });
                 }
             }
         }
-        if best_score >= lower_bound && best_score < upper_bound {
-            transposition_table.add(game_state, best_score);
+        if best_score >= upper_bound {
+            if let Some(cutoff_move) = &best_move {
+                context.move_ordering.record_cutoff(cutoff_move, context.ply, depth);
+            }
         }
-        Ok(PVSResult {
+        let mut entry = TranspositionEntry::from_search(best_score, depth, original_lower_bound, upper_bound, best_move.clone());
+        entry.rating = rating_for_transposition_table(entry.rating, context.ply);
+        context.transposition_table.store(&game_state, entry);
+        Ok(Some(PVSResult {
             best_move,
-            rating: best_score
-        })
+            rating: best_score,
+            principal_variation
+        }))
     }
 
-    fn get_move_for_depth(&self, state: &State, depth: i32, last_rating: i32, time_measurer: &TimeMeasurer) -> anyhow::Result<PVSResult> {
+    fn search_child(game_state: State, depth: i32, lower_bound: i32, upper_bound: i32, context: &mut SearchContext) -> anyhow::Result<Option<PVSResult>> {
+        context.ply += 1;
+        let result = Self::pvs(game_state, depth, lower_bound, upper_bound, context);
+        context.ply -= 1;
+        result
+    }
+
+    // Searches every root move with the full window, best first. Moves not searched in time are left out.
+    pub fn score_root_moves(&self, state: &State, depth: i32, time_measurer: &TimeMeasurer) -> anyhow::Result<Vec<(Move, i32)>> {
+        let mut context = SearchContext::new(time_measurer, &self.transposition_table, &self.evaluator);
+        let mut scored_moves = vec![];
+        for root_move in state.possible_moves() {
+            let next_game_state = state.with_move_performed(root_move.clone())?;
+            let Some(result) = Self::search_child(next_game_state, depth - 1, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context)? else {
+                break;
+            };
+            scored_moves.push((root_move, -result.rating));
+        }
+        scored_moves.sort_by_key(|(_, score)| -score);
+        Ok(scored_moves)
+    }
+
+    #[cfg(test)]
+    fn get_move_for_depth(&self, state: &State, depth: i32, last_rating: i32, time_measurer: &TimeMeasurer) -> anyhow::Result<IterationReport> {
+        let mut context = SearchContext::new(time_measurer, &self.transposition_table, &self.evaluator);
+        Self::search_depth(state, depth, last_rating, &mut context)
+    }
+
+    fn search_depth(state: &State, depth: i32, last_rating: i32, context: &mut SearchContext) -> anyhow::Result<IterationReport> {
+        let start = Instant::now();
         let mut offset_lower_bound = -INITIAL_OFFSET;
         let mut offset_upper_bound = INITIAL_OFFSET;
         let mut lower_bound = last_rating + offset_lower_bound;
         let mut upper_bound = last_rating + offset_upper_bound;
-        let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(depth);
-        while time_measurer.has_time_left() {
-            let current_result = Self::pvs(state.clone(), depth, lower_bound, upper_bound, time_measurer, &mut transposition_table)?;
+        let mut aspiration_re_searches = 0;
+        let mut iteration = IterationReport {
+            depth,
+            best_move: None,
+            score: i32::MIN,
+            principal_variation: vec![],
+            window: (lower_bound, upper_bound),
+            aspiration_re_searches: 0,
+            partial: false,
+            nodes: 0,
+            transposition_hits: 0,
+            elapsed_millis: 0
+        };
+        loop {
+            context.finished_root_result = None;
+            let Some(current_result) = Self::pvs(state.clone(), depth, lower_bound, upper_bound, context)? else {
+                // The first root move is the best move of the last iteration,
+                // so a finished result of this window is at least as good
+                if let Some(finished_result) = context.finished_root_result.take() {
+                    iteration.best_move = finished_result.best_move;
+                    iteration.score = finished_result.rating;
+                    iteration.principal_variation = finished_result.principal_variation;
+                    iteration.window = (lower_bound, upper_bound);
+                    iteration.partial = true;
+                }
+                break;
+            };
             let current_rating = current_result.rating;
             if is_in_search_window(current_rating, lower_bound, upper_bound) {
-                println!("Search Window (Depth = {}): [{} {}]", depth, lower_bound, upper_bound);
-                return Ok(current_result);
+                iteration.best_move = current_result.best_move;
+                iteration.score = current_rating;
+                iteration.principal_variation = current_result.principal_variation;
+                iteration.window = (lower_bound, upper_bound);
+                break;
             }
 
+            aspiration_re_searches += 1;
+            // Widening step by step would take many re-searches to reach the rating of a proven outcome
             if current_rating <= lower_bound {
                 upper_bound = lower_bound;
                 offset_lower_bound *= WIDENING_FACTOR;
-                lower_bound = last_rating + offset_lower_bound;
+                lower_bound = if is_proven(current_rating) { INITIAL_LOWER_BOUND } else { last_rating + offset_lower_bound };
             } else {
                 lower_bound = upper_bound;
                 offset_upper_bound *= WIDENING_FACTOR;
-                upper_bound = last_rating + offset_upper_bound;
+                upper_bound = if is_proven(current_rating) { INITIAL_UPPER_BOUND } else { last_rating + offset_upper_bound };
             }
         }
 
-        Ok(PVSResult {
-            best_move: None,
-            rating: i32::min_value()
-        })
+        iteration.aspiration_re_searches = aspiration_re_searches;
+        iteration.nodes = context.nodes;
+        iteration.transposition_hits = context.transposition_hits;
+        iteration.elapsed_millis = start.elapsed().as_millis();
+        Ok(iteration)
     }
-}
 
-impl<Heuristic: Rater> MoveGetter for PVSMoveGetter<Heuristic> {
-    fn get_move(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<Move> {
-        if !state.has_team_any_moves(state.current_team()) {
-            anyhow::bail!("MoveGetter invoked without possible moves!");
-        }
-        
-        if self.fixed_depth {
-            let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(1);
-            return Self::pvs(state.clone(), 1, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, time_measurer, &mut transposition_table).map(|result| result.best_move.unwrap());
-        }
-
-        let mut depth = 1; // Skipping 0 because the calculation time of 1 is insignificant
-        let mut best_move = Some(state.possible_moves().next().unwrap());
-        let mut last_ratings = [Heuristic::rate(state); 2];
-        while time_measurer.has_time_left() {
-            let depth_index = (depth % 2) as usize;
-            let current_result = self.get_move_for_depth(state, depth, last_ratings[depth_index], time_measurer)?;
-            last_ratings[depth_index] = current_result.rating;
-
-            if current_result.best_move.is_none() {
-                continue;
+    // Deepens the search through the given depths until the time is over or the search is stopped.
+    fn iterative_deepening(state: &State, depths: RangeInclusive<i32>, time_measurer: &TimeMeasurer, transposition_table: &HashTranspositionTable, evaluator: &dyn Evaluator, stop: &AtomicBool, report: &mut SearchReport) -> anyhow::Result<()> {
+        let mut depth = *depths.start();
+        let mut last_ratings = [report.score; 2];
+        let mut move_ordering = MoveOrdering::new();
+        loop {
+            let mut context = SearchContext::new(time_measurer, transposition_table, evaluator)
+                .with_stop(stop)
+                .with_move_ordering(move_ordering);
+            if !context.has_time_left() {
+                break;
             }
-
-            if !time_measurer.has_time_left() {
+            let depth_index = (depth % 2) as usize;
+            let iteration = Self::search_depth(state, depth, last_ratings[depth_index], &mut context)?;
+            move_ordering = context.move_ordering;
+            if iteration.best_move.is_none() {
                 break;
             }
 
-            if depth >= MAX_DEPTH {
+            // Searching deeper can't change a proven outcome
+            let is_final = iteration.partial || is_proven(iteration.score);
+            last_ratings[depth_index] = iteration.score;
+            report.add_iteration(iteration);
+            if is_final || depth >= *depths.end() {
                 break;
             }
+            depth += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<Heuristic: Evaluator + Sync> MoveGetter for PVSMoveGetter<Heuristic> {
+    fn get_move(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<Move> {
+        self.search(state, time_measurer).map(|report| report.best_move)
+    }
 
-            best_move = current_result.best_move;
-            depth = depth + 1;
+    fn search(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<SearchReport> {
+        let start = Instant::now();
+        let first_move = state.possible_moves().next().context("MoveGetter invoked without possible moves!")?;
+        let mut report = SearchReport::from_move(first_move, 0);
+        report.score = self.evaluator.evaluate(state);
+        self.transposition_table.start_new_generation();
+
+        if self.fixed_depth {
+            let depth = 1;
+            let mut context = SearchContext::new(time_measurer, &self.transposition_table, &self.evaluator);
+            let Some(result) = Self::pvs(state.clone(), depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context)? else {
+                report.time_used_millis = start.elapsed().as_millis();
+                return Ok(report);
+            };
+            report.add_iteration(IterationReport {
+                depth,
+                best_move: Some(result.best_move.context("No move found")?),
+                score: result.rating,
+                principal_variation: result.principal_variation,
+                window: (INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND),
+                aspiration_re_searches: 0,
+                partial: false,
+                nodes: context.nodes,
+                transposition_hits: context.transposition_hits,
+                elapsed_millis: start.elapsed().as_millis()
+            });
+            self.finish_report(state, &mut report, start)?;
+            return Ok(report);
         }
 
-        println!("Reached depth: {}", depth);
-        best_move.context("No move found")
+        // Skipping depth 0 because the calculation time of 1 is insignificant
+        let stop = AtomicBool::new(false);
+        let transposition_table = &self.transposition_table;
+        let evaluator = &self.evaluator;
+        let max_depth = self.max_depth;
+        thread::scope(|scope| -> anyhow::Result<()> {
+            let helpers: Vec<_> = (1..self.threads).map(|helper_index| {
+                // Every other helper searches one ply deeper than the main thread
+                let start_depth = 1 + (helper_index % 2) as i32;
+                let mut helper_report = report.clone();
+                let stop = &stop;
+                scope.spawn(move || Self::iterative_deepening(state, start_depth.min(max_depth)..=max_depth, time_measurer, transposition_table, evaluator, stop, &mut helper_report))
+            }).collect();
+            let main_result = Self::iterative_deepening(state, 1..=max_depth, time_measurer, transposition_table, evaluator, &stop, &mut report);
+            stop.store(true, Ordering::Relaxed);
+            main_result?;
+            for (helper_index, helper) in helpers.into_iter().enumerate() {
+                helper.join()
+                    .map_err(|_| anyhow::anyhow!("Helper search thread {} panicked", helper_index + 1))?
+                    .with_context(|| format!("Helper search thread {} failed", helper_index + 1))?;
+            }
+            Ok(())
+        })?;
+
+        self.finish_report(state, &mut report, start)?;
+        Ok(report)
     }
 }
 
@@ -186,11 +513,14 @@ impl<Heuristic: Rater> MoveGetter for PVSMoveGetter<Heuristic> {
 mod tests {
     use super::*;
 
+    use std::sync::Mutex;
+
     use rostware23_lib::xml::common::Team;
     use rostware23_lib::game::common::Coordinate;
     use rostware23_lib::game::board::*;
+    use rostware23_lib::game::state_generator::create_board_from_seed;
 
-    use crate::logic::battle::Battle;
+    use crate::logic::battle::{Battle, BattleOutcome, ParallelBattle};
     use crate::logic::bitset_penguin_restrictions::BitsetPenguinRestrictions;
     use crate::logic::board_parser::parse_board;
     use crate::logic::combined_rater::CombinedRater;
@@ -200,6 +530,7 @@ mod tests {
     use crate::logic::quadrant_occupation_rater::QuadrantOccupationRater;
     use crate::logic::random_getter::*;
     use crate::logic::restricted_reachable_fish_rater::RestrictedReachableFishRater;
+    use crate::logic::weighted_evaluator::WeightedEvaluator;
 
     #[test]
     fn given_game_state_with_option_of_either_one_or_two_fish_when_calculating_move_with_zero_depth_then_choose_more_fish() {
@@ -212,11 +543,16 @@ mod tests {
         board.perform_move(Move::Place(moving_penguin_coord.clone()), Team::One).unwrap();
         board.set(expected_target.clone(), FieldState::Fish(2)).unwrap();
         board.set(Coordinate::new(14, 0), FieldState::Fish(1)).unwrap();
+        // Team two is already stuck, so both moves end the game
+        for x in [0, 4, 8, 12] {
+            board.perform_move(Move::Place(Coordinate::new(x, 4)), Team::Two).unwrap();
+        }
         let game_state = State::from_initial_board_with_start_team_one(board);
         let expected_move = Move::Normal{from: moving_penguin_coord, to: expected_target};
         let time_measurer = TimeMeasurer::new_infinite();
-        let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(0);
-        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, 0, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &time_measurer, &mut transposition_table).unwrap();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, 0, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap();
         assert_eq!(expected_move, result_got.best_move.unwrap());
     }
 
@@ -235,8 +571,9 @@ mod tests {
         let game_state = State::from_initial_board_with_start_team_one(board);
         let expected_move = Move::Normal{from: moving_penguin_coord, to: expected_target};
         let time_measurer = TimeMeasurer::new_infinite();
-        let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(0);
-        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, 0, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &time_measurer, &mut transposition_table).unwrap();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, 0, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap();
         assert_eq!(expected_move, result_got.best_move.unwrap());
     }
 
@@ -271,8 +608,9 @@ mod tests {
         let game_state = create_higher_depth_test_game_state(moving_penguin_coord.clone(), expected_target.clone());
         let expected_move = Move::Normal{from: moving_penguin_coord, to: expected_target};
         let time_measurer = TimeMeasurer::new_infinite();
-        let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(2);
-        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, 2, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &time_measurer, &mut transposition_table).unwrap();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, 2, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap();
         assert_eq!(expected_move, result_got.best_move.unwrap());
     }
 
@@ -284,8 +622,9 @@ mod tests {
         let expected_move = Move::Normal{from: moving_penguin_coord, to: expected_target};
         let time_measurer = TimeMeasurer::new_infinite();
         let depth = 2;
-        let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(depth);
-        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, 3, 5, &time_measurer, &mut transposition_table).unwrap();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, 3, 5, &mut context).unwrap().unwrap();
         assert_eq!(expected_move, result_got.best_move.unwrap());
     }
 
@@ -296,8 +635,9 @@ mod tests {
         let game_state = create_higher_depth_test_game_state(moving_penguin_coord.clone(), expected_target.clone());
         let time_measurer = TimeMeasurer::new_infinite();
         let depth = 2;
-        let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(depth);
-        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, 0, 2, &time_measurer, &mut transposition_table).unwrap();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, 0, 2, &mut context).unwrap().unwrap();
         assert!(2 <= result_got.rating);
     }
 
@@ -312,13 +652,14 @@ mod tests {
         let game_state = State::from_initial_board_with_start_team_one(board);
         let time_measurer = TimeMeasurer::new_infinite();
         let depth = 2;
-        let mut transposition_table = SelectiveTranspositionTable::<SimpleTranspositionTable, AnyStateSelector>::create_for_depth(depth);
-        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &time_measurer, &mut transposition_table).unwrap();
-        assert_eq!(2, result_got.rating);
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        let result_got: PVSResult = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap();
+        // The game ends after the first move, won by two fish
+        assert_eq!(WIN_RATING - 1 + 2, result_got.rating);
     }
 
-    #[test]
-    fn early_game_cut_off_test() {
+    fn create_early_game_test_game_state() -> State {
         let board_string = "\
             = - 4 = 3 -   3\n\
              = - P 3 G = = -\n\
@@ -328,12 +669,379 @@ mod tests {
              = = = =     =  \n\
             - = P G G P - =\n\
              3   - 3 = 4 - =\n";
-        let board = parse_board(board_string);
-        println!("{}", &board);
-        let game_state = State::from_initial_board_with_start_team_one(board);
+        State::from_initial_board_with_start_team_one(parse_board(board_string))
+    }
+
+    #[test]
+    fn early_game_cut_off_test() {
+        let game_state = create_early_game_test_game_state();
+        println!("{}", &game_state.board);
         let pvs_move_getter = PVSMoveGetter::<CombinedRater>::new();
         let expected_move = Move::Normal{from: Coordinate::new(6, 6), to: Coordinate::new(4, 4) };
         let move_got = pvs_move_getter.get_move_for_depth(&game_state, 3, 0, &TimeMeasurer::Infinite).unwrap().best_move.unwrap();
         assert_eq!(expected_move, move_got)
     }
+
+    #[test]
+    fn search_report_of_fixed_depth_search_starts_principal_variation_with_best_move() {
+        let moving_penguin_coord = Coordinate::new(12, 0);
+        let expected_target = Coordinate::new(10, 0);
+        let game_state = create_higher_depth_test_game_state(moving_penguin_coord, expected_target);
+        let report = PVSMoveGetter::<FishDifferenceRater>::new_fixed().search(&game_state, &TimeMeasurer::Infinite).unwrap();
+        assert_eq!(1, report.completed_depth);
+        assert_eq!(1, report.iterations.len());
+        assert_eq!(Some(&report.best_move), report.principal_variation.first());
+        assert_eq!(report.nodes, report.iterations[0].nodes);
+        assert!(report.nodes > 1);
+    }
+
+    #[test]
+    fn search_report_traces_evaluation_of_root_and_principal_variation_leaf() {
+        let game_state = create_early_game_test_game_state();
+        let move_getter = PVSMoveGetter::with_evaluator(WeightedEvaluator::default()).with_evaluation_trace();
+        let report = move_getter.search(&game_state, &TimeMeasurer::new(100)).unwrap();
+        let trace = report.evaluation_trace.clone().unwrap();
+        assert_eq!(WeightedEvaluator::default().trace(&game_state), trace.root);
+        assert_eq!(report.principal_variation.len(), trace.leaf_ply);
+        assert!(report.to_string().contains("Principal variation leaf"));
+        assert!(PVSMoveGetter::<FishDifferenceRater>::new_fixed().search(&game_state, &TimeMeasurer::Infinite).unwrap().evaluation_trace.is_none());
+    }
+
+    #[test]
+    fn search_report_contains_history_of_every_completed_iteration() {
+        let moving_penguin_coord = Coordinate::new(12, 0);
+        let expected_target = Coordinate::new(10, 0);
+        let game_state = create_higher_depth_test_game_state(moving_penguin_coord.clone(), expected_target.clone());
+        let report = PVSMoveGetter::<FishDifferenceRater>::new().search(&game_state, &TimeMeasurer::new(200)).unwrap();
+        let depths: Vec<i32> = report.iterations.iter()
+            .filter(|iteration| !iteration.partial)
+            .map(|iteration| iteration.depth)
+            .collect();
+        let expected_depths: Vec<i32> = (1..=report.completed_depth).collect();
+        assert_eq!(expected_depths, depths);
+        assert!(report.iterations.iter().rev().skip(1).all(|iteration| !iteration.partial));
+        assert_eq!(Move::Normal{from: moving_penguin_coord, to: expected_target}, report.best_move);
+        assert_eq!(report.nodes, report.iterations.iter().map(|iteration| iteration.nodes).sum::<u64>());
+        let last_iteration = report.iterations.last().unwrap();
+        assert_eq!(last_iteration.score, report.score);
+        assert_eq!(last_iteration.principal_variation, report.principal_variation);
+    }
+
+    #[test]
+    fn search_stops_at_max_depth_and_root_moves_are_ranked_by_score() {
+        let game_state = create_early_game_test_game_state();
+        let move_getter = PVSMoveGetter::<FishDifferenceRater>::new().with_max_depth(2);
+        let report = move_getter.search(&game_state, &TimeMeasurer::Infinite).unwrap();
+        assert_eq!(2, report.completed_depth);
+        assert_eq!(2, report.iterations.len());
+        let scored_moves = move_getter.score_root_moves(&game_state, 2, &TimeMeasurer::Infinite).unwrap();
+        assert_eq!(game_state.possible_moves().count(), scored_moves.len());
+        assert!(scored_moves.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(report.score, scored_moves[0].1);
+    }
+
+    fn rate_higher_depth_test_game_state_with_fresh_table(game_state: &State, depth: i32) -> PVSResult {
+        let time_measurer = TimeMeasurer::new_infinite();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        PVSMoveGetter::<FishDifferenceRater>::pvs(game_state.clone(), depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap()
+    }
+
+    #[test]
+    fn shallow_transposition_entries_do_not_change_result_of_deeper_search() {
+        let game_state = create_higher_depth_test_game_state(Coordinate::new(12, 0), Coordinate::new(10, 0));
+        let depth = 2;
+        let expected = rate_higher_depth_test_game_state_with_fresh_table(&game_state, depth);
+
+        let time_measurer = TimeMeasurer::new_infinite();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        let bogus_entry = TranspositionEntry::new(999, 0, Bound::Exact, None);
+        context.transposition_table.store(&game_state, bogus_entry.clone());
+        for possible_move in game_state.possible_moves() {
+            let next_game_state = game_state.with_move_performed(possible_move).unwrap();
+            context.transposition_table.store(&next_game_state, bogus_entry.clone());
+        }
+        let result_got = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap();
+
+        assert_eq!(expected.rating, result_got.rating);
+        assert_eq!(expected.best_move, result_got.best_move);
+    }
+
+    #[test]
+    fn bound_transposition_entries_outside_of_window_do_not_change_result() {
+        let game_state = create_higher_depth_test_game_state(Coordinate::new(12, 0), Coordinate::new(10, 0));
+        let depth = 2;
+        let expected = rate_higher_depth_test_game_state_with_fresh_table(&game_state, depth);
+
+        let time_measurer = TimeMeasurer::new_infinite();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        context.transposition_table.store(&game_state, TranspositionEntry::new(-999, MAX_DEPTH, Bound::Lower, None));
+        let result_got = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap();
+
+        assert_eq!(expected.rating, result_got.rating);
+    }
+
+    #[test]
+    fn fail_soft_results_of_narrow_window_do_not_change_result_of_later_full_window_search() {
+        let game_state = create_higher_depth_test_game_state(Coordinate::new(12, 0), Coordinate::new(10, 0));
+        let depth = 2;
+        let expected = rate_higher_depth_test_game_state_with_fresh_table(&game_state, depth);
+
+        let time_measurer = TimeMeasurer::new_infinite();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+        PVSMoveGetter::<FishDifferenceRater>::pvs(game_state.clone(), depth, 0, 2, &mut context).unwrap().unwrap();
+        assert!(!context.transposition_table.is_empty());
+        let result_got = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap();
+
+        assert_eq!(expected.rating, result_got.rating);
+        assert_eq!(expected.best_move, result_got.best_move);
+    }
+
+    #[test]
+    fn transposition_table_is_kept_between_searches() {
+        let game_state = create_higher_depth_test_game_state(Coordinate::new(12, 0), Coordinate::new(10, 0));
+        let pvs_getter = PVSMoveGetter::<FishDifferenceRater>::new_fixed().with_transposition_table_size_in_megabytes(1);
+        let first_report = pvs_getter.search(&game_state, &TimeMeasurer::Infinite).unwrap();
+        let second_report = pvs_getter.search(&game_state, &TimeMeasurer::Infinite).unwrap();
+        assert_eq!(first_report.best_move, second_report.best_move);
+        assert_eq!(1, second_report.transposition_hits);
+        assert_eq!(1, second_report.nodes);
+    }
+
+    fn count_nodes_of_iterative_deepening(game_state: &State, max_depth: i32, mut move_ordering: MoveOrdering) -> (i32, u64) {
+        let time_measurer = TimeMeasurer::new_infinite();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(4);
+        let mut rating = 0;
+        let mut nodes = 0;
+        for depth in 1..=max_depth {
+            let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {}).with_move_ordering(move_ordering);
+            rating = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state.clone(), depth, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap().unwrap().rating;
+            nodes += context.nodes;
+            move_ordering = context.move_ordering;
+        }
+        (rating, nodes)
+    }
+
+    #[test]
+    fn move_ordering_searches_fewer_nodes_in_early_game_position() {
+        let game_state = create_early_game_test_game_state();
+        let (unordered_rating, unordered_nodes) = count_nodes_of_iterative_deepening(&game_state, 4, MoveOrdering::unordered());
+        let (ordered_rating, ordered_nodes) = count_nodes_of_iterative_deepening(&game_state, 4, MoveOrdering::new());
+        println!("Nodes without ordering: {}, with ordering: {}", unordered_nodes, ordered_nodes);
+        assert_eq!(unordered_rating, ordered_rating);
+        assert!(ordered_nodes < unordered_nodes);
+    }
+
+    #[test]
+    fn move_ordering_searches_fewer_nodes_in_mid_game_position() {
+        let board_string =
+            "4 3 3 =   = = 3\n\
+         - = P = = G - =\n\
+        - = G = - - P -\n\
+         = =     = - = -\n\
+        - = - =     = =\n\
+         - G - - = P = -\n\
+        = - - = = - = -\n\
+         3 = =   = 3 3 4\n";
+        let game_state = State::from_initial_board_with_start_team_one(parse_board(board_string));
+        let (unordered_rating, unordered_nodes) = count_nodes_of_iterative_deepening(&game_state, 5, MoveOrdering::unordered());
+        let (ordered_rating, ordered_nodes) = count_nodes_of_iterative_deepening(&game_state, 5, MoveOrdering::new());
+        println!("Nodes without ordering: {}, with ordering: {}", unordered_nodes, ordered_nodes);
+        assert_eq!(unordered_rating, ordered_rating);
+        assert!(ordered_nodes < unordered_nodes);
+    }
+
+    fn rate_root_moves_with_full_window(game_state: &State, depth: i32) -> Vec<(Move, i32)> {
+        game_state.possible_moves()
+            .map(|possible_move| {
+                let next_game_state = game_state.with_move_performed(possible_move.clone()).unwrap();
+                let rating = -rate_higher_depth_test_game_state_with_fresh_table(&next_game_state, depth - 1).rating;
+                (possible_move, rating)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn aborted_search_returns_nothing() {
+        let game_state = create_early_game_test_game_state();
+        let time_measurer = TimeMeasurer::new_infinite();
+        let transposition_table = HashTranspositionTable::with_size_in_megabytes(1);
+        let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {}).with_node_limit(10);
+        let result = PVSMoveGetter::<FishDifferenceRater>::pvs(game_state, 3, INITIAL_LOWER_BOUND, INITIAL_UPPER_BOUND, &mut context).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn iteration_aborted_at_any_node_never_returns_move_of_half_searched_window() {
+        let game_state = create_early_game_test_game_state();
+        let depth = 3;
+        let root_move_ratings = rate_root_moves_with_full_window(&game_state, depth);
+        let best_rating = root_move_ratings.iter().map(|(_, rating)| *rating).max().unwrap();
+        let time_measurer = TimeMeasurer::new_infinite();
+
+        let previous_transposition_table = HashTranspositionTable::with_size_in_megabytes(4);
+        let mut previous_context = SearchContext::new(&time_measurer, &previous_transposition_table, &FishDifferenceRater {});
+        let previous_iteration = PVSMoveGetter::<FishDifferenceRater>::search_depth(&game_state, depth - 1, 0, &mut previous_context).unwrap();
+        let previous_best_move = previous_iteration.best_move.unwrap();
+        let previous_best_move_rating = root_move_ratings.iter()
+            .find(|(root_move, _)| *root_move == previous_best_move)
+            .unwrap().1;
+
+        let mut full_context = SearchContext::new(&time_measurer, &previous_transposition_table, &FishDifferenceRater {});
+        let node_count = PVSMoveGetter::<FishDifferenceRater>::search_depth(&game_state, depth, 0, &mut full_context).unwrap().nodes;
+        let mut partial_iterations = 0;
+        for node_limit in (1..=node_count).step_by((node_count / 150).max(1) as usize) {
+            let transposition_table = HashTranspositionTable::with_size_in_megabytes(4);
+            let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {});
+            PVSMoveGetter::<FishDifferenceRater>::search_depth(&game_state, depth - 1, 0, &mut context).unwrap();
+            let mut context = SearchContext::new(&time_measurer, &transposition_table, &FishDifferenceRater {})
+                .with_move_ordering(context.move_ordering)
+                .with_node_limit(node_limit);
+            let iteration = PVSMoveGetter::<FishDifferenceRater>::search_depth(&game_state, depth, 0, &mut context).unwrap();
+            let Some(best_move) = iteration.best_move else {
+                continue;
+            };
+            let exact_rating = root_move_ratings.iter()
+                .find(|(root_move, _)| *root_move == best_move)
+                .unwrap().1;
+            assert_eq!(exact_rating, iteration.score, "Wrong rating with node limit {}", node_limit);
+            if iteration.partial {
+                partial_iterations += 1;
+                assert!(iteration.score >= previous_best_move_rating, "Worse move with node limit {}", node_limit);
+            } else {
+                assert_eq!(best_rating, iteration.score);
+            }
+        }
+        println!("{} of the aborted iterations returned a partial result", partial_iterations);
+        assert!(partial_iterations > 0);
+    }
+
+    #[test]
+    fn finished_games_are_rated_by_result_and_distance_from_perspective_of_current_team() {
+        let mut game_state = State::from_initial_board_with_start_team_one(Board::empty());
+        game_state.team_one_fish = 5;
+        game_state.team_two_fish = 2;
+        assert_eq!(WIN_RATING - 3 + 3, rate_finished_game(&game_state, 3).unwrap());
+        game_state.turn = 1;
+        assert_eq!(-(WIN_RATING - 3) - 3, rate_finished_game(&game_state, 3).unwrap());
+        game_state.team_two_fish = 5;
+        assert_eq!(0, rate_finished_game(&game_state, 3).unwrap());
+    }
+
+    #[test]
+    fn proven_ratings_are_stored_relative_to_stored_state() {
+        let rating_at_ply_two = WIN_RATING - 5;
+        let stored_rating = rating_for_transposition_table(rating_at_ply_two, 2);
+        assert_eq!(WIN_RATING - 3, stored_rating);
+        assert_eq!(WIN_RATING - 4, rating_from_transposition_table(stored_rating, 1));
+        assert_eq!(-(WIN_RATING - 4), rating_from_transposition_table(rating_for_transposition_table(-(WIN_RATING - 5), 2), 1));
+        assert_eq!(17, rating_from_transposition_table(rating_for_transposition_table(17, 2), 1));
+    }
+
+    fn create_faster_or_bigger_win_game_state() -> State {
+        let mut board = Board::empty();
+        for x in [0, 4, 12] {
+            board.perform_move(Move::Place(Coordinate::new(x, 6)), Team::One).unwrap();
+        }
+        board.perform_move(Move::Place(Coordinate::new(8, 0)), Team::One).unwrap();
+        // Team two is already stuck
+        for x in [0, 4, 8, 12] {
+            board.perform_move(Move::Place(Coordinate::new(x, 3)), Team::Two).unwrap();
+        }
+        for x in [6, 10, 12] {
+            board.set(Coordinate::new(x, 0), FieldState::Fish(1)).unwrap();
+        }
+        State::from_initial_board_with_start_team_one(board)
+    }
+
+    #[test]
+    fn faster_win_is_preferred_over_later_win_with_more_fish() {
+        let game_state = create_faster_or_bigger_win_game_state();
+        let result = rate_higher_depth_test_game_state_with_fresh_table(&game_state, 4);
+        assert_eq!(Some(Move::Normal { from: Coordinate::new(8, 0), to: Coordinate::new(6, 0) }), result.best_move);
+        assert_eq!(WIN_RATING - 1 + 1, result.rating);
+    }
+
+    #[test]
+    fn iterative_deepening_stops_once_outcome_is_proven() {
+        let game_state = create_faster_or_bigger_win_game_state();
+        let report = PVSMoveGetter::<FishDifferenceRater>::new().with_transposition_table_size_in_megabytes(1)
+            .search(&game_state, &TimeMeasurer::Infinite).unwrap();
+        assert!(is_proven(report.score));
+        assert!(report.completed_depth < 4);
+        assert_eq!(Move::Normal { from: Coordinate::new(8, 0), to: Coordinate::new(6, 0) }, report.best_move);
+    }
+
+    #[test]
+    fn multi_threaded_search_returns_possible_move() {
+        let game_state = create_higher_depth_test_game_state(Coordinate::new(12, 0), Coordinate::new(10, 0));
+        let pvs_getter = PVSMoveGetter::<FishDifferenceRater>::new().with_transposition_table_size_in_megabytes(1).with_threads(3);
+        let report = pvs_getter.search(&game_state, &TimeMeasurer::new(100)).unwrap();
+        assert!(report.completed_depth >= 1);
+        assert!(game_state.possible_moves().any(|possible_move| possible_move == report.best_move));
+    }
+
+    // Searches every move through search() and keeps the completed depth of each report.
+    struct DepthRecordingGetter<Getter: MoveGetter> {
+        getter: Getter,
+        completed_depths: Mutex<Vec<i32>>
+    }
+
+    impl<Getter: MoveGetter> DepthRecordingGetter<Getter> {
+        fn new(getter: Getter) -> Self {
+            Self { getter, completed_depths: Mutex::new(vec![]) }
+        }
+    }
+
+    impl<Getter: MoveGetter> MoveGetter for DepthRecordingGetter<Getter> {
+        fn get_move(&self, state: &State, time_measurer: &TimeMeasurer) -> anyhow::Result<Move> {
+            let report = self.getter.search(state, time_measurer)?;
+            self.completed_depths.lock().unwrap().push(report.completed_depth);
+            Ok(report.best_move)
+        }
+    }
+
+    #[test]
+    fn multi_threaded_getter_completes_every_search_of_full_games_against_single_thread() {
+        let single_threaded_getter = DepthRecordingGetter::new(PVSMoveGetter::<FishDifferenceRater>::new().with_transposition_table_size_in_megabytes(1));
+        let multi_threaded_getter = DepthRecordingGetter::new(PVSMoveGetter::<FishDifferenceRater>::new().with_transposition_table_size_in_megabytes(1).with_threads(2));
+        let playout = Battle::between(&single_threaded_getter, &multi_threaded_getter).with_time_limit_in_millis(50);
+        let state = State::from_initial_board_with_start_team_one(create_board_from_seed(0));
+        for start_team in [Team::One, Team::Two] {
+            let record = playout.record_with_start_team(state.clone(), start_team).unwrap();
+            assert!(record.final_state().unwrap().is_over());
+        }
+        for getter in [&single_threaded_getter, &multi_threaded_getter] {
+            let completed_depths = getter.completed_depths.lock().unwrap();
+            assert!(!completed_depths.is_empty());
+            assert!(completed_depths.iter().all(|&depth| depth >= 1), "{:?}", completed_depths);
+        }
+    }
+
+    fn create_single_threaded_getter() -> anyhow::Result<Box<dyn MoveGetter>> {
+        Ok(Box::new(PVSMoveGetter::<CombinedRater>::new().with_transposition_table_size_in_megabytes(16)))
+    }
+
+    fn create_four_threaded_getter() -> anyhow::Result<Box<dyn MoveGetter>> {
+        Ok(Box::new(PVSMoveGetter::<CombinedRater>::new().with_transposition_table_size_in_megabytes(16).with_threads(4)))
+    }
+
+    // The only check that helper threads make the search stronger. The helpers fill the shared table in the order
+    // the scheduler runs them, so the result can't be made deterministic and it can only pass with spare cores.
+    // Run it with `cargo test --release -- --ignored four_threaded` on a machine with at least four cores.
+    // The games are played one after another, so the helper threads get the other cores.
+    #[test]
+    #[ignore = "needs ≥4 cores"]
+    fn four_threaded_getter_scores_more_than_single_thread_under_same_time_limit() {
+        let records = ParallelBattle::between(&create_single_threaded_getter, &create_four_threaded_getter)
+            .with_time_limit_in_millis(100)
+            .with_first_board_seed(11)
+            .run(0, 5)
+            .unwrap();
+        let outcome = BattleOutcome::from_records(&records).unwrap();
+        assert!(outcome.wins(Team::Two) > outcome.wins(Team::One), "{:?}", outcome);
+    }
 }
@@ -38,6 +38,10 @@ impl Rater for QuadrantOccupationRater {
     fn rate(state: &State) -> i32 {
         get_rating_for_team(state, state.current_team()) - get_rating_for_team(state, state.current_team().opponent())
     }
+
+    fn rate_per_team(state: &State) -> Option<(i32, i32)> {
+        Some((get_rating_for_team(state, state.current_team()), -get_rating_for_team(state, state.current_team().opponent())))
+    }
 }
 
 #[cfg(test)]
@@ -1,19 +1,24 @@
 extern crate rand;
 
+use std::cell::RefCell;
+
 use rostware23_lib::game::moves::Move;
 use rostware23_lib::game::state::State;
 
 use super::MoveGetter;
 use super::time_measurer::TimeMeasurer;
 use anyhow::Context;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 
 pub struct RandomGetter {
+    rng: RefCell<StdRng>
 }
 
 impl RandomGetter {
     pub fn new() -> Self {
-        Self {}
+        Self { rng: RefCell::new(StdRng::from_entropy()) }
     }
 }
 
@@ -21,7 +26,7 @@ impl MoveGetter for RandomGetter {
     fn get_move(&self, state: &State, _: &TimeMeasurer) -> anyhow::Result<Move> {
         let possible_moves_iter = state.possible_moves();
         let possible_moves: Vec<Move> = possible_moves_iter.collect();
-        let chosen_move = possible_moves.choose(&mut rand::thread_rng()).context("No possible moves found")?;
+        let chosen_move = possible_moves.choose(&mut *self.rng.borrow_mut()).context("No possible moves found")?;
         Ok(chosen_move.clone())
     }
 }
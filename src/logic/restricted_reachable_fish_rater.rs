@@ -68,6 +68,11 @@ impl<Restrictions: PenguinRestrictions> Rater for RestrictedReachableFishRater<R
         Self::reachable_fish_count_of_team(game_state, current_team) 
             - Self::reachable_fish_count_of_team(game_state, current_team.opponent())
     }
+
+    fn rate_per_team(game_state: &State) -> Option<(i32, i32)> {
+        let current_team = game_state.current_team();
+        Some((Self::reachable_fish_count_of_team(game_state, current_team), -Self::reachable_fish_count_of_team(game_state, current_team.opponent())))
+    }
 }
 
 #[cfg(test)]
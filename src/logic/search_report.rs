@@ -0,0 +1,91 @@
+use std::fmt;
+
+use rostware23_lib::game::moves::Move;
+
+use super::evaluation_trace::PrincipalVariationTrace;
+
+#[derive(Debug, Clone)]
+pub struct IterationReport {
+    pub depth: i32,
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub principal_variation: Vec<Move>,
+    pub window: (i32, i32),
+    pub aspiration_re_searches: u32,
+    // Ran out of time, only the root moves searched completely were compared
+    pub partial: bool,
+    pub nodes: u64,
+    pub transposition_hits: u64,
+    pub elapsed_millis: u128
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchReport {
+    pub best_move: Move,
+    pub score: i32,
+    pub completed_depth: i32,
+    pub principal_variation: Vec<Move>,
+    pub nodes: u64,
+    pub transposition_hits: u64,
+    pub aspiration_re_searches: u32,
+    pub time_used_millis: u128,
+    pub iterations: Vec<IterationReport>,
+    pub evaluation_trace: Option<PrincipalVariationTrace>
+}
+
+impl SearchReport {
+    // For move getters that choose a move without searching.
+    pub fn from_move(best_move: Move, time_used_millis: u128) -> Self {
+        Self {
+            principal_variation: vec![best_move.clone()],
+            best_move,
+            score: 0,
+            completed_depth: 0,
+            nodes: 0,
+            transposition_hits: 0,
+            aspiration_re_searches: 0,
+            time_used_millis,
+            iterations: vec![],
+            evaluation_trace: None
+        }
+    }
+
+    pub fn add_iteration(&mut self, iteration: IterationReport) {
+        self.nodes += iteration.nodes;
+        self.transposition_hits += iteration.transposition_hits;
+        self.aspiration_re_searches += iteration.aspiration_re_searches;
+        if let Some(best_move) = &iteration.best_move {
+            self.best_move = best_move.clone();
+            self.score = iteration.score;
+            if !iteration.partial {
+                self.completed_depth = iteration.depth;
+            }
+            self.principal_variation = iteration.principal_variation.clone();
+        }
+        self.iterations.push(iteration);
+    }
+}
+
+impl fmt::Display for IterationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Depth {}{}: score {} in window [{} {}] after {} re-searches, {} nodes, {} transposition hits, {}ms",
+            self.depth, if self.partial { " (partial)" } else { "" }, self.score, self.window.0, self.window.1,
+            self.aspiration_re_searches, self.nodes, self.transposition_hits, self.elapsed_millis)
+    }
+}
+
+impl fmt::Display for SearchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for iteration in &self.iterations {
+            writeln!(f, "{}", iteration)?;
+        }
+        writeln!(f, "Best move {:?} with score {} at depth {}", self.best_move, self.score, self.completed_depth)?;
+        writeln!(f, "Principal variation: {:?}", self.principal_variation)?;
+        write!(f, "{} nodes, {} transposition hits, {} aspiration re-searches in {}ms",
+            self.nodes, self.transposition_hits, self.aspiration_re_searches, self.time_used_millis)?;
+        if let Some(evaluation_trace) = &self.evaluation_trace {
+            write!(f, "\n{}", evaluation_trace)?;
+        }
+        Ok(())
+    }
+}
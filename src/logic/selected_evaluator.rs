@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use rostware23_lib::game::state::State;
+
+use super::Evaluator;
+use super::evaluation_trace::EvaluationTrace;
+use super::learned_evaluator::LearnedEvaluator;
+use super::weighted_evaluator::WeightedEvaluator;
+
+// The evaluation chosen on the command line with --model or --evaluation
+pub enum SelectedEvaluator {
+    Learned(LearnedEvaluator),
+    Weighted(WeightedEvaluator)
+}
+
+impl SelectedEvaluator {
+    // The default weighted evaluation is used if neither file is given
+    pub fn from_files(model: Option<&str>, evaluation_config: Option<&str>) -> anyhow::Result<Self> {
+        match (model, evaluation_config) {
+            (Some(_), Some(_)) => anyhow::bail!("Only one of --model and --evaluation can be used"),
+            (Some(path), None) => Ok(Self::Learned(LearnedEvaluator::from_file(Path::new(path))?)),
+            (None, Some(path)) => Ok(Self::Weighted(WeightedEvaluator::from_file(Path::new(path))?)),
+            (None, None) => Ok(Self::Weighted(WeightedEvaluator::default()))
+        }
+    }
+}
+
+impl Evaluator for SelectedEvaluator {
+    fn evaluate(&self, state: &State) -> i32 {
+        match self {
+            Self::Learned(evaluator) => evaluator.evaluate(state),
+            Self::Weighted(evaluator) => evaluator.evaluate(state)
+        }
+    }
+
+    fn trace(&self, state: &State) -> EvaluationTrace {
+        match self {
+            Self::Learned(evaluator) => evaluator.trace(state),
+            Self::Weighted(evaluator) => evaluator.trace(state)
+        }
+    }
+}
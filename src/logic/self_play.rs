@@ -0,0 +1,388 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::{Rng, SeedableRng};
+use rostware23_lib::game::moves::Move;
+use rostware23_lib::game::state::State;
+use rostware23_lib::game::state_generator::create_board_from_seed;
+use rostware23_lib::xml::common::Team;
+
+use super::MoveGetter;
+use super::time_measurer::TimeMeasurer;
+
+pub const DEFAULT_SHARD_SIZE: usize = 10000;
+
+// Starts every binary shard, followed by the version of the record layout as u16
+const BINARY_MAGIC: &[u8; 4] = b"RWSP";
+const BINARY_VERSION: u16 = 1;
+
+#[derive(Debug, Clone)]
+pub struct SelfPlaySettings {
+    pub games: u64,
+    // Game i is played on the board of seed + i, odd games are started by team two
+    pub seed: u64,
+    pub time_limit_in_millis: u128,
+    // Probability of playing a random move instead of the engine's
+    pub exploration: f64,
+    // Random moves are only played in the first plies of every game
+    pub exploration_plies: u32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrainingPosition {
+    pub game: u64,
+    pub state: State,
+    // The search score and the outcome are seen from the current team
+    pub score: i32,
+    pub chosen_move: Move,
+    pub explored: bool,
+    // 1 for a win, 0 for a draw and -1 for a loss
+    pub outcome: i8
+}
+
+impl TrainingPosition {
+    pub fn to_json_line(&self) -> String {
+        format!("{{\"game\":{},\"state\":\"{}\",\"score\":{},\"move\":\"{}\",\"explored\":{},\"outcome\":{}}}",
+            self.game, self.state.to_notation(), self.score, self.chosen_move.to_notation(), self.explored, self.outcome)
+    }
+
+    // Little endian: game as u64, the length of the state notation as u8 and the notation,
+    // score as i32, the length of the move notation as u8 and the notation, explored as u8, outcome as i8
+    fn write_binary(&self, output: &mut impl Write) -> anyhow::Result<()> {
+        output.write_all(&self.game.to_le_bytes())?;
+        write_length_prefixed(output, &self.state.to_notation())?;
+        output.write_all(&self.score.to_le_bytes())?;
+        write_length_prefixed(output, &self.chosen_move.to_notation())?;
+        output.write_all(&[u8::from(self.explored), self.outcome as u8])?;
+        Ok(())
+    }
+
+    fn read_binary(input: &mut impl Read) -> anyhow::Result<Option<Self>> {
+        let mut game = [0; 8];
+        match input.read_exact(&mut game) {
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?
+        }
+        let state = State::from_notation(&read_length_prefixed(input)?)?;
+        let mut score = [0; 4];
+        input.read_exact(&mut score)?;
+        let chosen_move = Move::from_notation(&read_length_prefixed(input)?)?;
+        let mut flags = [0; 2];
+        input.read_exact(&mut flags)?;
+        Ok(Some(Self { game: u64::from_le_bytes(game), state, score: i32::from_le_bytes(score), chosen_move, explored: flags[0] != 0, outcome: flags[1] as i8 }))
+    }
+
+    pub fn from_json_line(line: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            game: json_value(line, "game")?.parse()?,
+            state: State::from_notation(json_value(line, "state")?)?,
+            score: json_value(line, "score")?.parse()?,
+            chosen_move: Move::from_notation(json_value(line, "move")?)?,
+            explored: json_value(line, "explored")?.parse()?,
+            outcome: json_value(line, "outcome")?.parse()?
+        })
+    }
+}
+
+// Only reads the flat objects written by to_json_line, strings are never escaped there.
+fn json_value<'a>(line: &'a str, key: &str) -> anyhow::Result<&'a str> {
+    let start = line.find(&format!("\"{}\":", key)).with_context(|| format!("Missing '{}' in '{}'", key, line))? + key.len() + 3;
+    let value = &line[start..];
+    match value.strip_prefix('"') {
+        Some(string) => string.split('"').next().context("Unterminated string"),
+        None => Ok(value.split([',', '}']).next().unwrap_or_default().trim())
+    }
+}
+
+fn read_length_prefixed(input: &mut impl Read) -> anyhow::Result<String> {
+    let mut length = [0; 1];
+    input.read_exact(&mut length)?;
+    let mut text = vec![0; length[0] as usize];
+    input.read_exact(&mut text)?;
+    Ok(String::from_utf8(text)?)
+}
+
+fn write_length_prefixed(output: &mut impl Write, text: &str) -> anyhow::Result<()> {
+    output.write_all(&[u8::try_from(text.len()).context("Notation too long for binary shard")?])?;
+    output.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn outcome_for_team(final_state: &State, team: Team) -> anyhow::Result<i8> {
+    Ok(match final_state.get_result()?.winner {
+        Some(winner) if winner == team => 1,
+        Some(_) => -1,
+        None => 0
+    })
+}
+
+// Plays one game and labels all its positions with the final outcome.
+pub fn play_game(engine_team_one: &dyn MoveGetter, engine_team_two: &dyn MoveGetter, game: u64, settings: &SelfPlaySettings) -> anyhow::Result<Vec<TrainingPosition>> {
+    let seed = settings.seed.wrapping_add(game);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut state = State::from_initial_board_with_start_team_one(create_board_from_seed(seed));
+    if game % 2 == 1 {
+        state.start_team = Team::Two;
+    }
+
+    let mut positions = vec![];
+    let mut ply = 0;
+    while !state.is_over() {
+        state = state.with_moveless_player_skipped()?;
+        let engine = match state.current_team() {
+            Team::One => engine_team_one,
+            Team::Two => engine_team_two
+        };
+        let report = engine.search(&state, &TimeMeasurer::new(settings.time_limit_in_millis))?;
+        let explored = ply < settings.exploration_plies && rng.gen_bool(settings.exploration.clamp(0.0, 1.0));
+        let chosen_move = if explored {
+            state.possible_moves().choose(&mut rng).context("No possible moves found")?
+        } else {
+            report.best_move
+        };
+        positions.push(TrainingPosition { game, state: state.clone(), score: report.score, chosen_move: chosen_move.clone(), explored, outcome: 0 });
+        state.perform_move(chosen_move)?;
+        ply += 1;
+    }
+
+    for position in &mut positions {
+        position.outcome = outcome_for_team(&state, position.state.current_team())?;
+    }
+    Ok(positions)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrainingDataFormat {
+    Jsonl,
+    Binary
+}
+
+impl TrainingDataFormat {
+    pub fn from_name(name: &str) -> anyhow::Result<Self> {
+        match name {
+            "jsonl" => Ok(Self::Jsonl),
+            "binary" => Ok(Self::Binary),
+            _ => anyhow::bail!("Unknown training data format '{}', expected jsonl or binary", name)
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Jsonl => "jsonl",
+            Self::Binary => "bin"
+        }
+    }
+
+    fn of_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "jsonl" => Some(Self::Jsonl),
+            "bin" => Some(Self::Binary),
+            _ => None
+        }
+    }
+}
+
+pub fn read_training_shard(path: &Path) -> anyhow::Result<Vec<TrainingPosition>> {
+    let format = TrainingDataFormat::of_path(path).with_context(|| format!("Unknown training data format of {}", path.display()))?;
+    let mut input = BufReader::new(File::open(path).with_context(|| format!("Could not open {}", path.display()))?);
+    let mut positions = vec![];
+    match format {
+        TrainingDataFormat::Jsonl => for line in input.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                positions.push(TrainingPosition::from_json_line(&line)?);
+            }
+        },
+        TrainingDataFormat::Binary => {
+            let mut header = [0; 6];
+            input.read_exact(&mut header)?;
+            if &header[..4] != BINARY_MAGIC || header[4..] != BINARY_VERSION.to_le_bytes() {
+                anyhow::bail!("{} is no training data shard of version {}", path.display(), BINARY_VERSION);
+            }
+            while let Some(position) = TrainingPosition::read_binary(&mut input)? {
+                positions.push(position);
+            }
+        }
+    }
+    Ok(positions)
+}
+
+// Reads a single shard or all shards of a directory in the order of their names.
+pub fn read_training_data(path: &Path) -> anyhow::Result<Vec<TrainingPosition>> {
+    if !path.is_dir() {
+        return read_training_shard(path);
+    }
+    let mut shard_paths: Vec<PathBuf> = fs::read_dir(path)
+        .with_context(|| format!("Could not read {}", path.display()))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|shard_path| TrainingDataFormat::of_path(shard_path).is_some())
+        .collect();
+    shard_paths.sort();
+    let mut positions = vec![];
+    for shard_path in shard_paths {
+        positions.extend(read_training_shard(&shard_path)?);
+    }
+    Ok(positions)
+}
+
+// Splits the positions into shards of a fixed size in one directory.
+pub struct TrainingDataWriter {
+    directory: PathBuf,
+    format: TrainingDataFormat,
+    shard_size: usize,
+    shard: Option<BufWriter<File>>,
+    shard_paths: Vec<PathBuf>,
+    positions: usize
+}
+
+impl TrainingDataWriter {
+    pub fn new(directory: &Path, format: TrainingDataFormat, shard_size: usize) -> anyhow::Result<Self> {
+        fs::create_dir_all(directory).with_context(|| format!("Could not create {}", directory.display()))?;
+        Ok(Self { directory: directory.to_path_buf(), format, shard_size: shard_size.max(1), shard: None, shard_paths: vec![], positions: 0 })
+    }
+
+    fn open_next_shard(&mut self) -> anyhow::Result<()> {
+        self.finish_shard()?;
+        let path = self.directory.join(format!("selfplay-{:05}.{}", self.shard_paths.len(), self.format.extension()));
+        let mut shard = BufWriter::new(File::create(&path).with_context(|| format!("Could not create {}", path.display()))?);
+        if self.format == TrainingDataFormat::Binary {
+            shard.write_all(BINARY_MAGIC)?;
+            shard.write_all(&BINARY_VERSION.to_le_bytes())?;
+        }
+        self.shard = Some(shard);
+        self.shard_paths.push(path);
+        Ok(())
+    }
+
+    fn finish_shard(&mut self) -> anyhow::Result<()> {
+        if let Some(mut shard) = self.shard.take() {
+            shard.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn write(&mut self, position: &TrainingPosition) -> anyhow::Result<()> {
+        if self.positions.is_multiple_of(self.shard_size) {
+            self.open_next_shard()?;
+        }
+        let shard = self.shard.as_mut().context("No open shard")?;
+        match self.format {
+            TrainingDataFormat::Jsonl => writeln!(shard, "{}", position.to_json_line())?,
+            TrainingDataFormat::Binary => position.write_binary(shard)?
+        }
+        self.positions += 1;
+        Ok(())
+    }
+
+    pub fn positions(&self) -> usize {
+        self.positions
+    }
+
+    pub fn finish(mut self) -> anyhow::Result<Vec<PathBuf>> {
+        self.finish_shard()?;
+        Ok(self.shard_paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::common::Coordinate;
+
+    use super::*;
+    use crate::logic::fish_difference_rater::FishDifferenceRater;
+    use crate::logic::pvs_getter::PVSMoveGetter;
+    use crate::logic::random_getter::RandomGetter;
+
+    fn create_settings(exploration: f64, exploration_plies: u32) -> SelfPlaySettings {
+        SelfPlaySettings { games: 1, seed: 11, time_limit_in_millis: 1000, exploration, exploration_plies }
+    }
+
+    fn create_engine() -> PVSMoveGetter<FishDifferenceRater> {
+        PVSMoveGetter::<FishDifferenceRater>::new_fixed().with_transposition_table_size_in_megabytes(1)
+    }
+
+    #[test]
+    fn positions_are_labelled_with_outcome_of_their_team() {
+        let engine = RandomGetter::new();
+        let positions = play_game(&engine, &engine, 1, &create_settings(0.0, 0)).unwrap();
+        assert_eq!(Team::Two, positions[0].state.start_team);
+        let mut final_state = positions.last().unwrap().state.clone();
+        final_state.perform_move(positions.last().unwrap().chosen_move.clone()).unwrap();
+        assert!(final_state.is_over());
+        for position in &positions {
+            let team = position.state.current_team();
+            assert_eq!(outcome_for_team(&final_state, team).unwrap(), position.outcome);
+            assert!(!position.explored);
+        }
+        assert!(positions.windows(2).all(|pair| pair[0].state.turn < pair[1].state.turn));
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible_and_explore_only_in_the_opening() {
+        let settings = create_settings(1.0, 4);
+        let play = || {
+            let (engine_team_one, engine_team_two) = (create_engine(), create_engine());
+            play_game(&engine_team_one, &engine_team_two, 0, &settings).unwrap()
+        };
+        let (first, second) = (play(), play());
+        assert_eq!(first, second);
+        assert!(first.iter().take(4).all(|position| position.explored));
+        assert!(first.iter().skip(4).all(|position| !position.explored));
+    }
+
+    #[test]
+    fn json_lines_contain_notations() {
+        let state = State::from_notation("10000000/00000000/00000000/00000000/00000000/00000000/00000000/0000000G one 9 3 5").unwrap();
+        let position = TrainingPosition {
+            game: 4,
+            state,
+            score: -12,
+            chosen_move: Move::Normal { from: Coordinate::new(15, 7), to: Coordinate::new(0, 0) },
+            explored: false,
+            outcome: -1
+        };
+        assert_eq!("{\"game\":4,\"state\":\"10000000/00000000/00000000/00000000/00000000/00000000/00000000/0000000G one 9 3 5\",\"score\":-12,\"move\":\"h8-a1\",\"explored\":false,\"outcome\":-1}",
+            position.to_json_line());
+    }
+
+    #[test]
+    fn positions_are_split_into_shards() {
+        let engine = RandomGetter::new();
+        let positions = play_game(&engine, &engine, 0, &create_settings(0.0, 0)).unwrap();
+        for format in [TrainingDataFormat::Jsonl, TrainingDataFormat::Binary] {
+            let directory = std::env::temp_dir().join(format!("rostware23_self_play_test_{:?}", format));
+            let mut writer = TrainingDataWriter::new(&directory, format, 10).unwrap();
+            for position in &positions {
+                writer.write(position).unwrap();
+            }
+            assert_eq!(positions.len(), writer.positions());
+            let paths = writer.finish().unwrap();
+            assert_eq!(positions.len().div_ceil(10), paths.len());
+            let first_shard = fs::read(&paths[0]).unwrap();
+            let read_positions = read_training_data(&directory).unwrap();
+            fs::remove_dir_all(&directory).unwrap();
+            assert_eq!(positions.iter().map(TrainingPosition::to_json_line).collect::<Vec<_>>(),
+                read_positions.iter().map(TrainingPosition::to_json_line).collect::<Vec<_>>());
+            match format {
+                TrainingDataFormat::Jsonl => {
+                    let content = String::from_utf8(first_shard).unwrap();
+                    assert_eq!(10, content.lines().count());
+                    assert_eq!(positions[0].to_json_line(), content.lines().next().unwrap());
+                },
+                TrainingDataFormat::Binary => {
+                    assert_eq!(BINARY_MAGIC, &first_shard[..4]);
+                    let notation = positions[0].state.to_notation();
+                    assert_eq!(notation.len() as u8, first_shard[14]);
+                    assert_eq!(notation.as_bytes(), &first_shard[15..15 + notation.len()]);
+                }
+            }
+        }
+        assert!(TrainingDataFormat::from_name("parquet").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rostware23_lib::xml::common::Team;
+
+use super::MoveGetter;
+use super::battle::{BattleOutcome, ParallelBattle};
+use super::pvs_getter::PVSMoveGetter;
+use super::weighted_evaluator::WeightedEvaluator;
+
+pub const DEFAULT_LEARNING_RATE: f64 = 4.0;
+pub const DEFAULT_PERTURBATION: f64 = 2.0;
+
+// Decay exponents recommended by Spall for the step size and the perturbation
+const LEARNING_RATE_DECAY: f64 = 0.602;
+const PERTURBATION_DECAY: f64 = 0.101;
+
+const TRANSPOSITION_TABLE_SIZE_IN_MEGABYTES: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct SpsaSettings {
+    pub iterations: usize,
+    // Bi-directional games per iteration, each on its own board
+    pub games_per_iteration: usize,
+    pub threads: usize,
+    pub time_limit_in_millis: u128,
+    pub learning_rate: f64,
+    pub perturbation: f64,
+    // Determines the perturbations and the boards of every iteration
+    pub seed: u64
+}
+
+#[derive(Debug, Clone)]
+pub struct SpsaStep {
+    pub iteration: usize,
+    // Share of games won by the positive perturbation minus the share won by the negative one
+    pub score: f64,
+    pub weights: Vec<i32>
+}
+
+// Simultaneous perturbation stochastic approximation: every iteration plays the weights
+// shifted in a random direction against the weights shifted in the opposite direction
+// and moves the weights towards the winner.
+pub struct SpsaTuner {
+    settings: SpsaSettings,
+    evaluator: WeightedEvaluator,
+    parameters: Vec<f64>,
+    iteration: usize
+}
+
+impl SpsaTuner {
+    pub fn new(evaluator: WeightedEvaluator, settings: SpsaSettings) -> Self {
+        Self {
+            parameters: evaluator.weights().into_iter().map(f64::from).collect(),
+            evaluator,
+            settings,
+            iteration: 0
+        }
+    }
+
+    // Continues the schedule of an earlier run, whose last checkpoint was loaded as evaluator.
+    pub fn with_first_iteration(mut self, iteration: usize) -> Self {
+        self.iteration = iteration;
+        self
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.iteration >= self.settings.iterations
+    }
+
+    pub fn evaluator(&self) -> WeightedEvaluator {
+        self.evaluator_with_parameters(&self.parameters)
+    }
+
+    fn evaluator_with_parameters(&self, parameters: &[f64]) -> WeightedEvaluator {
+        let weights: Vec<i32> = parameters.iter().map(|parameter| parameter.round() as i32).collect();
+        self.evaluator.with_weights(&weights).expect("Parameters are created from the evaluator's weights")
+    }
+
+    pub fn step(&mut self) -> anyhow::Result<SpsaStep> {
+        let settings = self.settings.clone();
+        let first_pair = self.iteration * settings.games_per_iteration;
+        self.step_with_objective(|plus, minus| play_games(plus, minus, first_pair, &settings))
+    }
+
+    // The objective returns how much better the first evaluator is than the second, between -1 and 1.
+    fn step_with_objective(&mut self, objective: impl FnOnce(&WeightedEvaluator, &WeightedEvaluator) -> anyhow::Result<f64>) -> anyhow::Result<SpsaStep> {
+        let iteration = self.iteration as f64 + 1.0;
+        let stability = self.settings.iterations as f64 / 10.0;
+        let learning_rate = self.settings.learning_rate / (iteration + stability).powf(LEARNING_RATE_DECAY);
+        let perturbation = self.settings.perturbation / iteration.powf(PERTURBATION_DECAY);
+
+        let mut rng = StdRng::seed_from_u64(self.settings.seed.wrapping_add(self.iteration as u64));
+        let directions: Vec<f64> = self.parameters.iter()
+            .map(|_| if rng.gen_bool(0.5) { 1.0 } else { -1.0 })
+            .collect();
+        let shifted = |sign: f64| -> Vec<f64> {
+            self.parameters.iter().zip(&directions)
+                .map(|(parameter, direction)| parameter + sign * perturbation * direction)
+                .collect()
+        };
+        let plus = self.evaluator_with_parameters(&shifted(1.0));
+        let minus = self.evaluator_with_parameters(&shifted(-1.0));
+
+        let score = objective(&plus, &minus)?;
+        for (parameter, direction) in self.parameters.iter_mut().zip(&directions) {
+            *parameter += learning_rate * score / (2.0 * perturbation * direction);
+        }
+        self.iteration += 1;
+        Ok(SpsaStep {
+            iteration: self.iteration,
+            score,
+            weights: self.evaluator().weights()
+        })
+    }
+}
+
+// Every board is played with both start teams, plus plays team one. Each iteration plays the next pairs of the seeded boards.
+fn play_games(plus: &WeightedEvaluator, minus: &WeightedEvaluator, first_pair: usize, settings: &SpsaSettings) -> anyhow::Result<f64> {
+    let create_getter = |evaluator: &WeightedEvaluator| -> anyhow::Result<Box<dyn MoveGetter>> {
+        Ok(Box::new(PVSMoveGetter::with_evaluator(evaluator.clone())
+            .with_transposition_table_size_in_megabytes(TRANSPOSITION_TABLE_SIZE_IN_MEGABYTES)))
+    };
+    let create_plus_getter = || create_getter(plus);
+    let create_minus_getter = || create_getter(minus);
+    let records = ParallelBattle::between(&create_plus_getter, &create_minus_getter)
+        .with_time_limit_in_millis(settings.time_limit_in_millis)
+        .with_first_board_seed(settings.seed)
+        .with_threads(settings.threads)
+        .run(first_pair, settings.games_per_iteration)?;
+    let outcome = BattleOutcome::from_records(&records)?;
+    let games = (settings.games_per_iteration * 2).max(1) as f64;
+    Ok((outcome.wins(Team::One) as f64 - outcome.wins(Team::Two) as f64) / games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_settings(iterations: usize) -> SpsaSettings {
+        SpsaSettings {
+            iterations,
+            games_per_iteration: 1,
+            threads: 1,
+            time_limit_in_millis: 5,
+            learning_rate: DEFAULT_LEARNING_RATE,
+            perturbation: DEFAULT_PERTURBATION,
+            seed: 7
+        }
+    }
+
+    fn fish_difference_weight(evaluator: &WeightedEvaluator) -> i32 {
+        evaluator.weights()[0]
+    }
+
+    #[test]
+    fn weights_move_towards_winning_perturbation() {
+        let evaluator = WeightedEvaluator::from_config("fish_difference 10\npotential_fish 3").unwrap();
+        let mut tuner = SpsaTuner::new(evaluator, create_settings(30));
+        while !tuner.is_finished() {
+            tuner.step_with_objective(|plus, minus| {
+                Ok((fish_difference_weight(plus) - fish_difference_weight(minus)).signum() as f64)
+            }).unwrap();
+        }
+        assert!(fish_difference_weight(&tuner.evaluator()) > 15);
+        assert_eq!(30, tuner.iteration());
+    }
+
+    #[test]
+    fn perturbations_are_reproducible_and_opposite() {
+        let evaluator = WeightedEvaluator::default();
+        let mut perturbations = vec![];
+        for _ in 0..2 {
+            let mut tuner = SpsaTuner::new(evaluator.clone(), create_settings(10)).with_first_iteration(3);
+            tuner.step_with_objective(|plus, minus| {
+                perturbations.push((plus.weights(), minus.weights()));
+                Ok(0.0)
+            }).unwrap();
+            assert_eq!(evaluator, tuner.evaluator());
+        }
+        assert_eq!(perturbations[0], perturbations[1]);
+        let (plus, minus) = &perturbations[0];
+        for ((plus, minus), weight) in plus.iter().zip(minus).zip(evaluator.weights()) {
+            assert_eq!(weight * 2, plus + minus);
+            assert_ne!(plus, minus);
+        }
+    }
+
+    #[test]
+    fn tuning_step_plays_games_in_parallel() {
+        let mut settings = create_settings(1);
+        settings.games_per_iteration = 2;
+        settings.threads = 2;
+        let mut tuner = SpsaTuner::new(WeightedEvaluator::default(), settings);
+        let step = tuner.step().unwrap();
+        assert!((-1.0..=1.0).contains(&step.score));
+        assert_eq!(WeightedEvaluator::default().weights().len(), step.weights.len());
+        assert!(tuner.is_finished());
+    }
+}
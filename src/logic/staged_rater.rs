@@ -6,6 +6,31 @@ use super::Rater;
 
 const EARLY_GAME_MAX_TURN: u32 = 8;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamePhase {
+    Early,
+    Mid,
+    // The opponent can't move anymore
+    End
+}
+
+impl GamePhase {
+    pub fn of_state(state: &State) -> Self {
+        if state.turn < EARLY_GAME_MAX_TURN {
+            return GamePhase::Early;
+        }
+        let opponent_team = state.current_team().opponent();
+        if !state.has_team_any_moves(opponent_team) {
+            return GamePhase::End;
+        }
+        GamePhase::Mid
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
 pub struct StagedRater<
     const EARLY_MULTIPLIER: i32,
     const MID_MULTIPLIER: i32,
@@ -20,19 +45,31 @@ impl<
         const MID_MULTIPLIER: i32,
         const END_MULTIPLIER: i32,
         Heuristic: Rater,
-    > Rater for StagedRater<EARLY_MULTIPLIER, MID_MULTIPLIER, END_MULTIPLIER, Heuristic>
+    > StagedRater<EARLY_MULTIPLIER, MID_MULTIPLIER, END_MULTIPLIER, Heuristic>
 {
-    fn rate(state: &State) -> i32 {
-        if state.turn < EARLY_GAME_MAX_TURN {
-            return EARLY_MULTIPLIER * Heuristic::rate(state);
+    fn multiplier(state: &State) -> i32 {
+        match GamePhase::of_state(state) {
+            GamePhase::Early => EARLY_MULTIPLIER,
+            GamePhase::Mid => MID_MULTIPLIER,
+            GamePhase::End => END_MULTIPLIER
         }
+    }
+}
 
-        let opponent_team = state.current_team().opponent();
-        if !state.has_team_any_moves(opponent_team) {
-            return END_MULTIPLIER * Heuristic::rate(state);
-        }
+impl<
+        const EARLY_MULTIPLIER: i32,
+        const MID_MULTIPLIER: i32,
+        const END_MULTIPLIER: i32,
+        Heuristic: Rater,
+    > Rater for StagedRater<EARLY_MULTIPLIER, MID_MULTIPLIER, END_MULTIPLIER, Heuristic>
+{
+    fn rate(state: &State) -> i32 {
+        Self::multiplier(state) * Heuristic::rate(state)
+    }
 
-        MID_MULTIPLIER * Heuristic::rate(state)
+    fn rate_per_team(state: &State) -> Option<(i32, i32)> {
+        let multiplier = Self::multiplier(state);
+        Heuristic::rate_per_team(state).map(|(own, opponent)| (multiplier * own, multiplier * opponent))
     }
 }
 
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use rostware23_lib::game::common::Coordinate;
+use rostware23_lib::game::direction::DirectionIterator;
+use rostware23_lib::game::penguin::CoordinatesInDirectionIterator;
+use rostware23_lib::game::state::State;
+use rostware23_lib::xml::common::Team;
+use rostware23_lib::xml::state::FieldState;
+
+use super::Rater;
+
+const FIELD_COUNT: usize = 64;
+const UNREACHABLE: u8 = u8::MAX;
+
+// Assigns every fish field to the team whose penguins can slide there in fewer moves
+// and rates the difference of the fish in both territories.
+// The board is taken as it is, fields melting away during the moves are ignored.
+pub struct TerritoryRater;
+
+impl TerritoryRater {
+    // Multi-source breadth-first search over the moves of all penguins of the team.
+    fn move_distances_of_team(state: &State, team: Team) -> [u8; FIELD_COUNT] {
+        let mut distances = [UNREACHABLE; FIELD_COUNT];
+        let mut queue = VecDeque::new();
+        for penguin in state.board.get_penguin_iterator(team) {
+            distances[penguin.coordinate.to_field_index() as usize] = 0;
+            queue.push_back(penguin.coordinate);
+        }
+        while let Some(coordinate) = queue.pop_front() {
+            let distance = distances[coordinate.to_field_index() as usize];
+            for direction in DirectionIterator::new() {
+                let targets = CoordinatesInDirectionIterator::from(coordinate.clone(), direction)
+                    .take_while(|target| state.board.can_move_to(target.clone()).unwrap_or(false));
+                for target in targets {
+                    let target_index = target.to_field_index() as usize;
+                    if distances[target_index] == UNREACHABLE {
+                        distances[target_index] = distance + 1;
+                        queue.push_back(target);
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    // The fish in the territory of the current team and of its opponent.
+    // The current team moves first, so it wins fields both teams reach in the same amount of moves.
+    fn territory_fish(state: &State) -> (i32, i32) {
+        let current_team = state.current_team();
+        let own_distances = Self::move_distances_of_team(state, current_team);
+        let opponent_distances = Self::move_distances_of_team(state, current_team.opponent());
+        let mut own_fish = 0;
+        let mut opponent_fish = 0;
+        for index in 0..FIELD_COUNT {
+            let FieldState::Fish(fish) = state.board.get(Coordinate::from_field_index(index as u64)).unwrap() else {
+                continue;
+            };
+            let (own_distance, opponent_distance) = (own_distances[index], opponent_distances[index]);
+            if own_distance == UNREACHABLE && opponent_distance == UNREACHABLE {
+                continue;
+            }
+            if own_distance <= opponent_distance {
+                own_fish += fish as i32;
+            } else {
+                opponent_fish += fish as i32;
+            }
+        }
+        (own_fish, opponent_fish)
+    }
+}
+
+impl Rater for TerritoryRater {
+    fn rate(state: &State) -> i32 {
+        let (own_fish, opponent_fish) = Self::territory_fish(state);
+        own_fish - opponent_fish
+    }
+
+    fn rate_per_team(state: &State) -> Option<(i32, i32)> {
+        let (own_fish, opponent_fish) = Self::territory_fish(state);
+        Some((own_fish, -opponent_fish))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::board::Board;
+    use rostware23_lib::game::moves::Move;
+    use rostware23_lib::game::state_generator::create_any;
+
+    use super::*;
+    use crate::logic::Evaluator;
+    use crate::logic::weighted_evaluator::WeightedEvaluator;
+
+    #[test]
+    fn slides_count_as_one_move_and_are_blocked_by_empty_fields() {
+        let mut board = Board::empty();
+        board.perform_move(Move::Place(Coordinate::new(0, 0)), Team::One).unwrap();
+        for x in [2, 4, 6, 10] {
+            board.set(Coordinate::new(x, 0), FieldState::Fish(1)).unwrap();
+        }
+        board.set(Coordinate::new(7, 1), FieldState::Fish(1)).unwrap();
+        let state = State::from_initial_board_with_start_team_one(board);
+        let distances = TerritoryRater::move_distances_of_team(&state, Team::One);
+        assert_eq!(0, distances[Coordinate::new(0, 0).to_field_index() as usize]);
+        assert_eq!(1, distances[Coordinate::new(2, 0).to_field_index() as usize]);
+        assert_eq!(1, distances[Coordinate::new(6, 0).to_field_index() as usize]);
+        assert_eq!(2, distances[Coordinate::new(7, 1).to_field_index() as usize]);
+        assert_eq!(UNREACHABLE, distances[Coordinate::new(10, 0).to_field_index() as usize]);
+    }
+
+    #[test]
+    fn fields_go_to_the_team_reaching_them_first() {
+        let mut board = Board::empty();
+        board.perform_move(Move::Place(Coordinate::new(0, 0)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(14, 0)), Team::Two).unwrap();
+        board.set(Coordinate::new(2, 0), FieldState::Fish(1)).unwrap();
+        board.set(Coordinate::new(4, 0), FieldState::Fish(1)).unwrap();
+        board.set(Coordinate::new(12, 0), FieldState::Fish(2)).unwrap();
+        board.set(Coordinate::new(11, 1), FieldState::Fish(3)).unwrap();
+        board.set(Coordinate::new(9, 1), FieldState::Fish(3)).unwrap();
+        let state = State::from_initial_board_with_start_team_one(board);
+        assert_eq!(Some((2, -8)), TerritoryRater::rate_per_team(&state));
+        assert_eq!(-6, TerritoryRater::rate(&state));
+    }
+
+    #[test]
+    fn current_team_wins_fields_reached_in_the_same_amount_of_moves() {
+        let mut board = Board::empty();
+        board.perform_move(Move::Place(Coordinate::new(0, 0)), Team::One).unwrap();
+        board.perform_move(Move::Place(Coordinate::new(8, 0)), Team::Two).unwrap();
+        board.set(Coordinate::new(2, 0), FieldState::Fish(1)).unwrap();
+        board.set(Coordinate::new(4, 0), FieldState::Fish(4)).unwrap();
+        board.set(Coordinate::new(6, 0), FieldState::Fish(1)).unwrap();
+        let mut state = State::from_initial_board_with_start_team_one(board);
+        assert_eq!(6, TerritoryRater::rate(&state));
+        state.turn = 1;
+        assert_eq!(6, TerritoryRater::rate(&state));
+    }
+
+    #[test]
+    fn territory_can_be_selected_in_weighted_evaluation() {
+        let mut state = create_any();
+        for _ in 0..8 {
+            let possible_move = state.possible_moves().next().unwrap();
+            state.perform_move(possible_move).unwrap();
+        }
+        let evaluator = WeightedEvaluator::from_config("territory 3").unwrap();
+        assert_eq!(3 * TerritoryRater::rate(&state), evaluator.evaluate(&state));
+    }
+}
@@ -13,6 +13,14 @@ impl TimeMeasurer {
         }
     }
 
+    pub fn until(deadline: Instant) -> Self {
+        let start = Instant::now();
+        Self::Normal {
+            start,
+            maximum_time_in_millis: deadline.saturating_duration_since(start).as_millis()
+        }
+    }
+
     pub fn new_infinite() -> Self {
         Self::Infinite
     }
@@ -24,6 +32,14 @@ impl TimeMeasurer {
         }
     }
 
+    // None if the time is unlimited
+    pub fn remaining_millis(&self) -> Option<u128> {
+        match self {
+            TimeMeasurer::Normal { start: _, maximum_time_in_millis } => Some(maximum_time_in_millis.saturating_sub(self.elapsed_millis_since_start())),
+            TimeMeasurer::Infinite => None,
+        }
+    }
+
     pub fn has_time_left(&self) -> bool {
         match self {
             TimeMeasurer::Normal { start: _, maximum_time_in_millis } => self.elapsed_millis_since_start() < *maximum_time_in_millis,
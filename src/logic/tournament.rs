@@ -0,0 +1,491 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use rostware23_lib::xml::common::Team;
+
+use super::MoveGetter;
+use super::battle::{MoveGetterFactory, ParallelBattle};
+use super::move_getter_factory::{create_move_getter, is_move_getter_name, MOVE_GETTER_NAMES};
+
+// Two sided 95% quantile of the normal distribution
+const CONFIDENCE_QUANTILE: f64 = 1.96;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    pub name: String,
+    pub engine: String,
+    // Overrides the time limit of the tournament
+    pub time_limit_in_millis: Option<u128>
+}
+
+impl EngineConfig {
+    // "NAME=ENGINE@MILLIS", the name defaults to the engine and the time limit to the tournament's
+    pub fn from_spec(spec: &str) -> anyhow::Result<Self> {
+        let (spec_without_time, time_limit) = match spec.split_once('@') {
+            Some((spec_without_time, time_limit)) => (spec_without_time, Some(time_limit.trim().parse::<u128>()
+                .with_context(|| format!("Invalid time limit in engine configuration '{}'", spec))?)),
+            None => (spec, None)
+        };
+        let (name, engine) = spec_without_time.split_once('=').unwrap_or((spec_without_time, spec_without_time));
+        let (name, engine) = (name.trim(), engine.trim());
+        if name.is_empty() || !is_move_getter_name(engine) {
+            anyhow::bail!("Invalid engine configuration '{}', expected NAME=ENGINE@MILLIS with an engine of {:?} or external:COMMAND", spec, MOVE_GETTER_NAMES);
+        }
+        Ok(Self { name: name.to_string(), engine: engine.to_string(), time_limit_in_millis: time_limit })
+    }
+
+    fn create_move_getter(&self) -> anyhow::Result<Box<dyn MoveGetter>> {
+        Ok(create_move_getter(&self.engine)?)
+    }
+}
+
+impl fmt::Display for EngineConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.engine)?;
+        if let Some(time_limit_in_millis) = self.time_limit_in_millis {
+            write!(f, "@{}", time_limit_in_millis)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TournamentFormat {
+    RoundRobin,
+    // The first engine plays against all others
+    Gauntlet
+}
+
+impl TournamentFormat {
+    pub fn from_name(name: &str) -> anyhow::Result<Self> {
+        match name {
+            "round-robin" => Ok(Self::RoundRobin),
+            "gauntlet" => Ok(Self::Gauntlet),
+            _ => anyhow::bail!("Unknown tournament format '{}', expected round-robin or gauntlet", name)
+        }
+    }
+
+    fn pairings(self, engines: usize) -> Vec<(usize, usize)> {
+        match self {
+            Self::RoundRobin => (0..engines).flat_map(|first| (first + 1..engines).map(move |second| (first, second))).collect(),
+            Self::Gauntlet => (1..engines).map(|second| (0, second)).collect()
+        }
+    }
+}
+
+impl fmt::Display for TournamentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::Gauntlet => write!(f, "gauntlet")
+        }
+    }
+}
+
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn elo_of_score(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    // Bounds of the 95% confidence interval
+    pub lower: f64,
+    pub upper: f64
+}
+
+impl fmt::Display for EloEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+.1} [{:+.1}, {:+.1}]", self.elo, self.lower, self.upper)
+    }
+}
+
+// Games seen from the first engine of a pairing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+impl MatchScore {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    pub fn reversed(&self) -> Self {
+        Self { wins: self.losses, draws: self.draws, losses: self.wins }
+    }
+
+    fn add_result(&mut self, winner: Option<Team>, team: Team) {
+        match winner {
+            Some(winner) if winner == team => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1
+        }
+    }
+
+    fn combined(&self, other: &Self) -> Self {
+        Self { wins: self.wins + other.wins, draws: self.draws + other.draws, losses: self.losses + other.losses }
+    }
+
+    // Mean and variance of the points of a single game
+    fn mean_and_variance(&self) -> (f64, f64) {
+        let games = self.games().max(1) as f64;
+        let mean = self.points() / games;
+        let variance = (self.wins as f64 * (1.0 - mean).powi(2) + self.draws as f64 * (0.5 - mean).powi(2) + self.losses as f64 * mean.powi(2)) / games;
+        (mean, variance)
+    }
+
+    // Scores of 0 or 1 give infinite Elo differences
+    pub fn elo(&self) -> EloEstimate {
+        let (mean, variance) = self.mean_and_variance();
+        let deviation = (variance / self.games().max(1) as f64).sqrt();
+        EloEstimate {
+            elo: elo_of_score(mean),
+            lower: elo_of_score((mean - CONFIDENCE_QUANTILE * deviation).max(0.0)),
+            upper: elo_of_score((mean + CONFIDENCE_QUANTILE * deviation).min(1.0))
+        }
+    }
+}
+
+impl fmt::Display for MatchScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{} ={} -{}", self.wins, self.draws, self.losses)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SprtStatus {
+    Continue,
+    // The first engine is at most elo0 stronger
+    AcceptH0,
+    // The first engine is at least elo1 stronger
+    AcceptH1
+}
+
+impl fmt::Display for SprtStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Continue => write!(f, "running"),
+            Self::AcceptH0 => write!(f, "H0 accepted"),
+            Self::AcceptH1 => write!(f, "H1 accepted")
+        }
+    }
+}
+
+// Sequential probability ratio test of elo0 against elo1 with the normal approximation of the
+// log likelihood ratio used by fishtest and cutechess.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64
+}
+
+impl Sprt {
+    pub fn log_likelihood_ratio(&self, score: &MatchScore) -> f64 {
+        let (mean, variance) = score.mean_and_variance();
+        if variance <= 0.0 {
+            return 0.0;
+        }
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+        score.games() as f64 * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance)
+    }
+
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    pub fn status(&self, score: &MatchScore) -> SprtStatus {
+        let log_likelihood_ratio = self.log_likelihood_ratio(score);
+        let (lower, upper) = self.bounds();
+        if log_likelihood_ratio >= upper {
+            SprtStatus::AcceptH1
+        } else if log_likelihood_ratio <= lower {
+            SprtStatus::AcceptH0
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentSettings {
+    pub format: TournamentFormat,
+    // Every pair plays one board with both start teams
+    pub game_pairs: usize,
+    // Pair i is played on the board of this seed + i
+    pub first_board_seed: u64,
+    pub time_limit_in_millis: u128,
+    // Pairs played at the same time, the SPRT is checked after every batch
+    pub threads: usize,
+    // Records of every game are written to a subdirectory per pairing
+    pub record_directory: Option<PathBuf>,
+    // Stops a pairing early once the test is decided
+    pub sprt: Option<Sprt>
+}
+
+#[derive(Debug, Clone)]
+pub struct PairingResult {
+    pub first: String,
+    pub second: String,
+    pub score: MatchScore,
+    pub sprt: Option<SprtStatus>
+}
+
+pub struct Tournament {
+    engines: Vec<EngineConfig>,
+    settings: TournamentSettings,
+    results: Vec<PairingResult>
+}
+
+impl Tournament {
+    pub fn new(engines: Vec<EngineConfig>, settings: TournamentSettings) -> anyhow::Result<Self> {
+        if engines.len() < 2 {
+            anyhow::bail!("A tournament needs at least two engines");
+        }
+        if engines.iter().enumerate().any(|(index, engine)| engines[..index].iter().any(|other| other.name == engine.name)) {
+            anyhow::bail!("The names of the engines must be unique");
+        }
+        Ok(Self { engines, settings, results: vec![] })
+    }
+
+    pub fn results(&self) -> &[PairingResult] {
+        &self.results
+    }
+
+    // The points of every engine against all of its opponents
+    pub fn standings(&self) -> Vec<(String, MatchScore)> {
+        let mut standings: Vec<(String, MatchScore)> = self.engines.iter()
+            .map(|engine| {
+                let score = self.results.iter().fold(MatchScore::default(), |score, result| {
+                    if result.first == engine.name {
+                        score.combined(&result.score)
+                    } else if result.second == engine.name {
+                        score.combined(&result.score.reversed())
+                    } else {
+                        score
+                    }
+                });
+                (engine.name.clone(), score)
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            let fraction = |score: &MatchScore| score.points() / score.games().max(1) as f64;
+            fraction(&b.1).total_cmp(&fraction(&a.1))
+        });
+        standings
+    }
+
+    fn create_battle<'factory>(&self, first: &EngineConfig, second: &EngineConfig,
+                               create_first: &'factory MoveGetterFactory<'factory>, create_second: &'factory MoveGetterFactory<'factory>) -> ParallelBattle<'factory> {
+        let time_limit_of = |engine: &EngineConfig| Some(engine.time_limit_in_millis.unwrap_or(self.settings.time_limit_in_millis));
+        let battle = ParallelBattle::between(create_first, create_second)
+            .with_names(&first.name, &second.name)
+            .with_time_limits_in_millis(time_limit_of(first), time_limit_of(second))
+            .with_first_board_seed(self.settings.first_board_seed)
+            .with_threads(self.settings.threads);
+        match &self.settings.record_directory {
+            Some(record_directory) => battle.with_record_directory(&record_directory.join(format!("{}-vs-{}", first.name, second.name))),
+            None => battle
+        }
+    }
+
+    // Plays all pairings one after another, the callback is called after every batch of game pairs.
+    pub fn run(&mut self, on_progress: impl FnMut(&Self) -> anyhow::Result<()>) -> anyhow::Result<()> {
+        self.run_batches(|tournament, first, second, first_pair, pairs| {
+            let create_first = || first.create_move_getter();
+            let create_second = || second.create_move_getter();
+            let records = tournament.create_battle(first, second, &create_first, &create_second).run(first_pair, pairs)?;
+            records.iter().map(|record| Ok(record.result()?.winner)).collect()
+        }, on_progress)
+    }
+
+    // play_batch plays the given game pairs of a pairing and returns the winner of every game,
+    // the first engine of the pairing plays team one.
+    fn run_batches(&mut self,
+                   mut play_batch: impl FnMut(&Self, &EngineConfig, &EngineConfig, usize, usize) -> anyhow::Result<Vec<Option<Team>>>,
+                   mut on_progress: impl FnMut(&Self) -> anyhow::Result<()>) -> anyhow::Result<()> {
+        let batch_size = self.settings.threads.max(1);
+        for (first_index, second_index) in self.settings.format.pairings(self.engines.len()) {
+            let (first, second) = (self.engines[first_index].clone(), self.engines[second_index].clone());
+            self.results.push(PairingResult {
+                first: first.name.clone(),
+                second: second.name.clone(),
+                score: MatchScore::default(),
+                sprt: self.settings.sprt.map(|_| SprtStatus::Continue)
+            });
+
+            for first_pair in (0..self.settings.game_pairs).step_by(batch_size) {
+                let pairs = batch_size.min(self.settings.game_pairs - first_pair);
+                let winners = play_batch(self, &first, &second, first_pair, pairs)
+                    .with_context(|| format!("Games of {} against {} failed", first.name, second.name))?;
+                let pairing = self.results.last_mut().context("No pairing started")?;
+                for winner in winners {
+                    pairing.score.add_result(winner, Team::One);
+                }
+                pairing.sprt = self.settings.sprt.map(|sprt| sprt.status(&pairing.score));
+                let decided = pairing.sprt.is_some_and(|status| status != SprtStatus::Continue);
+                on_progress(self)?;
+                if decided {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Tournament: {}, {} game pairs per pairing, board seeds from {}, {} ms per move",
+            self.settings.format, self.settings.game_pairs, self.settings.first_board_seed, self.settings.time_limit_in_millis)?;
+        writeln!(f, "Engines: {}", self.engines.iter().map(EngineConfig::to_string).collect::<Vec<_>>().join(", "))?;
+        if let Some(sprt) = &self.settings.sprt {
+            let (lower, upper) = sprt.bounds();
+            writeln!(f, "SPRT: elo0 {}, elo1 {}, alpha {}, beta {}, bounds [{:.2}, {:.2}]", sprt.elo0, sprt.elo1, sprt.alpha, sprt.beta, lower, upper)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<32} {:>6} {:>16} {:>8} {:>28}", "pairing", "games", "W/D/L", "score", "elo (95%)")?;
+        for result in &self.results {
+            let score = &result.score;
+            write!(f, "{:<32} {:>6} {:>16} {:>7.1}% {:>28}", format!("{} vs {}", result.first, result.second), score.games(),
+                score.to_string(), 100.0 * score.points() / score.games().max(1) as f64, score.elo().to_string())?;
+            if let (Some(sprt), Some(status)) = (&self.settings.sprt, result.sprt) {
+                write!(f, "  SPRT {} (LLR {:.2})", status, sprt.log_likelihood_ratio(score))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<32} {:>6} {:>16} {:>8} {:>28}", "engine", "games", "W/D/L", "score", "elo vs field (95%)")?;
+        for (name, score) in self.standings() {
+            writeln!(f, "{:<32} {:>6} {:>16} {:>7.1}% {:>28}", name, score.games(), score.to_string(),
+                100.0 * score.points() / score.games().max(1) as f64, score.elo().to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_settings(format: TournamentFormat, game_pairs: usize, sprt: Option<Sprt>) -> TournamentSettings {
+        TournamentSettings { format, game_pairs, first_board_seed: 3, time_limit_in_millis: 20, threads: 1, record_directory: None, sprt }
+    }
+
+    fn create_engines(specs: &[&str]) -> Vec<EngineConfig> {
+        specs.iter().map(|spec| EngineConfig::from_spec(spec).unwrap()).collect()
+    }
+
+    #[test]
+    fn engine_configurations_are_named() {
+        let expected = EngineConfig { name: "new".to_string(), engine: "pvs-weighted".to_string(), time_limit_in_millis: Some(200) };
+        assert_eq!(expected, EngineConfig::from_spec("new=pvs-weighted@200").unwrap());
+        assert_eq!(expected, EngineConfig::from_spec(&expected.to_string()).unwrap());
+        assert_eq!("random", EngineConfig::from_spec("random").unwrap().name);
+        assert_eq!(None, EngineConfig::from_spec("random").unwrap().time_limit_in_millis);
+        assert!(EngineConfig::from_spec("new=stockfish").is_err());
+        assert!(EngineConfig::from_spec("new=random@soon").is_err());
+        assert!(Tournament::new(create_engines(&["random", "random"]), create_settings(TournamentFormat::Gauntlet, 1, None)).is_err());
+    }
+
+    #[test]
+    fn formats_pair_engines() {
+        assert_eq!(vec![(0, 1), (0, 2), (1, 2)], TournamentFormat::RoundRobin.pairings(3));
+        assert_eq!(vec![(0, 1), (0, 2)], TournamentFormat::Gauntlet.pairings(3));
+    }
+
+    #[test]
+    fn elo_follows_score_with_confidence_interval() {
+        let even = MatchScore { wins: 10, draws: 4, losses: 10 };
+        assert!(even.elo().elo.abs() < 1e-9);
+        let better = MatchScore { wins: 30, draws: 0, losses: 10 };
+        let estimate = better.elo();
+        assert!((estimate.elo - 190.85).abs() < 0.01);
+        assert!(estimate.lower < estimate.elo && estimate.elo < estimate.upper);
+        assert!(estimate.lower > 0.0);
+        let more_games = MatchScore { wins: 300, draws: 0, losses: 100 }.elo();
+        assert!(more_games.upper - more_games.lower < estimate.upper - estimate.lower);
+    }
+
+    #[test]
+    fn sprt_accepts_hypothesis_of_clear_results() {
+        let sprt = Sprt { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 };
+        assert_eq!(SprtStatus::Continue, sprt.status(&MatchScore { wins: 6, draws: 2, losses: 5 }));
+        assert_eq!(SprtStatus::AcceptH1, sprt.status(&MatchScore { wins: 600, draws: 100, losses: 400 }));
+        assert_eq!(SprtStatus::AcceptH0, sprt.status(&MatchScore { wins: 400, draws: 100, losses: 600 }));
+        let (lower, upper) = sprt.bounds();
+        assert!((upper - 2.944).abs() < 0.001 && (lower + 2.944).abs() < 0.001);
+    }
+
+    #[test]
+    fn tournament_plays_all_pairings_and_reports_progress() {
+        let mut tournament = Tournament::new(create_engines(&["a=random", "b=random", "c=random"]), create_settings(TournamentFormat::RoundRobin, 2, None)).unwrap();
+        let mut progress = 0;
+        tournament.run(|_| {
+            progress += 1;
+            Ok(())
+        }).unwrap();
+        assert_eq!(6, progress);
+        assert_eq!(3, tournament.results().len());
+        assert!(tournament.results().iter().all(|result| result.score.games() == 4));
+        let standings = tournament.standings();
+        assert_eq!(3, standings.len());
+        assert!(standings.iter().all(|(_, score)| score.games() == 8));
+        let summary = tournament.to_string();
+        assert!(summary.contains("a vs b") && summary.contains("b vs c"));
+    }
+
+    // Adds the batch to the score until the test is decided, returns the amount of batches and the decision.
+    fn batches_until_decided(sprt: &Sprt, batch: MatchScore) -> (u32, SprtStatus) {
+        let mut score = MatchScore::default();
+        for batches in 1..1000 {
+            score = score.combined(&batch);
+            let status = sprt.status(&score);
+            if status != SprtStatus::Continue {
+                return (batches, status);
+            }
+        }
+        panic!("The test was not decided after 1000 batches");
+    }
+
+    #[test]
+    fn sprt_crosses_bounds_after_expected_batches() {
+        let sprt = Sprt { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 };
+        // Every batch of 12 games adds 0.065 to the log likelihood ratio, the upper bound is 2.944
+        assert_eq!((46, SprtStatus::AcceptH1), batches_until_decided(&sprt, MatchScore { wins: 6, draws: 2, losses: 4 }));
+        assert_eq!((38, SprtStatus::AcceptH0), batches_until_decided(&sprt, MatchScore { wins: 4, draws: 2, losses: 6 }));
+    }
+
+    #[test]
+    fn sprt_stops_pairing_after_first_decided_batch() {
+        // Every game adds 0.48 to the log likelihood ratio, so the upper bound of 2.944 is crossed in the fourth pair
+        let sprt = Sprt { elo0: -800.0, elo1: 0.0, alpha: 0.05, beta: 0.05 };
+        let mut tournament = Tournament::new(create_engines(&["first=random", "second=random"]), create_settings(TournamentFormat::Gauntlet, 30, Some(sprt))).unwrap();
+        let mut played_pairs = vec![];
+        let mut statuses = vec![];
+        // The start team wins every game, so the first engine wins one game of every pair
+        tournament.run_batches(|_, _, _, first_pair, pairs| {
+            played_pairs.push(first_pair);
+            Ok([Some(Team::One), Some(Team::Two)].repeat(pairs))
+        }, |tournament| {
+            statuses.push(tournament.results()[0].sprt.unwrap());
+            Ok(())
+        }).unwrap();
+        assert_eq!(vec![0, 1, 2, 3], played_pairs);
+        assert_eq!(vec![SprtStatus::Continue, SprtStatus::Continue, SprtStatus::Continue, SprtStatus::AcceptH1], statuses);
+        assert_eq!(MatchScore { wins: 4, draws: 0, losses: 4 }, tournament.results()[0].score);
+    }
+}
@@ -1,12 +1,88 @@
-use rostware23_lib::game::state::State;
+use rostware23_lib::game::moves::Move;
 
-pub trait TranspositionTable {
-    fn create_for_depth(depth: i32) -> Self;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    // The real rating is at least the stored one (fail high)
+    Lower,
+    // The real rating is at most the stored one (fail low)
+    Upper
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranspositionEntry {
+    pub rating: i32,
+    pub depth: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>
+}
 
-    fn add(&mut self, state: State, rating: i32);
-    fn contains(&self, state: &State) -> bool;
-    fn get(&self, state: &State) -> anyhow::Result<i32>;
+impl TranspositionEntry {
+    pub fn new(rating: i32, depth: i32, bound: Bound, best_move: Option<Move>) -> Self {
+        Self { rating, depth, bound, best_move }
+    }
 
-    fn is_empty(&self) -> bool;
+    // Classifies a fail-soft result by the window it was searched with.
+    pub fn from_search(rating: i32, depth: i32, lower_bound: i32, upper_bound: i32, best_move: Option<Move>) -> Self {
+        let bound = if rating <= lower_bound {
+            Bound::Upper
+        } else if rating >= upper_bound {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        Self::new(rating, depth, bound, best_move)
+    }
+
+    // Returns the stored rating if it can replace a search with the given depth and window.
+    pub fn usable_rating(&self, depth: i32, lower_bound: i32, upper_bound: i32) -> Option<i32> {
+        if self.depth < depth {
+            return None;
+        }
+        match self.bound {
+            Bound::Exact => Some(self.rating),
+            Bound::Lower if self.rating >= upper_bound => Some(self.rating),
+            Bound::Upper if self.rating <= lower_bound => Some(self.rating),
+            _ => None
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_entry_is_usable_for_shallower_and_equal_depth() {
+        let entry = TranspositionEntry::new(7, 3, Bound::Exact, None);
+        assert_eq!(Some(7), entry.usable_rating(2, -100, 100));
+        assert_eq!(Some(7), entry.usable_rating(3, -100, 100));
+    }
+
+    #[test]
+    fn shallow_entry_is_not_usable_for_deeper_search() {
+        let entry = TranspositionEntry::new(7, 1, Bound::Exact, None);
+        assert_eq!(None, entry.usable_rating(2, -100, 100));
+    }
+
+    #[test]
+    fn lower_bound_entry_is_only_usable_when_failing_high() {
+        let entry = TranspositionEntry::new(7, 2, Bound::Lower, None);
+        assert_eq!(None, entry.usable_rating(2, 0, 10));
+        assert_eq!(Some(7), entry.usable_rating(2, 0, 5));
+    }
+
+    #[test]
+    fn upper_bound_entry_is_only_usable_when_failing_low() {
+        let entry = TranspositionEntry::new(7, 2, Bound::Upper, None);
+        assert_eq!(None, entry.usable_rating(2, 0, 10));
+        assert_eq!(Some(7), entry.usable_rating(2, 9, 20));
+    }
+
+    #[test]
+    fn search_results_are_classified_by_their_window() {
+        assert_eq!(Bound::Upper, TranspositionEntry::from_search(0, 1, 0, 5, None).bound);
+        assert_eq!(Bound::Exact, TranspositionEntry::from_search(3, 1, 0, 5, None).bound);
+        assert_eq!(Bound::Lower, TranspositionEntry::from_search(5, 1, 0, 5, None).bound);
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context;
+use rostware23_lib::game::state::State;
+
+use super::{Evaluator, Rater};
+use super::evaluation_trace::{ComponentTrace, EvaluationTrace};
+use super::edge_penguin_penalty::EdgePenguinPenalty;
+use super::fish_difference_rater::FishDifferenceRater;
+use super::penguin_cutoff_rater::PenguinCutOffRater;
+use super::potential_fish_rater::PotentialFishRater;
+use super::quadrant_occupation_rater::QuadrantOccupationRater;
+use super::reachable_fish_rater::ReachableFishRater;
+use super::restricted_reachable_fish_rater::RestrictedReachableFishRater;
+use super::staged_rater::GamePhase;
+use super::territory_rater::TerritoryRater;
+use super::vec_penguin_restrictions::VecPenguinRestrictions;
+
+type RateFunction = fn(&State) -> i32;
+type RatePerTeamFunction = fn(&State) -> Option<(i32, i32)>;
+
+const COMPONENTS: [(&str, RateFunction, RatePerTeamFunction); 8] = [
+    ("fish_difference", FishDifferenceRater::rate, FishDifferenceRater::rate_per_team),
+    ("penguin_cutoff", PenguinCutOffRater::rate, PenguinCutOffRater::rate_per_team),
+    ("quadrant_occupation", QuadrantOccupationRater::rate, QuadrantOccupationRater::rate_per_team),
+    ("restricted_reachable_fish", RestrictedReachableFishRater::<VecPenguinRestrictions>::rate, RestrictedReachableFishRater::<VecPenguinRestrictions>::rate_per_team),
+    ("edge_penguin_penalty", EdgePenguinPenalty::rate, EdgePenguinPenalty::rate_per_team),
+    ("potential_fish", PotentialFishRater::rate, PotentialFishRater::rate_per_team),
+    ("reachable_fish", ReachableFishRater::rate, ReachableFishRater::rate_per_team),
+    ("territory", TerritoryRater::rate, TerritoryRater::rate_per_team)
+];
+
+// Same weights as the CombinedRater
+pub const DEFAULT_CONFIG: &str = "\
+# component early mid end
+fish_difference 20
+penguin_cutoff 2
+quadrant_occupation 10
+restricted_reachable_fish 5
+edge_penguin_penalty 1 0 0
+potential_fish 3
+";
+
+#[derive(Debug, Clone)]
+pub struct WeightedComponent {
+    pub name: &'static str,
+    rate: RateFunction,
+    rate_per_team: RatePerTeamFunction,
+    // Indexed by GamePhase::index
+    pub weights: [i32; 3]
+}
+
+// Components with the same name share the rate function
+impl PartialEq for WeightedComponent {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.weights == other.weights
+    }
+}
+
+impl Eq for WeightedComponent {}
+
+impl WeightedComponent {
+    fn from_name(name: &str, weights: [i32; 3]) -> anyhow::Result<Self> {
+        let (name, rate, rate_per_team) = COMPONENTS.iter()
+            .find(|(component_name, _, _)| *component_name == name)
+            .with_context(|| format!("Unknown component '{}', expected one of {:?}", name, component_names()))?;
+        Ok(Self { name, rate: *rate, rate_per_team: *rate_per_team, weights })
+    }
+}
+
+pub fn component_names() -> Vec<&'static str> {
+    COMPONENTS.iter().map(|(name, _, _)| *name).collect()
+}
+
+// The unweighted rating of a single component.
+pub fn rate_component(name: &str, state: &State) -> anyhow::Result<i32> {
+    let component = WeightedComponent::from_name(name, [1; 3])?;
+    Ok((component.rate)(state))
+}
+
+// Sums the ratings of the configured components, weighted by the phase of the game.
+// Configured with one line per component, holding either a weight for all phases
+// or one weight for each of the early, mid and end game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightedEvaluator {
+    components: Vec<WeightedComponent>
+}
+
+impl WeightedEvaluator {
+    pub fn from_config(config: &str) -> anyhow::Result<Self> {
+        let mut components = vec![];
+        let mut names = HashSet::new();
+        for (line_index, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let component = Self::parse_component(line)
+                .with_context(|| format!("Invalid evaluation config in line {}", line_index + 1))?;
+            if !names.insert(component.name) {
+                anyhow::bail!("Component '{}' is configured twice in line {}", component.name, line_index + 1);
+            }
+            components.push(component);
+        }
+        Ok(Self { components })
+    }
+
+    fn parse_component(line: &str) -> anyhow::Result<WeightedComponent> {
+        let mut tokens = line.split_whitespace();
+        let name = tokens.next().context("Missing component name")?;
+        let weights = tokens.map(|token| token.parse::<i32>().with_context(|| format!("Invalid weight '{}'", token)))
+            .collect::<anyhow::Result<Vec<i32>>>()?;
+        let weights = match weights[..] {
+            [weight] => [weight; 3],
+            [early, mid, end] => [early, mid, end],
+            _ => anyhow::bail!("Expected one weight or three weights for the early, mid and end game")
+        };
+        WeightedComponent::from_name(name, weights)
+    }
+
+    // The weights of all components, ordered by component and phase.
+    pub fn weights(&self) -> Vec<i32> {
+        self.components.iter().flat_map(|component| component.weights).collect()
+    }
+
+    pub fn with_weights(&self, weights: &[i32]) -> anyhow::Result<Self> {
+        if weights.len() != self.components.len() * 3 {
+            anyhow::bail!("Expected {} weights, got {}", self.components.len() * 3, weights.len());
+        }
+        let components = self.components.iter().zip(weights.chunks(3))
+            .map(|(component, weights)| WeightedComponent { weights: [weights[0], weights[1], weights[2]], ..component.clone() })
+            .collect();
+        Ok(Self { components })
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let config = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read evaluation config {}", path.display()))?;
+        Self::from_config(&config)
+    }
+}
+
+impl Default for WeightedEvaluator {
+    fn default() -> Self {
+        Self::from_config(DEFAULT_CONFIG).expect("The default evaluation config is valid")
+    }
+}
+
+impl Evaluator for WeightedEvaluator {
+    fn evaluate(&self, state: &State) -> i32 {
+        let phase = GamePhase::of_state(state).index();
+        self.components.iter()
+            .filter(|component| component.weights[phase] != 0)
+            .map(|component| component.weights[phase] * (component.rate)(state))
+            .sum()
+    }
+
+    fn trace(&self, state: &State) -> EvaluationTrace {
+        let phase = GamePhase::of_state(state).index();
+        let components = self.components.iter()
+            .map(|component| ComponentTrace {
+                name: component.name.to_string(),
+                raw: (component.rate)(state),
+                weight: component.weights[phase],
+                per_team: (component.rate_per_team)(state)
+            })
+            .collect();
+        EvaluationTrace::new(state, components)
+    }
+}
+
+impl fmt::Display for WeightedEvaluator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# component early mid end")?;
+        for component in &self.components {
+            let [early, mid, end] = component.weights;
+            writeln!(f, "{} {} {} {}", component.name, early, mid, end)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rostware23_lib::game::moves::Move;
+    use rostware23_lib::game::state_generator::create_any;
+
+    use super::*;
+    use crate::logic::board_parser::parse_board;
+    use crate::logic::combined_rater::CombinedRater;
+    use crate::logic::MoveGetter;
+    use crate::logic::pvs_getter::PVSMoveGetter;
+    use crate::logic::time_measurer::TimeMeasurer;
+
+    fn create_mid_game_state() -> State {
+        let board_string =
+            "4 3 3 =   = = 3\n\
+         - = P = = G - =\n\
+        - = G = - - P -\n\
+         = =     = - = -\n\
+        - = - =     = =\n\
+         - G - - = P = -\n\
+        = - - = = - = -\n\
+         3 = =   = 3 3 4\n";
+        let mut state = State::from_initial_board_with_start_team_one(parse_board(board_string));
+        state.turn = 10;
+        state
+    }
+
+    #[test]
+    fn default_weighted_evaluator_rates_like_combined_rater() {
+        let evaluator = WeightedEvaluator::default();
+        let mut state = create_any();
+        assert_eq!(CombinedRater::rate(&state), evaluator.evaluate(&state));
+        while !state.is_over() {
+            state = state.with_moveless_player_skipped().unwrap();
+            let possible_move = state.possible_moves().next().unwrap();
+            state.perform_move(possible_move).unwrap();
+            assert_eq!(CombinedRater::rate(&state), evaluator.evaluate(&state));
+        }
+        assert_eq!(CombinedRater::rate(&create_mid_game_state()), evaluator.evaluate(&create_mid_game_state()));
+    }
+
+    #[test]
+    fn weights_of_current_phase_are_used() {
+        let evaluator = WeightedEvaluator::from_config("fish_difference 1 7 100").unwrap();
+        let mut state = create_mid_game_state();
+        state.team_one_fish = 3;
+        assert_eq!(21, evaluator.evaluate(&state));
+        state.turn = 2;
+        assert_eq!(3, evaluator.evaluate(&state));
+    }
+
+    #[test]
+    fn config_with_comments_and_single_weights_is_parsed() {
+        let evaluator = WeightedEvaluator::from_config("# weights\n\nfish_difference 4 # everywhere\npotential_fish 1 2 3\n").unwrap();
+        assert_eq!(2, evaluator.components.len());
+        assert_eq!([4, 4, 4], evaluator.components[0].weights);
+        assert_eq!("potential_fish", evaluator.components[1].name);
+        assert_eq!([1, 2, 3], evaluator.components[1].weights);
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        assert!(WeightedEvaluator::from_config("stockfish_nnue 1").is_err());
+        assert!(WeightedEvaluator::from_config("fish_difference").is_err());
+        assert!(WeightedEvaluator::from_config("fish_difference 1 2").is_err());
+        assert!(WeightedEvaluator::from_config("fish_difference one").is_err());
+        assert!(WeightedEvaluator::from_config("fish_difference 1\nfish_difference 2").is_err());
+    }
+
+    #[test]
+    fn displayed_config_can_be_parsed_again() {
+        let evaluator = WeightedEvaluator::default();
+        assert_eq!(evaluator, WeightedEvaluator::from_config(&evaluator.to_string()).unwrap());
+    }
+
+    #[test]
+    fn weights_can_be_replaced_in_order_of_components() {
+        let evaluator = WeightedEvaluator::from_config("fish_difference 1 2 3\npotential_fish 4").unwrap();
+        assert_eq!(vec![1, 2, 3, 4, 4, 4], evaluator.weights());
+        let changed = evaluator.with_weights(&[6, 5, 4, 3, 2, 1]).unwrap();
+        assert_eq!([3, 2, 1], changed.components[1].weights);
+        assert!(evaluator.with_weights(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn evaluator_can_be_loaded_from_file() {
+        let path = std::env::temp_dir().join("rostware23_weighted_evaluator_test.txt");
+        std::fs::write(&path, DEFAULT_CONFIG).unwrap();
+        let loaded = WeightedEvaluator::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(WeightedEvaluator::default(), loaded.unwrap());
+        assert!(WeightedEvaluator::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn trace_explains_every_component_of_evaluation() {
+        let evaluator = WeightedEvaluator::default();
+        let state = create_mid_game_state();
+        let trace = evaluator.trace(&state);
+        assert_eq!(GamePhase::Mid, trace.phase);
+        assert_eq!(evaluator.evaluate(&state), trace.total());
+        let names: Vec<&str> = trace.components.iter().map(|component| component.name.as_str()).collect();
+        assert_eq!(vec!["fish_difference", "penguin_cutoff", "quadrant_occupation", "restricted_reachable_fish", "edge_penguin_penalty", "potential_fish"], names);
+        let edge_penalty = &trace.components[4];
+        assert_eq!(0, edge_penalty.weight);
+        assert_eq!(0, edge_penalty.contribution());
+        for component in &trace.components {
+            let (own, opponent) = component.per_team.unwrap();
+            assert_eq!(component.raw, own + opponent);
+        }
+    }
+
+    #[test]
+    fn move_getter_accepts_weighted_evaluator() {
+        let state = create_mid_game_state();
+        let move_getter = PVSMoveGetter::with_evaluator(WeightedEvaluator::default()).with_transposition_table_size_in_megabytes(1);
+        let chosen_move: Move = move_getter.get_move(&state, &TimeMeasurer::new(50)).unwrap();
+        assert!(state.possible_moves().any(|possible_move| possible_move == chosen_move));
+    }
+}